@@ -12,12 +12,28 @@ use crate::common::HasAddresser;
 ///
 /// Usage:
 /// ```
-/// let eh = EventHandler::new(&block);
+/// # use substreams_ethereum::{pb::eth::v2::{Block, Log, TransactionTrace}, Event};
+/// # use substreams_helper::event_handler::EventHandler;
+/// # macro_rules! event {
+/// #     ($name:ident) => {
+/// #         struct $name;
+/// #         impl Event for $name {
+/// #             const NAME: &'static str = stringify!($name);
+/// #             fn match_log(_: &Log) -> bool { true }
+/// #             fn decode(_: &Log) -> Result<Self, String> { Ok($name) }
+/// #         }
+/// #     };
+/// # }
+/// # event!(Transfer);
+/// # event!(Approval);
+/// # let block = Block::default();
+/// # let store: Vec<ethabi::ethereum_types::Address> = vec![];
+/// # let mut on_transfer = |_: Transfer, _: &TransactionTrace, _: &Log| {};
+/// # let mut on_approval = |_: Approval, _: &TransactionTrace, _: &Log| {};
+/// let mut eh = EventHandler::new(&block);
 /// eh.filter_by_address(store); // This is optional, if omitted it will handle all events that match the type, independently of the emitting contract.
 /// eh.on::<Transfer, _>(&mut on_transfer);
 /// eh.on::<Approval, _>(&mut on_approval);
-/// eh.on::<Mint, _>(&mut on_mint);
-/// eh.on::<Burn, _>(&mut on_burn);
 /// eh.handle_events(); // this will run all handlers
 /// ```
 ///
@@ -28,18 +44,33 @@ use crate::common::HasAddresser;
 ///
 /// Like so:
 /// ```
-/// let mut balances : Vec<Balance> = vec![];
+/// # use substreams_ethereum::{pb::eth::v2::{Block, Log, TransactionTrace}, Event};
+/// # use substreams_helper::event_handler::EventHandler;
+/// # macro_rules! event {
+/// #     ($name:ident) => {
+/// #         struct $name;
+/// #         impl Event for $name {
+/// #             const NAME: &'static str = stringify!($name);
+/// #             fn match_log(_: &Log) -> bool { true }
+/// #             fn decode(_: &Log) -> Result<Self, String> { Ok($name) }
+/// #         }
+/// #     };
+/// # }
+/// # event!(Transfer);
+/// # event!(Approval);
+/// # let block = Block::default();
+/// let mut transfers: Vec<Vec<u8>> = vec![];
 /// {
-///     let mut on_transfer = |/*...*/| {
-///         // this handler modifies `balances`
-///         balances.push(some_balance);
+///     let mut on_transfer = |_: Transfer, tx: &TransactionTrace, _: &Log| {
+///         // this handler modifies `transfers`
+///         transfers.push(tx.hash.clone());
 ///     };
-///     let eh = EventHandler::new(&block);
+///     let mut eh = EventHandler::new(&block);
 ///     eh.on::<Transfer, _>(&mut on_transfer);
 ///     eh.handle_events();
 /// }
 ///
-/// // do whatever else with `balances` here.
+/// // do whatever else with `transfers` here.
 /// ```
 pub struct EventHandler<'a> {
     block: &'a eth::Block,
@@ -0,0 +1,248 @@
+use anyhow::{anyhow, bail, Context, Result};
+use ethabi::{Contract, Event, EventParam, ParamType};
+use heck::{ToSnakeCase, ToUpperCamelCase};
+
+/// The factory event new components are created from.
+///
+/// Field names follow the naming of the bindings generated by `substreams_ethereum::Abigen`, so
+/// they can be used directly in the generated code.
+#[derive(Debug, PartialEq)]
+pub struct FactoryEvent {
+    /// Name of the generated event struct.
+    pub struct_name: String,
+    /// Human readable event signature, e.g. `PoolCreated(address,address[])`.
+    pub signature: String,
+    /// Hex encoded topic 0 of the event.
+    pub topic: String,
+    /// Field holding the component's contract address.
+    pub component_field: String,
+    /// Fields holding the component's tokens.
+    pub token_fields: Vec<TokenField>,
+}
+
+/// A field of the factory event holding one or multiple token addresses.
+#[derive(Debug, PartialEq)]
+pub enum TokenField {
+    Single(String),
+    List(String),
+}
+
+impl FactoryEvent {
+    /// Loads the event called `event_name` from a json ABI.
+    ///
+    /// ## Arguments
+    /// * `abi` - The json encoded contract ABI.
+    /// * `event_name` - Name of the event as defined in the ABI.
+    pub fn from_abi(abi: &[u8], event_name: &str) -> Result<Self> {
+        let contract = Contract::load(abi).context("Invalid ABI")?;
+        let event = contract
+            .events_by_name(event_name)
+            .map_err(|_| anyhow!("Event {event_name} not found in ABI"))?;
+        match event.as_slice() {
+            [event] => Self::from_event(event),
+            _ => bail!("Event {event_name} is overloaded, which is not supported"),
+        }
+    }
+
+    fn from_event(event: &Event) -> Result<Self> {
+        let fields = event
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(index, param)| (field_name(index, param), param))
+            .collect::<Vec<_>>();
+
+        let is_token = |name: &str| name.starts_with("token");
+        let addresses = fields
+            .iter()
+            .filter(|(_, param)| param.kind == ParamType::Address)
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        let component_field = addresses
+            .iter()
+            .find(|name| name.contains("pool") || name.contains("pair"))
+            .or_else(|| {
+                addresses
+                    .iter()
+                    .find(|name| !is_token(name))
+            })
+            .ok_or_else(|| {
+                anyhow!("Event {} has no address parameter to use as component address", event.name)
+            })?
+            .to_string();
+
+        let token_fields = fields
+            .iter()
+            .filter_map(|(name, param)| match &param.kind {
+                ParamType::Address if is_token(name) && *name != component_field => {
+                    Some(TokenField::Single(name.clone()))
+                }
+                // Indexed dynamic types are only available as hash.
+                ParamType::Array(inner) if **inner == ParamType::Address && !param.indexed => {
+                    Some(TokenField::List(name.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        Ok(Self {
+            struct_name: event.name.to_upper_camel_case(),
+            signature: format!(
+                "{}({})",
+                event.name,
+                event
+                    .inputs
+                    .iter()
+                    .map(|param| param.kind.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            topic: hex::encode(event.signature()),
+            component_field,
+            token_fields,
+        })
+    }
+
+    /// Rust expression building the tokens passed to `ProtocolComponent::with_tokens`.
+    pub fn tokens_expr(&self) -> String {
+        match self.token_fields.as_slice() {
+            [] => "&[] as &[Vec<u8>]".to_string(),
+            [TokenField::List(name)] => format!("&event.{name}"),
+            fields
+                if fields
+                    .iter()
+                    .all(|f| matches!(f, TokenField::Single(_))) =>
+            {
+                let names = fields
+                    .iter()
+                    .map(|f| format!("event.{}", f.name()))
+                    .collect::<Vec<_>>();
+                format!("&[{}]", names.join(", "))
+            }
+            fields => {
+                let parts = fields
+                    .iter()
+                    .map(|f| match f {
+                        TokenField::Single(name) => format!("vec![event.{name}]"),
+                        TokenField::List(name) => format!("event.{name}"),
+                    })
+                    .collect::<Vec<_>>();
+                format!("&[{}].concat()", parts.join(", "))
+            }
+        }
+    }
+}
+
+impl TokenField {
+    fn name(&self) -> &str {
+        match self {
+            TokenField::Single(name) | TokenField::List(name) => name,
+        }
+    }
+}
+
+/// Mirrors the field naming of `substreams_ethereum::Abigen`.
+fn field_name(index: usize, param: &EventParam) -> String {
+    match (param.name.is_empty(), param.indexed) {
+        (true, true) => format!("topic{index}"),
+        (true, false) => format!("param{index}"),
+        (false, _) => param.name.to_snake_case(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABI: &str = r#"[
+        {
+            "type": "event",
+            "name": "PairCreated",
+            "anonymous": false,
+            "inputs": [
+                {"name": "token0", "type": "address", "indexed": true},
+                {"name": "token1", "type": "address", "indexed": true},
+                {"name": "pair", "type": "address", "indexed": false},
+                {"name": "", "type": "uint256", "indexed": false}
+            ]
+        },
+        {
+            "type": "event",
+            "name": "PoolRegistered",
+            "anonymous": false,
+            "inputs": [
+                {"name": "poolAddress", "type": "address", "indexed": true},
+                {"name": "factory", "type": "address", "indexed": true},
+                {"name": "tokens", "type": "address[]", "indexed": false}
+            ]
+        },
+        {
+            "type": "event",
+            "name": "Deployed",
+            "anonymous": false,
+            "inputs": [
+                {"name": "token", "type": "address", "indexed": false}
+            ]
+        }
+    ]"#;
+
+    #[test]
+    fn test_from_abi_single_tokens() {
+        let res = FactoryEvent::from_abi(ABI.as_bytes(), "PairCreated").unwrap();
+
+        assert_eq!(
+            res,
+            FactoryEvent {
+                struct_name: "PairCreated".to_string(),
+                signature: "PairCreated(address,address,address,uint256)".to_string(),
+                topic: "0d3648bd0f6ba80134a33ba9275ac585d9d315f0ad8355cddefde31afa28d0e9"
+                    .to_string(),
+                component_field: "pair".to_string(),
+                token_fields: vec![
+                    TokenField::Single("token0".to_string()),
+                    TokenField::Single("token1".to_string()),
+                ],
+            }
+        );
+        assert_eq!(res.tokens_expr(), "&[event.token0, event.token1]");
+    }
+
+    #[test]
+    fn test_from_abi_token_list() {
+        let res = FactoryEvent::from_abi(ABI.as_bytes(), "PoolRegistered").unwrap();
+
+        assert_eq!(res.component_field, "pool_address");
+        assert_eq!(res.token_fields, vec![TokenField::List("tokens".to_string())]);
+        assert_eq!(res.tokens_expr(), "&event.tokens");
+    }
+
+    #[test]
+    fn test_from_abi_without_component_address() {
+        let res = FactoryEvent::from_abi(ABI.as_bytes(), "Deployed");
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_from_abi_unknown_event() {
+        let res = FactoryEvent::from_abi(ABI.as_bytes(), "PoolCreated");
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_tokens_expr_mixed() {
+        let event = FactoryEvent {
+            struct_name: "Created".to_string(),
+            signature: String::new(),
+            topic: String::new(),
+            component_field: "pool".to_string(),
+            token_fields: vec![
+                TokenField::Single("token".to_string()),
+                TokenField::List("tokens".to_string()),
+            ],
+        };
+
+        assert_eq!(event.tokens_expr(), "&[vec![event.token], event.tokens].concat()");
+    }
+}
@@ -0,0 +1,125 @@
+//! Scaffolding generator for new tycho substreams packages.
+//!
+//! Generates a package for a protocol that deploys its components through a factory contract and
+//! registers it as a member of the substreams workspace. See the Readme for details.
+mod abi;
+mod package;
+mod workspace;
+
+use std::{fs, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+
+use crate::{
+    abi::FactoryEvent,
+    package::{Implementation, PackageConfig},
+};
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Name of the protocol system, e.g. `balancer-v3`.
+    #[arg(long, value_parser = parse_name)]
+    name: String,
+    /// Path to the json ABI of the factory contract.
+    #[arg(long)]
+    abi: PathBuf,
+    /// Name of the factory event emitted for each new component.
+    #[arg(long)]
+    event: String,
+    /// Address of the factory contract.
+    #[arg(long, value_parser = parse_address)]
+    factory_address: [u8; 20],
+    /// How the components are simulated.
+    #[arg(long, value_enum, default_value_t = Implementation::Vm)]
+    implementation: Implementation,
+    /// Chain the package indexes.
+    #[arg(long, default_value = "ethereum", value_parser = parse_name)]
+    chain: String,
+    /// Block the factory was deployed at.
+    #[arg(long, default_value_t = 1)]
+    start_block: u64,
+    /// Root directory of the substreams workspace.
+    #[arg(long, default_value = ".")]
+    workspace: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let abi =
+        fs::read(&cli.abi).with_context(|| format!("Failed to read {}", cli.abi.display()))?;
+    let config = PackageConfig {
+        event: FactoryEvent::from_abi(&abi, &cli.event)?,
+        name: cli.name,
+        chain: cli.chain,
+        implementation: cli.implementation,
+        factory_address: cli.factory_address,
+        start_block: cli.start_block,
+        abi,
+    };
+
+    let manifest_path = cli.workspace.join("Cargo.toml");
+    let manifest = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("No workspace manifest found at {}", manifest_path.display()))?;
+    let package_name = config.package_name();
+    let manifest = workspace::add_member(&manifest, &package_name)?;
+    let fmt_config_path = cli.workspace.join("rustfmt.toml");
+    let fmt_config = fs::read_to_string(&fmt_config_path)
+        .ok()
+        .map(|config| workspace::add_fmt_ignore(&config, &format!("{package_name}/src/abi")))
+        .transpose()?;
+
+    config.write(&cli.workspace.join(&package_name))?;
+    fs::write(&manifest_path, manifest)?;
+    if let Some(fmt_config) = fmt_config {
+        fs::write(&fmt_config_path, fmt_config)?;
+    }
+
+    println!("Created {package_name}, next steps:");
+    println!("  1. Review src/pool_factories.rs and src/modules.rs");
+    println!("  2. cargo test -p {package_name} && cargo +nightly fmt");
+    println!("  3. cd {package_name} && substreams build");
+    Ok(())
+}
+
+/// Names are used for directories, crates and protocol type names.
+fn parse_name(name: &str) -> Result<String> {
+    if name.is_empty() ||
+        !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        bail!("Expected lowercase alphanumeric characters and dashes only");
+    }
+    Ok(name.to_string())
+}
+
+fn parse_address(address: &str) -> Result<[u8; 20]> {
+    let bytes = hex::decode(address.trim_start_matches("0x"))?;
+    bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Expected a 20 byte address"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_name() {
+        assert_eq!(parse_name("balancer-v3").unwrap(), "balancer-v3");
+        assert!(parse_name("Balancer V3").is_err());
+        assert!(parse_name("").is_err());
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            parse_address("0xfafafafafafafafafafafafafafafafafafafafa").unwrap(),
+            [0xfa; 20]
+        );
+        assert!(parse_address("0xfafa").is_err());
+    }
+}
@@ -0,0 +1,195 @@
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use heck::ToSnakeCase;
+
+use crate::abi::FactoryEvent;
+
+/// How the components of the new package are simulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Implementation {
+    /// Components are simulated by executing their contracts, the package emits contract changes.
+    Vm,
+    /// Components are simulated natively from their state attributes.
+    Native,
+}
+
+/// Everything needed to generate a new package.
+pub struct PackageConfig {
+    /// Name of the protocol system, e.g. `balancer-v3`.
+    pub name: String,
+    /// Chain the package indexes, e.g. `ethereum`.
+    pub chain: String,
+    pub implementation: Implementation,
+    pub factory_address: [u8; 20],
+    pub start_block: u64,
+    /// The json ABI of the factory.
+    pub abi: Vec<u8>,
+    pub event: FactoryEvent,
+}
+
+impl PackageConfig {
+    /// Name of the package directory and crate, following the `[CHAIN]-[PROTOCOL_SYSTEM]`
+    /// convention.
+    pub fn package_name(&self) -> String {
+        format!("{}-{}", self.chain, self.name)
+    }
+
+    fn lib_name(&self) -> String {
+        self.package_name().replace('-', "_")
+    }
+
+    fn protocol_type_name(&self) -> String {
+        format!("{}_pool", self.name.to_snake_case())
+    }
+
+    /// Renders all files of the package, paths are relative to the package directory.
+    pub fn render(&self) -> Vec<(&'static str, String)> {
+        let (contract_changes_import, contract_changes, state_helpers) = match self.implementation {
+            Implementation::Vm => (
+                "    contract::extract_contract_changes_builder,\n",
+                include_str!("../templates/contract_changes_vm.rs.tmpl"),
+                "",
+            ),
+            Implementation::Native => (
+                "",
+                include_str!("../templates/contract_changes_native.rs.tmpl"),
+                include_str!("../templates/state_helpers_native.rs.tmpl"),
+            ),
+        };
+        let implementation_type = match self.implementation {
+            Implementation::Vm => "Vm",
+            Implementation::Native => "Custom",
+        };
+        let vars = [
+            ("package_name", self.package_name()),
+            ("lib_name", self.lib_name()),
+            ("protocol_name", self.name.clone()),
+            ("protocol_type_name", self.protocol_type_name()),
+            ("chain", self.chain.clone()),
+            ("start_block", self.start_block.to_string()),
+            ("factory_address", hex::encode(self.factory_address)),
+            ("implementation_type", implementation_type.to_string()),
+            ("event_struct", self.event.struct_name.clone()),
+            ("event_signature", self.event.signature.clone()),
+            ("event_topic", self.event.topic.clone()),
+            ("component_field", self.event.component_field.clone()),
+            ("tokens_expr", self.event.tokens_expr()),
+            ("contract_changes_import", contract_changes_import.to_string()),
+            ("contract_changes", contract_changes.to_string()),
+            ("state_helpers", state_helpers.to_string()),
+        ];
+
+        [
+            ("Cargo.toml", include_str!("../templates/Cargo.toml.tmpl")),
+            ("build.rs", include_str!("../templates/build.rs.tmpl")),
+            ("buf.gen.yaml", include_str!("../templates/buf.gen.yaml")),
+            ("substreams.yaml", include_str!("../templates/substreams.yaml.tmpl")),
+            (
+                "integration_test.tycho.yaml",
+                include_str!("../templates/integration_test.tycho.yaml.tmpl"),
+            ),
+            ("src/lib.rs", include_str!("../templates/lib.rs.tmpl")),
+            ("src/modules.rs", include_str!("../templates/modules.rs.tmpl")),
+            ("src/pool_factories.rs", include_str!("../templates/pool_factories.rs.tmpl")),
+            ("src/abi/mod.rs", include_str!("../templates/abi_mod.rs.tmpl")),
+        ]
+        .into_iter()
+        .map(|(path, template)| (path, render(template, &vars)))
+        .collect()
+    }
+
+    /// Writes the package into `dir`.
+    ///
+    /// ## Errors
+    /// In case `dir` already exists, existing packages are never overwritten.
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        if dir.exists() {
+            bail!("{} already exists", dir.display());
+        }
+        fs::create_dir_all(dir.join("src/abi"))?;
+        fs::create_dir_all(dir.join("abi"))?;
+        fs::write(dir.join("abi/factory.json"), &self.abi)?;
+        for (path, content) in self.render() {
+            fs::write(dir.join(path), content)
+                .with_context(|| format!("Failed to write {path}"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Replaces all `{{key}}` placeholders in `template`.
+fn render(template: &str, vars: &[(&str, String)]) -> String {
+    vars.iter()
+        .fold(template.to_string(), |acc, (key, value)| {
+            acc.replace(&format!("{{{{{key}}}}}"), value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABI: &str = r#"[{
+        "type": "event",
+        "name": "PoolCreated",
+        "anonymous": false,
+        "inputs": [
+            {"name": "pool", "type": "address", "indexed": true},
+            {"name": "tokens", "type": "address[]", "indexed": false}
+        ]
+    }]"#;
+
+    fn config(implementation: Implementation) -> PackageConfig {
+        PackageConfig {
+            name: "my-protocol".to_string(),
+            chain: "ethereum".to_string(),
+            implementation,
+            factory_address: [0xfa; 20],
+            start_block: 123,
+            abi: ABI.as_bytes().to_vec(),
+            event: FactoryEvent::from_abi(ABI.as_bytes(), "PoolCreated").unwrap(),
+        }
+    }
+
+    fn file(files: &[(&str, String)], path: &str) -> String {
+        files
+            .iter()
+            .find(|(p, _)| *p == path)
+            .map(|(_, content)| content.clone())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_render_vm() {
+        let files = config(Implementation::Vm).render();
+
+        assert!(files
+            .iter()
+            .all(|(_, content)| !content.contains("{{")));
+        assert!(file(&files, "Cargo.toml").contains("name = \"ethereum-my-protocol\""));
+        assert!(file(&files, "substreams.yaml").contains("release/ethereum_my_protocol.wasm"));
+        assert!(file(&files, "substreams.yaml").contains(
+            "factory_address=fafafafafafafafafafafafafafafafafafafafa&protocol_type_name=my_protocol_pool"
+        ));
+        let factories = file(&files, "src/pool_factories.rs");
+        assert!(factories.contains("on_event(move |event: PoolCreated, ctx|"));
+        assert!(factories.contains(".with_tokens(&event.tokens)"));
+        assert!(factories.contains("ImplementationType::Vm"));
+        assert!(file(&files, "src/modules.rs").contains("extract_contract_changes_builder("));
+    }
+
+    #[test]
+    fn test_render_native() {
+        let files = config(Implementation::Native).render();
+
+        assert!(files
+            .iter()
+            .all(|(_, content)| !content.contains("{{")));
+        assert!(file(&files, "src/pool_factories.rs").contains("ImplementationType::Custom"));
+        let modules = file(&files, "src/modules.rs");
+        assert!(!modules.contains("extract_contract_changes_builder"));
+        assert!(modules.contains("fn component_state_changes("));
+    }
+}
@@ -0,0 +1,78 @@
+use anyhow::{bail, Context, Result};
+
+/// Adds `member` to the `members` list of a workspace `Cargo.toml`.
+///
+/// The manifest is edited textually to keep its formatting and comments. Members already part of
+/// the workspace are left untouched.
+///
+/// ## Arguments
+/// * `manifest` - Content of the workspace `Cargo.toml`.
+/// * `member` - Path of the new member relative to the workspace root.
+pub fn add_member(manifest: &str, member: &str) -> Result<String> {
+    add_list_entry(manifest, "members", member)
+}
+
+/// Adds `path` to the `ignore` list of the workspace `rustfmt.toml`.
+///
+/// Used to exclude generated ABI bindings from formatting.
+pub fn add_fmt_ignore(config: &str, path: &str) -> Result<String> {
+    add_list_entry(config, "ignore", path)
+}
+
+/// Appends a string entry to a multiline toml list, unless it is already present.
+fn add_list_entry(content: &str, key: &str, value: &str) -> Result<String> {
+    let start = content
+        .find(&format!("{key} = ["))
+        .with_context(|| format!("No {key} list found"))?;
+    let end = start +
+        content[start..]
+            .find(']')
+            .with_context(|| format!("Unterminated {key} list"))?;
+
+    let entry = format!("\"{value}\"");
+    if content[start..end].contains(&entry) {
+        return Ok(content.to_string());
+    }
+    let (head, tail) = content.split_at(end);
+    if !head.ends_with('\n') {
+        bail!("Expected one entry per line in the {key} list");
+    }
+    Ok(format!("{head}    {entry},\n{tail}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str =
+        "[workspace]\nmembers = [\n    \"ethereum-template\",\n]\nresolver = \"2\"\n";
+
+    #[test]
+    fn test_add_member() {
+        let res = add_member(MANIFEST, "ethereum-new").unwrap();
+
+        assert_eq!(
+            res,
+            "[workspace]\nmembers = [\n    \"ethereum-template\",\n    \"ethereum-new\",\n]\nresolver = \"2\"\n"
+        );
+    }
+
+    #[test]
+    fn test_add_existing_member() {
+        let res = add_member(MANIFEST, "ethereum-template").unwrap();
+
+        assert_eq!(res, MANIFEST);
+    }
+
+    #[test]
+    fn test_add_fmt_ignore() {
+        let config = "chain_width = 40\nignore = [\n    \"ethereum-template/src/abi\",\n]\n";
+
+        let res = add_fmt_ignore(config, "ethereum-new/src/abi").unwrap();
+
+        assert_eq!(
+            res,
+            "chain_width = 40\nignore = [\n    \"ethereum-template/src/abi\",\n    \"ethereum-new/src/abi\",\n]\n"
+        );
+    }
+}
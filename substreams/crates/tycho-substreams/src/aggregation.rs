@@ -0,0 +1,414 @@
+//! Block level aggregation of `BlockChanges`.
+//!
+//! By default all packages emit one `TransactionChanges` per transaction that touched a tracked
+//! component. Consumers that are only interested in the state at the end of a block can opt into
+//! collapsing these into a single block level change set. The aggregated set contains:
+//!
+//! - all protocol components created within the block, each one keeping its creating transaction,
+//! - the last value for each attribute, balance and storage slot,
+//! - the last transaction of the block as the transaction of the aggregated change set.
+//!
+//! Packages usually expose this through a `params` switch on their final output module, see
+//! [`AggregationMode::from_params`].
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Result};
+
+use crate::{
+    models::InterimEntityChanges,
+    pb::tycho::evm::v1::{
+        BalanceChange, BlockChanges, ChangeType, ContractChange, ContractSlot, EntityChanges,
+        ProtocolComponent, TransactionChanges,
+    },
+};
+
+/// The params key used to select the aggregation mode.
+pub const AGGREGATION_PARAM: &str = "aggregation";
+
+/// Granularity of the changes emitted by a package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AggregationMode {
+    /// Emit one `TransactionChanges` per transaction (default).
+    #[default]
+    Transaction,
+    /// Collapse all changes of a block into a single `TransactionChanges`.
+    Block,
+}
+
+impl AggregationMode {
+    /// Parses the aggregation mode from a query string encoded params string.
+    ///
+    /// Looks for the `aggregation` key, which can be either `transaction` or `block`. Any other
+    /// keys are ignored, so the mode can be added to existing module params. If the key is
+    /// missing, `AggregationMode::Transaction` is returned.
+    ///
+    /// ## Errors
+    /// In case the `aggregation` value is not one of the supported modes.
+    pub fn from_params(params: &str) -> Result<Self> {
+        params
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == AGGREGATION_PARAM)
+            .map(|(_, value)| match value {
+                "transaction" => Ok(AggregationMode::Transaction),
+                "block" => Ok(AggregationMode::Block),
+                other => bail!("Unsupported aggregation mode: {other}"),
+            })
+            .unwrap_or(Ok(AggregationMode::default()))
+    }
+
+    /// Applies the aggregation mode to the given changes.
+    pub fn apply(self, changes: BlockChanges) -> BlockChanges {
+        match self {
+            AggregationMode::Transaction => changes,
+            AggregationMode::Block => aggregate_block_changes(changes),
+        }
+    }
+}
+
+/// Collapses all transaction changes of a block into a single block level change set.
+///
+/// Changes are applied in transaction index order and the last value wins for each attribute,
+/// balance, storage slot, contract balance and contract code. Protocol components are kept as is,
+/// including the transaction that created them. Attributes that are created and deleted within the
+/// block are dropped, attributes that are created and later updated within the block are still
+/// emitted as a creation.
+///
+/// The aggregated `TransactionChanges` is associated with the last transaction of the block.
+/// Returns the input unchanged if it contains no transaction changes.
+pub fn aggregate_block_changes(block_changes: BlockChanges) -> BlockChanges {
+    let mut changes = block_changes.changes;
+    changes.sort_by_key(|change| {
+        change
+            .tx
+            .as_ref()
+            .map(|tx| tx.index)
+            .unwrap_or_default()
+    });
+
+    let last_tx = match changes
+        .iter()
+        .rev()
+        .find_map(|change| change.tx.clone())
+    {
+        Some(tx) => tx,
+        None => return BlockChanges { block: block_changes.block, changes },
+    };
+
+    let mut components: Vec<ProtocolComponent> = Vec::new();
+    let mut contracts: HashMap<Vec<u8>, AggregatedContractChange> = HashMap::new();
+    let mut entities: HashMap<String, InterimEntityChanges> = HashMap::new();
+    let mut created_attributes: HashSet<(String, String)> = HashSet::new();
+    let mut balances: HashMap<(Vec<u8>, Vec<u8>), BalanceChange> = HashMap::new();
+
+    for change in changes {
+        for component in change.component_changes {
+            if !components
+                .iter()
+                .any(|c| c.id == component.id)
+            {
+                components.push(component);
+            }
+        }
+        for contract_change in change.contract_changes.iter() {
+            contracts
+                .entry(contract_change.address.clone())
+                .or_insert_with(|| AggregatedContractChange::new(contract_change))
+                .merge(contract_change);
+        }
+        for entity_change in change.entity_changes.iter() {
+            let entry = entities
+                .entry(entity_change.component_id.clone())
+                .or_insert_with(|| InterimEntityChanges::new(&entity_change.component_id));
+            for attr in entity_change.attributes.iter() {
+                let key = (entity_change.component_id.clone(), attr.name.clone());
+                let mut attr = attr.clone();
+                // The attribute does not exist before the block, so a later update within the
+                // block still has to be emitted as its creation.
+                if attr.change == i32::from(ChangeType::Creation) {
+                    created_attributes.insert(key);
+                } else if attr.change == i32::from(ChangeType::Update) &&
+                    created_attributes.contains(&key)
+                {
+                    attr.change = ChangeType::Creation.into();
+                }
+                entry.set_attribute(&attr);
+            }
+        }
+        for balance_change in change.balance_changes {
+            balances.insert(
+                (balance_change.component_id.clone(), balance_change.token.clone()),
+                balance_change,
+            );
+        }
+    }
+
+    let mut contract_changes = contracts
+        .into_values()
+        .map(ContractChange::from)
+        .collect::<Vec<_>>();
+    contract_changes.sort_by(|a, b| a.address.cmp(&b.address));
+
+    let mut entity_changes = entities
+        .into_values()
+        .filter_map(Option::<EntityChanges>::from)
+        .map(|mut ec| {
+            ec.attributes
+                .sort_by(|a, b| a.name.cmp(&b.name));
+            ec
+        })
+        .collect::<Vec<_>>();
+    entity_changes.sort_by(|a, b| a.component_id.cmp(&b.component_id));
+
+    let mut balance_changes = balances
+        .into_values()
+        .collect::<Vec<_>>();
+    balance_changes.sort_by(|a, b| (&a.component_id, &a.token).cmp(&(&b.component_id, &b.token)));
+
+    BlockChanges {
+        block: block_changes.block,
+        changes: vec![TransactionChanges {
+            tx: Some(last_tx),
+            contract_changes,
+            entity_changes,
+            component_changes: components,
+            balance_changes,
+        }],
+    }
+}
+
+/// Accumulates multiple `ContractChange`s of the same contract.
+struct AggregatedContractChange {
+    address: Vec<u8>,
+    balance: Vec<u8>,
+    code: Vec<u8>,
+    slots: HashMap<Vec<u8>, Vec<u8>>,
+    change: i32,
+}
+
+impl AggregatedContractChange {
+    fn new(change: &ContractChange) -> Self {
+        Self {
+            address: change.address.clone(),
+            balance: vec![],
+            code: vec![],
+            slots: HashMap::new(),
+            change: change.change,
+        }
+    }
+
+    fn merge(&mut self, change: &ContractChange) {
+        if !change.balance.is_empty() {
+            self.balance = change.balance.clone();
+        }
+        if !change.code.is_empty() {
+            self.code = change.code.clone();
+        }
+        for slot in change.slots.iter() {
+            self.slots
+                .insert(slot.slot.clone(), slot.value.clone());
+        }
+        // A creation stays a creation, a later deletion overrides any previous change type.
+        if change.change == i32::from(ChangeType::Deletion) {
+            self.change = change.change;
+        }
+    }
+}
+
+impl From<AggregatedContractChange> for ContractChange {
+    fn from(value: AggregatedContractChange) -> Self {
+        let mut slots = value
+            .slots
+            .into_iter()
+            .map(|(slot, value)| ContractSlot { slot, value })
+            .collect::<Vec<_>>();
+        slots.sort_by(|a, b| a.slot.cmp(&b.slot));
+        ContractChange {
+            address: value.address,
+            balance: value.balance,
+            code: value.code,
+            slots,
+            change: value.change,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::tycho::evm::v1::{Attribute, Block, Transaction};
+
+    fn tx(index: u64) -> Transaction {
+        Transaction { hash: vec![index as u8], from: vec![], to: vec![], index }
+    }
+
+    fn attribute(name: &str, value: u8, change: ChangeType) -> Attribute {
+        Attribute { name: name.to_string(), value: vec![value], change: change.into() }
+    }
+
+    fn balance(component: &str, token: u8, value: u8) -> BalanceChange {
+        BalanceChange {
+            token: vec![token],
+            balance: vec![value],
+            component_id: component.as_bytes().to_vec(),
+        }
+    }
+
+    fn block_changes() -> BlockChanges {
+        BlockChanges {
+            block: Some(Block { hash: vec![1], parent_hash: vec![0], number: 1, ts: 1 }),
+            changes: vec![
+                TransactionChanges {
+                    tx: Some(tx(3)),
+                    contract_changes: vec![ContractChange {
+                        address: vec![0xaa],
+                        balance: vec![],
+                        code: vec![],
+                        slots: vec![
+                            ContractSlot { slot: vec![1], value: vec![3] },
+                            ContractSlot { slot: vec![2], value: vec![3] },
+                        ],
+                        change: ChangeType::Update.into(),
+                    }],
+                    entity_changes: vec![EntityChanges {
+                        component_id: "pool".to_string(),
+                        attributes: vec![
+                            attribute("reserve", 3, ChangeType::Update),
+                            attribute("tmp", 0, ChangeType::Deletion),
+                        ],
+                    }],
+                    component_changes: vec![],
+                    balance_changes: vec![balance("pool", 1, 3)],
+                },
+                TransactionChanges {
+                    tx: Some(tx(1)),
+                    contract_changes: vec![ContractChange {
+                        address: vec![0xaa],
+                        balance: vec![7],
+                        code: vec![0x60],
+                        slots: vec![ContractSlot { slot: vec![1], value: vec![1] }],
+                        change: ChangeType::Creation.into(),
+                    }],
+                    entity_changes: vec![EntityChanges {
+                        component_id: "pool".to_string(),
+                        attributes: vec![
+                            attribute("reserve", 1, ChangeType::Creation),
+                            attribute("tmp", 1, ChangeType::Creation),
+                        ],
+                    }],
+                    component_changes: vec![ProtocolComponent::new("pool", &tx(1))],
+                    balance_changes: vec![balance("pool", 1, 1), balance("pool", 2, 1)],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_from_params() {
+        assert_eq!(AggregationMode::from_params("").unwrap(), AggregationMode::Transaction);
+        assert_eq!(
+            AggregationMode::from_params("aggregation=transaction").unwrap(),
+            AggregationMode::Transaction
+        );
+        assert_eq!(
+            AggregationMode::from_params("factory_address=abc&aggregation=block").unwrap(),
+            AggregationMode::Block
+        );
+    }
+
+    #[test]
+    fn test_from_params_invalid() {
+        let res = AggregationMode::from_params("aggregation=epoch");
+
+        assert_eq!(res.unwrap_err().to_string(), "Unsupported aggregation mode: epoch");
+    }
+
+    #[test]
+    fn test_transaction_mode_is_noop() {
+        let changes = block_changes();
+
+        let res = AggregationMode::Transaction.apply(changes.clone());
+
+        assert_eq!(res, changes);
+    }
+
+    #[test]
+    fn test_aggregate_block_changes() {
+        let exp = BlockChanges {
+            block: Some(Block { hash: vec![1], parent_hash: vec![0], number: 1, ts: 1 }),
+            changes: vec![TransactionChanges {
+                tx: Some(tx(3)),
+                contract_changes: vec![ContractChange {
+                    address: vec![0xaa],
+                    balance: vec![7],
+                    code: vec![0x60],
+                    slots: vec![
+                        ContractSlot { slot: vec![1], value: vec![3] },
+                        ContractSlot { slot: vec![2], value: vec![3] },
+                    ],
+                    change: ChangeType::Creation.into(),
+                }],
+                entity_changes: vec![EntityChanges {
+                    component_id: "pool".to_string(),
+                    attributes: vec![attribute("reserve", 3, ChangeType::Creation)],
+                }],
+                component_changes: vec![ProtocolComponent::new("pool", &tx(1))],
+                balance_changes: vec![balance("pool", 1, 3), balance("pool", 2, 1)],
+            }],
+        };
+
+        let res = aggregate_block_changes(block_changes());
+
+        assert_eq!(res, exp);
+    }
+
+    #[test]
+    fn test_aggregate_update_after_creation_stays_creation() {
+        let entity_changes = |index: u64, attributes: Vec<Attribute>| TransactionChanges {
+            tx: Some(tx(index)),
+            entity_changes: vec![EntityChanges { component_id: "pool".to_string(), attributes }],
+            ..Default::default()
+        };
+        let changes = BlockChanges {
+            block: None,
+            changes: vec![
+                entity_changes(
+                    1,
+                    vec![
+                        attribute("created", 1, ChangeType::Creation),
+                        attribute("existing", 1, ChangeType::Update),
+                    ],
+                ),
+                entity_changes(
+                    2,
+                    vec![
+                        attribute("created", 2, ChangeType::Update),
+                        attribute("existing", 2, ChangeType::Update),
+                    ],
+                ),
+            ],
+        };
+
+        let res = aggregate_block_changes(changes);
+
+        assert_eq!(
+            res.changes[0].entity_changes,
+            vec![EntityChanges {
+                component_id: "pool".to_string(),
+                attributes: vec![
+                    attribute("created", 2, ChangeType::Creation),
+                    attribute("existing", 2, ChangeType::Update),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_empty_block_changes() {
+        let changes = BlockChanges { block: None, changes: vec![] };
+
+        let res = aggregate_block_changes(changes.clone());
+
+        assert_eq!(res, changes);
+    }
+}
@@ -193,6 +193,9 @@ pub fn aggregate_balances_changes(
 /// # Example
 ///
 /// ```
+/// # use substreams_ethereum::pb::eth::v2::TransactionTrace;
+/// # use tycho_substreams::balances::extract_balance_deltas_from_tx;
+/// # let tx = TransactionTrace::default();
 /// let predicate = |log_address: &[u8], transfer_address: &[u8]| -> bool {
 ///     // Your predicate logic here, e.g., checking if the address matches a specific pattern.
 ///     true
@@ -0,0 +1,627 @@
+//! Declarative discovery of protocol components deployed by factories.
+//!
+//! Most protocols deploy their components through one or multiple factory contracts. Instead of
+//! hand writing the matching logic, a package declares which factories exist at which address,
+//! which event and/or call to decode and how to map the decoded values to a `ProtocolComponent`:
+//!
+//! ```
+//! # use substreams_ethereum::pb::eth::v2::{Call, Log};
+//! # use substreams_ethereum::{Event, Function};
+//! # macro_rules! abi {
+//! #     ($module:ident { $($field:ident),* }) => {
+//! #         pub mod $module {
+//! #             pub mod events {
+//! #                 pub struct PoolCreated { $(pub $field: Vec<u8>),* }
+//! #             }
+//! #             pub mod functions {
+//! #                 pub struct Create { $(pub $field: Vec<u8>),* }
+//! #             }
+//! #             impl super::Event for events::PoolCreated {
+//! #                 const NAME: &'static str = "PoolCreated";
+//! #                 fn match_log(_: &super::Log) -> bool { false }
+//! #                 fn decode(_: &super::Log) -> Result<Self, String> { unimplemented!() }
+//! #             }
+//! #             impl super::Function for functions::Create {
+//! #                 const NAME: &'static str = "create";
+//! #                 fn match_call(_: &super::Call) -> bool { false }
+//! #                 fn decode(_: &super::Call) -> Result<Self, String> { unimplemented!() }
+//! #                 fn encode(&self) -> Vec<u8> { unimplemented!() }
+//! #             }
+//! #         }
+//! #     };
+//! # }
+//! # abi!(v1 { pool, token0, token1 });
+//! # abi!(v2 { pool, token0, token1, fee });
+//! # abi!(v3 { pool, token0, token1 });
+//! # fn main() {}
+//! # const V1_FACTORY: [u8; 20] = [1; 20];
+//! # const V2_FACTORY: [u8; 20] = [2; 20];
+//! # const V3_FACTORY: [u8; 20] = [3; 20];
+//! use tycho_substreams::{
+//!     factories::{on_call, on_event, on_event_and_call, FactoryRegistry},
+//!     prelude::*,
+//! };
+//!
+//! pub fn factories() -> FactoryRegistry {
+//!     FactoryRegistry::new()
+//!         .with_factory(
+//!             &V1_FACTORY,
+//!             on_event(|event: v1::events::PoolCreated, ctx| {
+//!                 Some(
+//!                     ProtocolComponent::at_contract(&event.pool, &ctx.tx.into())
+//!                         .with_tokens(&[event.token0, event.token1])
+//!                         .as_swap_type("my_pool", ImplementationType::Vm),
+//!                 )
+//!             }),
+//!         )
+//!         .with_factory(
+//!             &V2_FACTORY,
+//!             on_event_and_call(|event: v2::events::PoolCreated, call: v2::functions::Create, ctx| {
+//!                 Some(
+//!                     ProtocolComponent::at_contract(&event.pool, &ctx.tx.into())
+//!                         .with_tokens(&[event.token0, event.token1])
+//!                         .with_attributes(&[("fee", call.fee)])
+//!                         .as_swap_type("my_pool", ImplementationType::Vm),
+//!                 )
+//!             }),
+//!         )
+//!         // Factories emitting no event are matched on their calls only.
+//!         .with_factory(
+//!             &V3_FACTORY,
+//!             on_call(|call: v3::functions::Create, ctx| {
+//!                 Some(
+//!                     ProtocolComponent::at_contract(&call.pool, &ctx.tx.into())
+//!                         .with_tokens(&[call.token0, call.token1])
+//!                         .as_swap_type("my_pool", ImplementationType::Vm),
+//!                 )
+//!             }),
+//!         )
+//! }
+//! ```
+//!
+//! The [`component_factory_handlers!`](crate::component_factory_handlers) macro then generates
+//! the `map` and `store` handlers for the registry.
+use std::{collections::HashMap, marker::PhantomData, rc::Rc};
+
+use substreams::store::StoreSet;
+use substreams_ethereum::{
+    pb::eth::v2::{Block, Call, Log, TransactionTrace},
+    Event, Function,
+};
+
+use crate::pb::tycho::evm::v1::{
+    BlockTransactionProtocolComponents, ProtocolComponent, TransactionProtocolComponents,
+};
+
+/// Everything a factory may use to build a component.
+pub struct FactoryContext<'a> {
+    /// Address of the factory, this is the address that emitted the log.
+    pub factory_address: &'a [u8],
+    /// The log emitted by the factory.
+    pub log: &'a Log,
+    /// The call that emitted the log.
+    pub call: &'a Call,
+    /// The transaction containing the call.
+    pub tx: &'a TransactionTrace,
+}
+
+/// Everything a factory matching calls only may use to build a component.
+pub struct CallContext<'a> {
+    /// Address of the factory, this is the address that was called.
+    pub factory_address: &'a [u8],
+    /// The call to the factory.
+    pub call: &'a Call,
+    /// The transaction containing the call.
+    pub tx: &'a TransactionTrace,
+}
+
+/// A factory contract deploying protocol components.
+///
+/// Factories announcing new components through an event implement [`create_component`], factories
+/// emitting no event implement [`create_component_from_call`] instead.
+///
+/// [`create_component`]: ComponentFactory::create_component
+/// [`create_component_from_call`]: ComponentFactory::create_component_from_call
+pub trait ComponentFactory {
+    /// Creates a component if the log was emitted for a new component.
+    fn create_component(&self, _ctx: &FactoryContext) -> Option<ProtocolComponent> {
+        None
+    }
+
+    /// Creates a component if the call deployed a new component.
+    fn create_component_from_call(&self, _ctx: &CallContext) -> Option<ProtocolComponent> {
+        None
+    }
+}
+
+/// A factory creating components from a single event, see [`on_event`].
+pub struct EventFactory<E, F> {
+    mapper: F,
+    _event: PhantomData<E>,
+}
+
+impl<E, F> ComponentFactory for EventFactory<E, F>
+where
+    E: Event,
+    F: Fn(E, &FactoryContext) -> Option<ProtocolComponent>,
+{
+    fn create_component(&self, ctx: &FactoryContext) -> Option<ProtocolComponent> {
+        (self.mapper)(E::match_and_decode(ctx.log)?, ctx)
+    }
+}
+
+/// A factory creating components from an event and the call emitting it, see
+/// [`on_event_and_call`].
+pub struct EventCallFactory<E, C, F> {
+    mapper: F,
+    _event: PhantomData<(E, C)>,
+}
+
+impl<E, C, F> ComponentFactory for EventCallFactory<E, C, F>
+where
+    E: Event,
+    C: Function,
+    F: Fn(E, C, &FactoryContext) -> Option<ProtocolComponent>,
+{
+    fn create_component(&self, ctx: &FactoryContext) -> Option<ProtocolComponent> {
+        let event = E::match_and_decode(ctx.log)?;
+        let call = C::match_and_decode(ctx.call)?;
+        (self.mapper)(event, call, ctx)
+    }
+}
+
+/// A factory creating components from a single call, see [`on_call`].
+pub struct CallFactory<C, F> {
+    mapper: F,
+    _call: PhantomData<C>,
+}
+
+impl<C, F> ComponentFactory for CallFactory<C, F>
+where
+    C: Function,
+    F: Fn(C, &CallContext) -> Option<ProtocolComponent>,
+{
+    fn create_component_from_call(&self, ctx: &CallContext) -> Option<ProtocolComponent> {
+        (self.mapper)(C::match_and_decode(ctx.call)?, ctx)
+    }
+}
+
+/// Creates components from the factory event `E`.
+///
+/// The mapper is only invoked for logs that successfully decode as `E`.
+pub fn on_event<E, F>(mapper: F) -> EventFactory<E, F>
+where
+    E: Event,
+    F: Fn(E, &FactoryContext) -> Option<ProtocolComponent>,
+{
+    EventFactory { mapper, _event: PhantomData }
+}
+
+/// Creates components from the factory event `E` and the factory call `C` that emitted it.
+///
+/// Useful if the event does not contain all information about the component, e.g. its
+/// parameters. The mapper is only invoked if both the log and the call decode successfully.
+pub fn on_event_and_call<E, C, F>(mapper: F) -> EventCallFactory<E, C, F>
+where
+    E: Event,
+    C: Function,
+    F: Fn(E, C, &FactoryContext) -> Option<ProtocolComponent>,
+{
+    EventCallFactory { mapper, _event: PhantomData }
+}
+
+/// Creates components from the factory call `C`, for factories that emit no creation event.
+///
+/// The mapper is only invoked for successful calls that decode as `C`.
+pub fn on_call<C, F>(mapper: F) -> CallFactory<C, F>
+where
+    C: Function,
+    F: Fn(C, &CallContext) -> Option<ProtocolComponent>,
+{
+    CallFactory { mapper, _call: PhantomData }
+}
+
+/// Dispatches factory logs and calls to the factories registered at the emitting, respectively
+/// called, address.
+///
+/// Multiple factories can be registered at the same address, e.g. if a factory deploys different
+/// kinds of components. They are tried in registration order and the first component created
+/// wins. Registering the same factory at multiple addresses allows to support several deployments
+/// of the same factory version.
+#[derive(Default)]
+pub struct FactoryRegistry {
+    factories: HashMap<Vec<u8>, Vec<Rc<dyn ComponentFactory>>>,
+}
+
+impl FactoryRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `factory` at `address`.
+    pub fn with_factory(self, address: &[u8], factory: impl ComponentFactory + 'static) -> Self {
+        self.with_factories(&[address], factory)
+    }
+
+    /// Registers the same `factory` at all `addresses`.
+    pub fn with_factories<A: AsRef<[u8]>>(
+        mut self,
+        addresses: &[A],
+        factory: impl ComponentFactory + 'static,
+    ) -> Self {
+        let factory: Rc<dyn ComponentFactory> = Rc::new(factory);
+        for address in addresses {
+            self.factories
+                .entry(address.as_ref().to_vec())
+                .or_default()
+                .push(factory.clone());
+        }
+        self
+    }
+
+    /// Whether any factory is registered at `address`.
+    pub fn contains(&self, address: &[u8]) -> bool {
+        self.factories.contains_key(address)
+    }
+
+    /// Creates a component if the log was emitted by a registered factory for a new component.
+    pub fn create_component(
+        &self,
+        log: &Log,
+        call: &Call,
+        tx: &TransactionTrace,
+    ) -> Option<ProtocolComponent> {
+        let ctx = FactoryContext { factory_address: &log.address, log, call, tx };
+        self.factories
+            .get(&log.address)?
+            .iter()
+            .find_map(|factory| factory.create_component(&ctx))
+    }
+
+    /// Creates a component if the call to a registered factory deployed a new component.
+    pub fn create_component_from_call(
+        &self,
+        call: &Call,
+        tx: &TransactionTrace,
+    ) -> Option<ProtocolComponent> {
+        if call.state_reverted {
+            return None;
+        }
+        let ctx = CallContext { factory_address: &call.address, call, tx };
+        self.factories
+            .get(&call.address)?
+            .iter()
+            .find_map(|factory| factory.create_component_from_call(&ctx))
+    }
+
+    /// Finds all components created within `block`, grouped by transaction and in ordinal order.
+    pub fn map_components(&self, block: &Block) -> BlockTransactionProtocolComponents {
+        BlockTransactionProtocolComponents {
+            tx_components: block
+                .transactions()
+                .filter_map(|tx| {
+                    let mut components = tx
+                        .logs_with_calls()
+                        .filter_map(|(log, call)| {
+                            Some((log.ordinal, self.create_component(log, call.call, tx)?))
+                        })
+                        .chain(tx.calls.iter().filter_map(|call| {
+                            Some((call.begin_ordinal, self.create_component_from_call(call, tx)?))
+                        }))
+                        .collect::<Vec<_>>();
+                    components.sort_by_key(|(ordinal, _)| *ordinal);
+
+                    if !components.is_empty() {
+                        Some(TransactionProtocolComponents {
+                            tx: Some(tx.into()),
+                            components: components
+                                .into_iter()
+                                .map(|(_, component)| component)
+                                .collect(),
+                        })
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>(),
+        }
+    }
+}
+
+/// Store key of a component.
+pub fn component_key(component_id: &str) -> String {
+    format!("pool:{component_id}")
+}
+
+/// Stores all created components under their [`component_key`].
+pub fn store_components(
+    map: BlockTransactionProtocolComponents,
+    store: impl StoreSet<ProtocolComponent>,
+) {
+    map.tx_components
+        .into_iter()
+        .flat_map(|tx_pc| tx_pc.components)
+        .for_each(|pc| store.set(0, component_key(&pc.id), &pc));
+}
+
+/// Items the handler macros expect to be in scope, the substreams store macro refers to the store
+/// type by its name.
+#[doc(hidden)]
+pub mod __handler_support {
+    pub use substreams::store::{StoreNew, StoreSetProto};
+}
+
+/// Generates `map` and `store` handlers for a [`FactoryRegistry`].
+///
+/// The map handler emits `BlockTransactionProtocolComponents`, the store handler stores each
+/// component under its [`component_key`] and must be declared with
+/// `valueType: proto:tycho.evm.v1.ProtocolComponent` in the manifest.
+///
+/// `factories` is a function building the registry:
+///
+/// ```
+/// # fn main() {}
+/// # mod pool_factories {
+/// #     pub fn factories() -> tycho_substreams::factories::FactoryRegistry {
+/// #         tycho_substreams::factories::FactoryRegistry::new()
+/// #     }
+/// # }
+/// tycho_substreams::component_factory_handlers! {
+///     map: map_components,
+///     store: store_components,
+///     factories: pool_factories::factories,
+/// }
+/// ```
+///
+/// If the map handler is declared with `(params)`, it receives the module params and returns a
+/// `Result<FactoryRegistry, _>`:
+///
+/// ```
+/// # fn main() {}
+/// # mod pool_factories {
+/// #     use tycho_substreams::factories::FactoryRegistry;
+/// #     pub fn from_params(_params: &str) -> Result<FactoryRegistry, substreams::errors::Error> {
+/// #         Ok(FactoryRegistry::new())
+/// #     }
+/// # }
+/// tycho_substreams::component_factory_handlers! {
+///     map: map_components(params),
+///     store: store_components,
+///     factories: |params: &str| pool_factories::from_params(params),
+/// }
+/// ```
+#[macro_export]
+macro_rules! component_factory_handlers {
+    (map: $map:ident, store: $store:ident, factories: $factories:expr $(,)?) => {
+        #[allow(unused_imports)]
+        use $crate::factories::__handler_support::*;
+
+        #[substreams::handlers::map]
+        pub fn $map(
+            block: substreams_ethereum::pb::eth::v2::Block,
+        ) -> Result<
+            $crate::prelude::BlockTransactionProtocolComponents,
+            substreams::errors::Error,
+        > {
+            let factories: $crate::factories::FactoryRegistry = ($factories)();
+            Ok(factories.map_components(&block))
+        }
+
+        $crate::component_factory_handlers!(@store $store);
+    };
+    (map: $map:ident(params), store: $store:ident, factories: $factories:expr $(,)?) => {
+        #[allow(unused_imports)]
+        use $crate::factories::__handler_support::*;
+
+        #[substreams::handlers::map]
+        pub fn $map(
+            params: String,
+            block: substreams_ethereum::pb::eth::v2::Block,
+        ) -> Result<
+            $crate::prelude::BlockTransactionProtocolComponents,
+            substreams::errors::Error,
+        > {
+            let factories: $crate::factories::FactoryRegistry = ($factories)(&params)?;
+            Ok(factories.map_components(&block))
+        }
+
+        $crate::component_factory_handlers!(@store $store);
+    };
+    (@store $store:ident) => {
+        #[substreams::handlers::store]
+        pub fn $store(
+            map: $crate::prelude::BlockTransactionProtocolComponents,
+            store: StoreSetProto<$crate::prelude::ProtocolComponent>,
+        ) {
+            $crate::factories::store_components(map, store)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        abi::erc20::{events::Transfer, functions::Transfer as TransferCall},
+        pb::tycho::evm::v1::{ImplementationType, Transaction},
+    };
+    use ethabi::{ethereum_types::Address, Token};
+    use substreams::{hex, scalar::BigInt};
+    use substreams_ethereum::pb::eth::v2::TransactionTraceStatus;
+
+    const FACTORY: [u8; 20] = [0xfa; 20];
+    const OTHER_FACTORY: [u8; 20] = [0xfb; 20];
+    const CALL_FACTORY: [u8; 20] = [0xfc; 20];
+    const POOL: [u8; 20] = [0xaa; 20];
+    const TOKEN: [u8; 20] = [0xbb; 20];
+    // keccak256("Transfer(address,address,uint256)")
+    const TRANSFER_TOPIC: [u8; 32] =
+        hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+    // The erc20 `Transfer` event and call stand in for a factory's creation event and call.
+    fn creation_log(emitter: &[u8]) -> Log {
+        Log {
+            address: emitter.to_vec(),
+            topics: vec![
+                TRANSFER_TOPIC.to_vec(),
+                ethabi::encode(&[Token::Address(Address::from(TOKEN))]),
+                ethabi::encode(&[Token::Address(Address::from(POOL))]),
+            ],
+            data: ethabi::encode(&[Token::Uint(1.into())]),
+            ..Default::default()
+        }
+    }
+
+    fn creation_call(emitter: &[u8]) -> Call {
+        Call {
+            address: emitter.to_vec(),
+            input: TransferCall { to: POOL.to_vec(), value: BigInt::from(1) }.encode(),
+            logs: vec![creation_log(emitter)],
+            ..Default::default()
+        }
+    }
+
+    fn tx(calls: Vec<Call>) -> TransactionTrace {
+        TransactionTrace {
+            hash: vec![1],
+            index: 2,
+            status: TransactionTraceStatus::Succeeded.into(),
+            calls,
+            ..Default::default()
+        }
+    }
+
+    fn component(pool: &[u8], tx: &TransactionTrace, type_name: &str) -> ProtocolComponent {
+        let tycho_tx: Transaction = tx.into();
+        ProtocolComponent::at_contract(pool, &tycho_tx)
+            .with_tokens(&[TOKEN])
+            .as_swap_type(type_name, ImplementationType::Vm)
+    }
+
+    fn registry() -> FactoryRegistry {
+        FactoryRegistry::new()
+            .with_factory(
+                &FACTORY,
+                on_event(|event: Transfer, ctx| Some(component(&event.to, ctx.tx, "v1_pool"))),
+            )
+            .with_factory(
+                &OTHER_FACTORY,
+                on_event_and_call(|event: Transfer, call: TransferCall, ctx| {
+                    assert_eq!(event.to, call.to);
+                    Some(component(&call.to, ctx.tx, "v2_pool"))
+                }),
+            )
+            .with_factory(
+                &CALL_FACTORY,
+                on_call(|call: TransferCall, ctx| Some(component(&call.to, ctx.tx, "v3_pool"))),
+            )
+    }
+
+    #[test]
+    fn test_create_component_dispatches_by_address() {
+        let call = creation_call(&FACTORY);
+        let other_call = creation_call(&OTHER_FACTORY);
+        let tx = tx(vec![]);
+        let registry = registry();
+
+        let res = registry.create_component(&call.logs[0], &call, &tx);
+        let other_res = registry.create_component(&other_call.logs[0], &other_call, &tx);
+
+        assert_eq!(res, Some(component(&POOL, &tx, "v1_pool")));
+        assert_eq!(other_res, Some(component(&POOL, &tx, "v2_pool")));
+    }
+
+    #[test]
+    fn test_create_component_ignores_unknown_addresses() {
+        let call = creation_call(&POOL);
+
+        let res = registry().create_component(&call.logs[0], &call, &tx(vec![]));
+
+        assert_eq!(res, None);
+    }
+
+    #[test]
+    fn test_create_component_requires_call() {
+        let mut call = creation_call(&OTHER_FACTORY);
+        call.input = vec![];
+
+        let res = registry().create_component(&call.logs[0], &call, &tx(vec![]));
+
+        assert_eq!(res, None);
+    }
+
+    #[test]
+    fn test_create_component_tries_all_factories_at_address() {
+        let call = creation_call(&FACTORY);
+        let tx = tx(vec![]);
+        let registry = FactoryRegistry::new()
+            .with_factory(&FACTORY, on_event(|_: Transfer, _| None))
+            .with_factories(
+                &[FACTORY, OTHER_FACTORY],
+                on_event(|event: Transfer, ctx| Some(component(&event.to, ctx.tx, "fallback"))),
+            );
+
+        let res = registry.create_component(&call.logs[0], &call, &tx);
+
+        assert_eq!(res, Some(component(&POOL, &tx, "fallback")));
+        assert!(registry.contains(&OTHER_FACTORY));
+    }
+
+    #[test]
+    fn test_map_components() {
+        let tx = tx(vec![creation_call(&FACTORY), creation_call(&TOKEN)]);
+        let block = Block { transaction_traces: vec![tx.clone()], ..Default::default() };
+
+        let res = registry().map_components(&block);
+
+        assert_eq!(
+            res,
+            BlockTransactionProtocolComponents {
+                tx_components: vec![TransactionProtocolComponents {
+                    tx: Some((&tx).into()),
+                    components: vec![component(&POOL, &tx, "v1_pool")],
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn test_create_component_from_call() {
+        let call = creation_call(&CALL_FACTORY);
+        let tx = tx(vec![]);
+
+        let res = registry().create_component_from_call(&call, &tx);
+
+        assert_eq!(res, Some(component(&POOL, &tx, "v3_pool")));
+    }
+
+    #[test]
+    fn test_create_component_from_call_ignores_reverted_and_event_factories() {
+        let mut reverted = creation_call(&CALL_FACTORY);
+        reverted.state_reverted = true;
+        let tx = tx(vec![]);
+        let registry = registry();
+
+        assert_eq!(registry.create_component_from_call(&reverted, &tx), None);
+        assert_eq!(registry.create_component_from_call(&creation_call(&FACTORY), &tx), None);
+    }
+
+    #[test]
+    fn test_map_components_orders_calls_and_logs() {
+        let mut event_call = creation_call(&FACTORY);
+        event_call.begin_ordinal = 1;
+        event_call.logs[0].ordinal = 5;
+        let mut call_only = creation_call(&CALL_FACTORY);
+        call_only.begin_ordinal = 3;
+        call_only.logs = vec![];
+        let tx = tx(vec![event_call, call_only]);
+        let block = Block { transaction_traces: vec![tx.clone()], ..Default::default() };
+
+        let res = registry().map_components(&block);
+
+        assert_eq!(
+            res.tx_components[0].components,
+            vec![component(&POOL, &tx, "v3_pool"), component(&POOL, &tx, "v1_pool")]
+        );
+    }
+}
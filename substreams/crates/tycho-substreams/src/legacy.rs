@@ -0,0 +1,459 @@
+//! Conversions from the deprecated output messages to `BlockChanges`.
+//!
+//! Older packages emit `BlockContractChanges` (VM integrations), `BlockEntityChanges` (native
+//! integrations) or, in the case of Ambient, an intermediate `BlockPoolChanges` message. This
+//! module provides lossless conversions into the unified `BlockChanges` format, so consumers only
+//! have to handle a single output type.
+//!
+//! Legacy handlers can be migrated without touching their internals by wrapping their output with
+//! [`into_block_changes`]:
+//!
+//! ```
+//! # use substreams_ethereum::pb::eth;
+//! # use tycho_substreams::{legacy, prelude::*};
+//! # fn map_contract_changes(
+//! #     _block: eth::v2::Block,
+//! # ) -> Result<BlockContractChanges, substreams::errors::Error> {
+//! #     Ok(BlockContractChanges::default())
+//! # }
+//! #[substreams::handlers::map]
+//! fn map_changes(block: eth::v2::Block) -> Result<BlockChanges, substreams::errors::Error> {
+//!     legacy::into_block_changes(map_contract_changes(block))
+//! }
+//! ```
+//!
+//! Conversions back into the legacy messages are fallible, since they can't represent every
+//! `BlockChanges`: a `TransactionContractChanges` has no entity changes and a
+//! `TransactionEntityChanges` has no contract changes.
+use std::fmt::{Display, Formatter};
+
+use crate::pb::tycho::{
+    ambient::v1::{AmbientBalanceDelta, BlockPoolChanges},
+    evm::v1::{
+        BalanceDelta, BlockBalanceDeltas, BlockChanges, BlockContractChanges, BlockEntityChanges,
+        BlockTransactionProtocolComponents, ProtocolComponent, TransactionChanges,
+        TransactionContractChanges, TransactionEntityChanges, TransactionProtocolComponents,
+    },
+};
+
+/// Error raised if a message can't be converted without losing information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The changes contain entity changes, which the target message can't represent.
+    UnexpectedEntityChanges,
+    /// The changes contain contract changes, which the target message can't represent.
+    UnexpectedContractChanges,
+    /// An Ambient balance delta has a token type other than `base` or `quote`.
+    UnknownTokenType(String),
+    /// The component referenced by an Ambient balance delta is unknown or lacks the token.
+    MissingComponent(String),
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConversionError::UnexpectedEntityChanges => {
+                write!(f, "entity changes can't be represented in the target message")
+            }
+            ConversionError::UnexpectedContractChanges => {
+                write!(f, "contract changes can't be represented in the target message")
+            }
+            ConversionError::UnknownTokenType(token_type) => {
+                write!(f, "unknown token type: {token_type}")
+            }
+            ConversionError::MissingComponent(id) => {
+                write!(f, "component not found or missing tokens: {id}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// Wraps the output of a legacy handler so it is emitted as `BlockChanges`.
+///
+/// Accepts any message that can be losslessly converted into `BlockChanges`, i.e.
+/// `BlockContractChanges` and `BlockEntityChanges`.
+pub fn into_block_changes<T, E>(output: Result<T, E>) -> Result<BlockChanges, E>
+where
+    T: Into<BlockChanges>,
+{
+    output.map(Into::into)
+}
+
+impl From<TransactionContractChanges> for TransactionChanges {
+    fn from(value: TransactionContractChanges) -> Self {
+        Self {
+            tx: value.tx,
+            contract_changes: value.contract_changes,
+            entity_changes: vec![],
+            component_changes: value.component_changes,
+            balance_changes: value.balance_changes,
+        }
+    }
+}
+
+impl From<TransactionEntityChanges> for TransactionChanges {
+    fn from(value: TransactionEntityChanges) -> Self {
+        Self {
+            tx: value.tx,
+            contract_changes: vec![],
+            entity_changes: value.entity_changes,
+            component_changes: value.component_changes,
+            balance_changes: value.balance_changes,
+        }
+    }
+}
+
+impl From<BlockContractChanges> for BlockChanges {
+    fn from(value: BlockContractChanges) -> Self {
+        Self {
+            block: value.block,
+            changes: value
+                .changes
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl From<BlockEntityChanges> for BlockChanges {
+    fn from(value: BlockEntityChanges) -> Self {
+        Self {
+            block: value.block,
+            changes: value
+                .changes
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<TransactionChanges> for TransactionContractChanges {
+    type Error = ConversionError;
+
+    fn try_from(value: TransactionChanges) -> Result<Self, Self::Error> {
+        if !value.entity_changes.is_empty() {
+            return Err(ConversionError::UnexpectedEntityChanges);
+        }
+        Ok(Self {
+            tx: value.tx,
+            contract_changes: value.contract_changes,
+            component_changes: value.component_changes,
+            balance_changes: value.balance_changes,
+        })
+    }
+}
+
+impl TryFrom<TransactionChanges> for TransactionEntityChanges {
+    type Error = ConversionError;
+
+    fn try_from(value: TransactionChanges) -> Result<Self, Self::Error> {
+        if !value.contract_changes.is_empty() {
+            return Err(ConversionError::UnexpectedContractChanges);
+        }
+        Ok(Self {
+            tx: value.tx,
+            entity_changes: value.entity_changes,
+            component_changes: value.component_changes,
+            balance_changes: value.balance_changes,
+        })
+    }
+}
+
+impl TryFrom<BlockChanges> for BlockContractChanges {
+    type Error = ConversionError;
+
+    fn try_from(value: BlockChanges) -> Result<Self, Self::Error> {
+        Ok(Self {
+            block: value.block,
+            changes: value
+                .changes
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl TryFrom<BlockChanges> for BlockEntityChanges {
+    type Error = ConversionError;
+
+    fn try_from(value: BlockChanges) -> Result<Self, Self::Error> {
+        Ok(Self {
+            block: value.block,
+            changes: value
+                .changes
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<BlockPoolChanges> for BlockTransactionProtocolComponents {
+    /// Groups the components by their creating transaction, keeping the order of appearance.
+    fn from(value: BlockPoolChanges) -> Self {
+        let mut tx_components: Vec<TransactionProtocolComponents> = Vec::new();
+        for component in value.protocol_components {
+            match tx_components
+                .iter_mut()
+                .find(|tx_component| tx_component.tx == component.tx)
+            {
+                Some(tx_component) => tx_component.components.push(component),
+                None => tx_components.push(TransactionProtocolComponents {
+                    tx: component.tx.clone(),
+                    components: vec![component],
+                }),
+            }
+        }
+        Self { tx_components }
+    }
+}
+
+impl AmbientBalanceDelta {
+    /// Converts this delta into a generic `BalanceDelta` of the given component.
+    ///
+    /// The `base` token is the first and the `quote` token the second token of the component.
+    pub fn try_into_balance_delta(
+        self,
+        component: &ProtocolComponent,
+    ) -> Result<BalanceDelta, ConversionError> {
+        let token_index = match self.token_type.as_str() {
+            "base" => 0,
+            "quote" => 1,
+            other => return Err(ConversionError::UnknownTokenType(other.to_string())),
+        };
+        let token = component
+            .tokens
+            .get(token_index)
+            .ok_or_else(|| ConversionError::MissingComponent(component.id.clone()))?;
+        Ok(BalanceDelta {
+            ord: self.ordinal,
+            tx: self.tx,
+            token: token.clone(),
+            delta: self.token_delta,
+            component_id: hex::encode(self.pool_hash).into_bytes(),
+        })
+    }
+}
+
+impl BlockPoolChanges {
+    /// Converts the Ambient specific balance deltas into generic `BlockBalanceDeltas`.
+    ///
+    /// Components created within this block are resolved from the message itself, any other
+    /// component is looked up through `get_component` by its id, e.g. from a component store.
+    pub fn try_into_balance_deltas<F>(
+        self,
+        get_component: F,
+    ) -> Result<BlockBalanceDeltas, ConversionError>
+    where
+        F: Fn(&str) -> Option<ProtocolComponent>,
+    {
+        let balance_deltas = self
+            .balance_deltas
+            .into_iter()
+            .map(|delta| {
+                let component_id = hex::encode(&delta.pool_hash);
+                let component = self
+                    .protocol_components
+                    .iter()
+                    .find(|c| c.id == component_id)
+                    .cloned()
+                    .or_else(|| get_component(&component_id))
+                    .ok_or(ConversionError::MissingComponent(component_id))?;
+                delta.try_into_balance_delta(&component)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(BlockBalanceDeltas { balance_deltas })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::tycho::evm::v1::{
+        Attribute, BalanceChange, Block, ChangeType, ContractChange, EntityChanges, Transaction,
+    };
+
+    fn tx(index: u64) -> Transaction {
+        Transaction { hash: vec![index as u8], from: vec![], to: vec![], index }
+    }
+
+    fn block() -> Block {
+        Block { hash: vec![1], parent_hash: vec![0], number: 1, ts: 1 }
+    }
+
+    fn contract_change() -> ContractChange {
+        ContractChange {
+            address: vec![0xaa],
+            balance: vec![1],
+            code: vec![],
+            slots: vec![],
+            change: ChangeType::Update.into(),
+        }
+    }
+
+    fn entity_change() -> EntityChanges {
+        EntityChanges {
+            component_id: "pool".to_string(),
+            attributes: vec![Attribute {
+                name: "reserve0".to_string(),
+                value: vec![1],
+                change: ChangeType::Update.into(),
+            }],
+        }
+    }
+
+    fn balance_change() -> BalanceChange {
+        BalanceChange { token: vec![1], balance: vec![2], component_id: b"pool".to_vec() }
+    }
+
+    #[test]
+    fn test_block_contract_changes_roundtrip() {
+        let legacy = BlockContractChanges {
+            block: Some(block()),
+            changes: vec![TransactionContractChanges {
+                tx: Some(tx(1)),
+                contract_changes: vec![contract_change()],
+                component_changes: vec![ProtocolComponent::new("pool", &tx(1))],
+                balance_changes: vec![balance_change()],
+            }],
+        };
+
+        let changes: BlockChanges = legacy.clone().into();
+        let res: BlockContractChanges = changes.clone().try_into().unwrap();
+
+        assert_eq!(changes.changes[0].contract_changes, vec![contract_change()]);
+        assert_eq!(res, legacy);
+    }
+
+    #[test]
+    fn test_block_entity_changes_roundtrip() {
+        let legacy = BlockEntityChanges {
+            block: Some(block()),
+            changes: vec![TransactionEntityChanges {
+                tx: Some(tx(1)),
+                entity_changes: vec![entity_change()],
+                component_changes: vec![ProtocolComponent::new("pool", &tx(1))],
+                balance_changes: vec![balance_change()],
+            }],
+        };
+
+        let changes: BlockChanges = legacy.clone().into();
+        let res: BlockEntityChanges = changes.clone().try_into().unwrap();
+
+        assert_eq!(changes.changes[0].entity_changes, vec![entity_change()]);
+        assert_eq!(res, legacy);
+    }
+
+    #[test]
+    fn test_lossy_conversions_fail() {
+        let changes = TransactionChanges {
+            tx: Some(tx(1)),
+            contract_changes: vec![contract_change()],
+            entity_changes: vec![entity_change()],
+            component_changes: vec![],
+            balance_changes: vec![],
+        };
+
+        let contract_res = TransactionContractChanges::try_from(changes.clone());
+        let entity_res = TransactionEntityChanges::try_from(changes);
+
+        assert_eq!(contract_res, Err(ConversionError::UnexpectedEntityChanges));
+        assert_eq!(entity_res, Err(ConversionError::UnexpectedContractChanges));
+    }
+
+    #[test]
+    fn test_into_block_changes() {
+        let legacy: Result<_, ConversionError> =
+            Ok(BlockContractChanges { block: Some(block()), changes: vec![] });
+
+        let res = into_block_changes(legacy);
+
+        assert_eq!(res, Ok(BlockChanges { block: Some(block()), changes: vec![] }));
+    }
+
+    fn pool_changes() -> BlockPoolChanges {
+        let pool_hash = vec![0xab; 32];
+        let component = ProtocolComponent::new(&hex::encode(&pool_hash), &tx(1))
+            .with_tokens(&[vec![1u8], vec![2u8]]);
+        BlockPoolChanges {
+            protocol_components: vec![component],
+            balance_deltas: vec![
+                AmbientBalanceDelta {
+                    pool_hash: pool_hash.clone(),
+                    token_type: "base".to_string(),
+                    token_delta: vec![10],
+                    ordinal: 4,
+                    tx: Some(tx(1)),
+                },
+                AmbientBalanceDelta {
+                    pool_hash,
+                    token_type: "quote".to_string(),
+                    token_delta: vec![0xf6],
+                    ordinal: 4,
+                    tx: Some(tx(1)),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_pool_changes_into_components() {
+        let changes = pool_changes();
+
+        let res: BlockTransactionProtocolComponents = changes.clone().into();
+
+        assert_eq!(
+            res,
+            BlockTransactionProtocolComponents {
+                tx_components: vec![TransactionProtocolComponents {
+                    tx: Some(tx(1)),
+                    components: changes.protocol_components,
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn test_pool_changes_into_balance_deltas() {
+        let component_id = hex::encode([0xab; 32]).into_bytes();
+
+        let res = pool_changes()
+            .try_into_balance_deltas(|_| None)
+            .unwrap();
+
+        assert_eq!(
+            res.balance_deltas,
+            vec![
+                BalanceDelta {
+                    ord: 4,
+                    tx: Some(tx(1)),
+                    token: vec![1],
+                    delta: vec![10],
+                    component_id: component_id.clone(),
+                },
+                BalanceDelta {
+                    ord: 4,
+                    tx: Some(tx(1)),
+                    token: vec![2],
+                    delta: vec![0xf6],
+                    component_id,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pool_changes_unknown_component() {
+        let mut changes = pool_changes();
+        changes.protocol_components.clear();
+
+        let res = changes.try_into_balance_deltas(|_| None);
+
+        assert_eq!(res, Err(ConversionError::MissingComponent(hex::encode([0xab; 32]))));
+    }
+}
@@ -1,7 +1,11 @@
 mod abi;
+pub mod aggregation;
 pub mod attributes;
 pub mod balances;
 pub mod contract;
+pub mod factories;
+pub mod legacy;
+#[cfg(test)]
 mod mock_store;
 pub mod models;
 #[allow(clippy::too_long_first_doc_paragraph)]
@@ -324,10 +324,12 @@ impl ProtocolComponent {
     /// # Example
     ///
     /// ```
-    /// let attributes_to_check = vec![
-    ///     ("attribute1".to_string(), vec![1, 2, 3]),
-    ///     ("attribute2".to_string(), vec![4, 5, 6]),
-    /// ];
+    /// # use tycho_substreams::prelude::*;
+    /// # let instance = ProtocolComponent::new("pool", &Transaction::default()).with_attributes(&[
+    /// #     ("attribute1", vec![1, 2, 3]),
+    /// #     ("attribute2", vec![4, 5, 6]),
+    /// # ]);
+    /// let attributes_to_check = vec![("attribute1", vec![1, 2, 3]), ("attribute2", vec![4, 5, 6])];
     ///
     /// let has_all_attributes = instance.has_attributes(&attributes_to_check);
     /// assert!(has_all_attributes);
@@ -366,6 +368,9 @@ impl ProtocolComponent {
     /// # Example
     ///
     /// ```
+    /// # use tycho_substreams::prelude::*;
+    /// # let instance = ProtocolComponent::new("pool", &Transaction::default())
+    /// #     .with_attributes(&[("attribute1", vec![1, 2, 3])]);
     /// let attribute_name = "attribute1";
     /// if let Some(value) = instance.get_attribute_value(attribute_name) {
     ///     // Use the attribute value
@@ -0,0 +1,147 @@
+//! Differential checker between `ethereum-uniswap-v3` and `ethereum-uniswap-v3-logs-only`.
+//!
+//! Both packages index the same pools, one from storage changes and one from events only. The
+//! checker replays the output of both over the same blocks and reports every pool state they
+//! disagree on. See the Readme for details.
+mod output;
+mod state;
+
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand};
+
+use crate::{
+    output::{read_output, BlockOutput, BytesEncoding},
+    state::diff,
+};
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+    /// Encoding of bytes fields in the output of `substreams run`.
+    #[arg(long, value_enum, default_value_t = BytesEncoding::Hex, global = true)]
+    bytes_encoding: BytesEncoding,
+    /// Maximum number of divergences to print.
+    #[arg(long, default_value_t = 100, global = true)]
+    max_reports: usize,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    /// Runs both packages with `substreams run` and compares their output.
+    Run {
+        /// Substreams endpoint, e.g. `mainnet.eth.streamingfast.io:443`.
+        #[arg(long)]
+        endpoint: String,
+        #[arg(long)]
+        start_block: u64,
+        /// Exclusive end of the compared block range.
+        #[arg(long)]
+        stop_block: u64,
+        /// Manifest or spkg of the storage based package.
+        #[arg(long, default_value = "ethereum-uniswap-v3/ethereum-uniswap-v3.yaml")]
+        storage_package: PathBuf,
+        /// Manifest or spkg of the logs only package.
+        #[arg(long, default_value = "ethereum-uniswap-v3-logs-only/ethereum-uniswap-v3.yaml")]
+        logs_only_package: PathBuf,
+    },
+    /// Compares previously recorded `jsonl` outputs of both packages.
+    Compare {
+        /// Output of `map_pool_events` of the storage based package.
+        #[arg(long)]
+        storage: PathBuf,
+        /// Output of `map_protocol_changes` of the logs only package.
+        #[arg(long)]
+        logs_only: PathBuf,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let (storage, logs_only) = match &cli.command {
+        Commands::Run { endpoint, start_block, stop_block, storage_package, logs_only_package } => {
+            let run = |package: &Path, module: &str| {
+                run_package(
+                    package,
+                    module,
+                    endpoint,
+                    *start_block,
+                    *stop_block,
+                    cli.bytes_encoding,
+                )
+            };
+            (
+                run(storage_package, "map_pool_events")?,
+                run(logs_only_package, "map_protocol_changes")?,
+            )
+        }
+        Commands::Compare { storage, logs_only } => {
+            (read_file(storage, cli.bytes_encoding)?, read_file(logs_only, cli.bytes_encoding)?)
+        }
+    };
+
+    let divergences = diff(&storage, &logs_only);
+    for divergence in divergences.iter().take(cli.max_reports) {
+        println!(
+            "block {} pool {} {}: storage {} logs-only {}",
+            divergence.block,
+            divergence.pool,
+            divergence.key,
+            divergence.storage,
+            divergence.logs_only
+        );
+    }
+    println!(
+        "Compared {} blocks, found {} divergences",
+        storage.len().max(logs_only.len()),
+        divergences.len()
+    );
+    if !divergences.is_empty() {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+fn read_file(path: &Path, encoding: BytesEncoding) -> Result<Vec<BlockOutput>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    read_output(BufReader::new(file), encoding)
+        .with_context(|| format!("Failed to read {}", path.display()))
+}
+
+fn run_package(
+    package: &Path,
+    module: &str,
+    endpoint: &str,
+    start_block: u64,
+    stop_block: u64,
+    encoding: BytesEncoding,
+) -> Result<Vec<BlockOutput>> {
+    let output = Command::new("substreams")
+        .arg("run")
+        .arg(package)
+        .arg(module)
+        .args(["-e", endpoint])
+        .args(["-s", &start_block.to_string()])
+        .args(["-t", &stop_block.to_string()])
+        .args(["-o", "jsonl"])
+        .output()
+        .context("Failed to run substreams, is it installed?")?;
+    if !output.status.success() {
+        bail!(
+            "Running {} of {} failed: {}",
+            module,
+            package.display(),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    read_output(output.stdout.as_slice(), encoding)
+}
@@ -0,0 +1,189 @@
+//! Parsing of the `jsonl` output of `substreams run`.
+use std::io::BufRead;
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// Encoding of bytes fields in the recorded output, see `substreams run --help`.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum BytesEncoding {
+    Hex,
+    Base64,
+}
+
+impl BytesEncoding {
+    fn decode(&self, value: &str) -> Result<Vec<u8>> {
+        match self {
+            BytesEncoding::Hex => hex::decode(value.trim_start_matches("0x"))
+                .with_context(|| format!("Invalid hex bytes {value}")),
+            BytesEncoding::Base64 => {
+                base64::decode(value).with_context(|| format!("Invalid base64 bytes {value}"))
+            }
+        }
+    }
+}
+
+/// A single state change of a component, decoded from a `BlockChanges` message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Attribute { component_id: String, name: String, value: Vec<u8>, deleted: bool },
+    Balance { component_id: String, token: Vec<u8>, balance: Vec<u8> },
+}
+
+/// The changes of one block, in transaction order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockOutput {
+    pub number: u64,
+    pub changes: Vec<Change>,
+}
+
+#[derive(Deserialize)]
+struct OutputLine {
+    #[serde(rename = "@block")]
+    block: u64,
+    #[serde(rename = "@data", default)]
+    data: BlockChangesJson,
+}
+
+#[derive(Default, Deserialize)]
+struct BlockChangesJson {
+    #[serde(default)]
+    changes: Vec<TransactionChangesJson>,
+}
+
+#[derive(Deserialize)]
+struct TransactionChangesJson {
+    #[serde(default, alias = "entityChanges")]
+    entity_changes: Vec<EntityChangesJson>,
+    #[serde(default, alias = "balanceChanges")]
+    balance_changes: Vec<BalanceChangeJson>,
+}
+
+#[derive(Deserialize)]
+struct EntityChangesJson {
+    #[serde(alias = "componentId")]
+    component_id: String,
+    #[serde(default)]
+    attributes: Vec<AttributeJson>,
+}
+
+#[derive(Deserialize)]
+struct AttributeJson {
+    name: String,
+    #[serde(default)]
+    value: String,
+    #[serde(default)]
+    change: ChangeTypeJson,
+}
+
+/// Enums are either rendered by name or by number.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChangeTypeJson {
+    Name(String),
+    Number(i32),
+}
+
+impl Default for ChangeTypeJson {
+    fn default() -> Self {
+        ChangeTypeJson::Number(0)
+    }
+}
+
+impl ChangeTypeJson {
+    fn is_deletion(&self) -> bool {
+        match self {
+            ChangeTypeJson::Name(name) => name == "CHANGE_TYPE_DELETION",
+            ChangeTypeJson::Number(number) => *number == 3,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct BalanceChangeJson {
+    #[serde(default)]
+    token: String,
+    #[serde(default)]
+    balance: String,
+    #[serde(default, alias = "componentId")]
+    component_id: String,
+}
+
+/// Reads the `BlockChanges` of all blocks from a `jsonl` recording.
+pub fn read_output(reader: impl BufRead, encoding: BytesEncoding) -> Result<Vec<BlockOutput>> {
+    let mut blocks = Vec::new();
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let output: OutputLine = serde_json::from_str(&line)
+            .with_context(|| format!("Invalid output on line {}", line_number + 1))?;
+        blocks.push(decode_block(output, encoding)?);
+    }
+    if blocks
+        .windows(2)
+        .any(|pair| pair[0].number >= pair[1].number)
+    {
+        bail!("Expected the blocks to be ordered");
+    }
+    Ok(blocks)
+}
+
+fn decode_block(output: OutputLine, encoding: BytesEncoding) -> Result<BlockOutput> {
+    let mut changes = Vec::new();
+    for tx in output.data.changes {
+        for entity_change in tx.entity_changes {
+            for attribute in entity_change.attributes {
+                changes.push(Change::Attribute {
+                    component_id: entity_change.component_id.clone(),
+                    deleted: attribute.change.is_deletion(),
+                    name: attribute.name,
+                    value: encoding.decode(&attribute.value)?,
+                });
+            }
+        }
+        for balance_change in tx.balance_changes {
+            changes.push(Change::Balance {
+                component_id: String::from_utf8(encoding.decode(&balance_change.component_id)?)
+                    .context("Balance component id is not utf8")?,
+                token: encoding.decode(&balance_change.token)?,
+                balance: encoding.decode(&balance_change.balance)?,
+            });
+        }
+    }
+    Ok(BlockOutput { number: output.block, changes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_output() {
+        let line = r#"{"@module":"map_pool_events","@block":12,"@type":"tycho.evm.v1.BlockChanges","@data":{"changes":[{"entityChanges":[{"componentId":"0xaa","attributes":[{"name":"tick","value":"0a","change":"CHANGE_TYPE_UPDATE"}]}],"balanceChanges":[{"token":"bb","balance":"01","componentId":"30786161"}]}]}}"#;
+
+        let res = read_output(line.as_bytes(), BytesEncoding::Hex).unwrap();
+
+        assert_eq!(
+            res,
+            vec![BlockOutput {
+                number: 12,
+                changes: vec![
+                    Change::Attribute {
+                        component_id: "0xaa".to_string(),
+                        name: "tick".to_string(),
+                        value: vec![10],
+                        deleted: false,
+                    },
+                    Change::Balance {
+                        component_id: "0xaa".to_string(),
+                        token: vec![0xbb],
+                        balance: vec![1],
+                    },
+                ],
+            }]
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Replays the output of both packages and compares the resulting pool states.
+use std::collections::{BTreeMap, BTreeSet};
+
+use num_bigint::{BigInt, Sign};
+
+use crate::output::{BlockOutput, Change};
+
+/// The package an output was recorded from, they differ in how values are encoded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Package {
+    /// `ethereum-uniswap-v3`, attributes are signed little endian.
+    Storage,
+    /// `ethereum-uniswap-v3-logs-only`, attributes are signed big endian.
+    LogsOnly,
+}
+
+impl Package {
+    fn decode_attribute(&self, value: &[u8]) -> BigInt {
+        match self {
+            Package::Storage => BigInt::from_signed_bytes_le(value),
+            Package::LogsOnly => BigInt::from_signed_bytes_be(value),
+        }
+    }
+
+    fn decode_balance(&self, value: &[u8]) -> BigInt {
+        match self {
+            Package::Storage => BigInt::from_bytes_be(Sign::Plus, value),
+            Package::LogsOnly => BigInt::from_signed_bytes_be(value),
+        }
+    }
+}
+
+/// Returns whether both packages are expected to agree on an attribute.
+///
+/// Attributes only one of the packages can provide, like fee growth, or that have different
+/// meanings, like `protocol_fees/*`, are ignored.
+fn is_compared(name: &str) -> bool {
+    matches!(name, "liquidity" | "tick" | "sqrt_price_x96") ||
+        name.strip_prefix("ticks/")
+            .and_then(|rest| rest.split_once('/'))
+            .is_some_and(|(_, field)| {
+                matches!(field, "net-liquidity" | "liquidity-gross" | "initialized")
+            })
+}
+
+/// Key of a compared value within a pool, balances are keyed as `balance/{token}`.
+fn balance_key(token: &[u8]) -> String {
+    format!("balance/0x{}", hex::encode(token))
+}
+
+/// The compared state of all pools of one package.
+#[derive(Debug, Default)]
+struct PoolStates {
+    pools: BTreeMap<String, BTreeMap<String, BigInt>>,
+}
+
+impl PoolStates {
+    /// Applies the changes of a block, returns the pools that were touched.
+    fn apply(&mut self, block: &BlockOutput, package: Package) -> BTreeSet<String> {
+        let mut touched = BTreeSet::new();
+        for change in &block.changes {
+            let (component_id, key, value) = match change {
+                Change::Attribute { name, .. } if !is_compared(name) => continue,
+                Change::Attribute { component_id, name, value, deleted } => (
+                    component_id,
+                    name.clone(),
+                    (!deleted).then(|| package.decode_attribute(value)),
+                ),
+                Change::Balance { component_id, token, balance } => {
+                    (component_id, balance_key(token), Some(package.decode_balance(balance)))
+                }
+            };
+            let pool = component_id.to_lowercase();
+            let state = self
+                .pools
+                .entry(pool.clone())
+                .or_default();
+            match value {
+                Some(value) => state.insert(key, value),
+                None => state.remove(&key),
+            };
+            touched.insert(pool);
+        }
+        touched
+    }
+
+    fn get(&self, pool: &str) -> Option<&BTreeMap<String, BigInt>> {
+        self.pools.get(pool)
+    }
+}
+
+/// A value both packages disagree on after a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub block: u64,
+    pub pool: String,
+    pub key: String,
+    pub storage: BigInt,
+    pub logs_only: BigInt,
+}
+
+/// Replays both outputs block by block and reports the divergences of the pools touched in each
+/// block. Values unknown to a package are considered zero, the recordings should therefore start
+/// at the same block, ideally before the compared pools were created.
+///
+/// Both outputs are expected in block order, as recorded by `substreams run`, and are merged in a
+/// single pass.
+pub fn diff(storage: &[BlockOutput], logs_only: &[BlockOutput]) -> Vec<Divergence> {
+    let mut storage_states = PoolStates::default();
+    let mut logs_only_states = PoolStates::default();
+    let mut storage = storage.iter().peekable();
+    let mut logs_only = logs_only.iter().peekable();
+    let empty = BTreeMap::new();
+
+    let mut divergences = Vec::new();
+    while let Some(number) = [storage.peek(), logs_only.peek()]
+        .into_iter()
+        .flatten()
+        .map(|block| block.number)
+        .min()
+    {
+        let mut touched = BTreeSet::new();
+        while let Some(block) = storage.next_if(|block| block.number == number) {
+            touched.extend(storage_states.apply(block, Package::Storage));
+        }
+        while let Some(block) = logs_only.next_if(|block| block.number == number) {
+            touched.extend(logs_only_states.apply(block, Package::LogsOnly));
+        }
+
+        for pool in touched {
+            let storage_state = storage_states
+                .get(&pool)
+                .unwrap_or(&empty);
+            let logs_only_state = logs_only_states
+                .get(&pool)
+                .unwrap_or(&empty);
+            let keys = storage_state
+                .keys()
+                .chain(logs_only_state.keys())
+                .collect::<BTreeSet<_>>();
+            for key in keys {
+                let storage_value = storage_state
+                    .get(key)
+                    .cloned()
+                    .unwrap_or_default();
+                let logs_only_value = logs_only_state
+                    .get(key)
+                    .cloned()
+                    .unwrap_or_default();
+                if storage_value != logs_only_value {
+                    divergences.push(Divergence {
+                        block: number,
+                        pool: pool.clone(),
+                        key: key.clone(),
+                        storage: storage_value,
+                        logs_only: logs_only_value,
+                    });
+                }
+            }
+        }
+    }
+    divergences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attribute(name: &str, value: Vec<u8>) -> Change {
+        Change::Attribute {
+            component_id: "0xAA".to_string(),
+            name: name.to_string(),
+            value,
+            deleted: false,
+        }
+    }
+
+    #[test]
+    fn test_diff_matching_encodings() {
+        let storage = vec![BlockOutput {
+            number: 1,
+            changes: vec![
+                attribute("tick", BigInt::from(-300).to_signed_bytes_le()),
+                attribute("ticks/60/net-liquidity", BigInt::from(0).to_signed_bytes_le()),
+                attribute("fee_growth_global0_x128", vec![1]),
+                Change::Balance {
+                    component_id: "0xaa".to_string(),
+                    token: vec![0xbb],
+                    balance: vec![0x80],
+                },
+            ],
+        }];
+        let logs_only = vec![BlockOutput {
+            number: 1,
+            changes: vec![
+                attribute("tick", BigInt::from(-300).to_signed_bytes_be()),
+                Change::Attribute {
+                    component_id: "0xaa".to_string(),
+                    name: "ticks/60/net-liquidity".to_string(),
+                    value: vec![],
+                    deleted: true,
+                },
+                Change::Balance {
+                    component_id: "0xaa".to_string(),
+                    token: vec![0xbb],
+                    balance: vec![0x00, 0x80],
+                },
+            ],
+        }];
+
+        assert_eq!(diff(&storage, &logs_only), vec![]);
+    }
+
+    #[test]
+    fn test_diff_reports_divergence() {
+        let storage = vec![
+            BlockOutput { number: 1, changes: vec![attribute("liquidity", vec![10])] },
+            BlockOutput { number: 2, changes: vec![attribute("liquidity", vec![20])] },
+        ];
+        let logs_only = vec![
+            BlockOutput { number: 1, changes: vec![attribute("liquidity", vec![10])] },
+            BlockOutput { number: 2, changes: vec![attribute("liquidity", vec![30])] },
+        ];
+
+        assert_eq!(
+            diff(&storage, &logs_only),
+            vec![Divergence {
+                block: 2,
+                pool: "0xaa".to_string(),
+                key: "liquidity".to_string(),
+                storage: BigInt::from(20),
+                logs_only: BigInt::from(30),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_merges_blocks_missing_in_one_output() {
+        let storage = vec![
+            BlockOutput { number: 1, changes: vec![attribute("liquidity", vec![10])] },
+            BlockOutput { number: 3, changes: vec![attribute("liquidity", vec![20])] },
+        ];
+        let logs_only = vec![
+            BlockOutput { number: 2, changes: vec![attribute("liquidity", vec![10])] },
+            BlockOutput { number: 3, changes: vec![attribute("liquidity", vec![20])] },
+        ];
+
+        assert_eq!(
+            diff(&storage, &logs_only),
+            vec![Divergence {
+                block: 1,
+                pool: "0xaa".to_string(),
+                key: "liquidity".to_string(),
+                storage: BigInt::from(10),
+                logs_only: BigInt::from(0),
+            }]
+        );
+    }
+}
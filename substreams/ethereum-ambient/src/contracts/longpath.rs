@@ -0,0 +1,243 @@
+use anyhow::{anyhow, bail};
+
+use crate::utils::encode_pool_hash;
+use ethabi::{decode, ethereum_types::U256, ParamType};
+use hex_literal::hex;
+use substreams_ethereum::pb::eth::v2::Call;
+
+pub const USER_CMD_LONGPATH_FN_SIG: [u8; 4] = hex!("f96dc788");
+const USER_CMD_EXTERNAL_ABI: &[ParamType] = &[
+    ParamType::Bytes, // userCmd
+];
+
+/// Schema type of the long form order directive, see `OrderEncoding`.
+const LONG_FORM_SCHEMA: u8 = 1;
+
+// Number of 32 byte words taken by each part of the long form order encoding.
+const SETTLEMENT_WORDS: usize = 4; // token, limitQty, dustThresh, useSurplus flags
+const IMPROVE_WORDS: usize = 1; // improve flags
+const AMBIENT_WORDS: usize = 3; // isAdd, rollType, liquidity
+const CONCENTRATED_WORDS: usize = 6; // lowTick, highTick, isTickRel, isAdd, rollType, liquidity
+const SWAP_WORDS: usize = 4; // direction flags, rollType, qty, limitPrice
+const CHAINING_WORDS: usize = 1; // chaining flags
+
+/// Reads the 32 byte words of an ABI packed order directive.
+struct Words<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Words<'a> {
+    fn next(&mut self) -> Result<&'a [u8], anyhow::Error> {
+        let word = self
+            .data
+            .get(self.offset..self.offset + 32)
+            .ok_or_else(|| anyhow!("LongPath order directive ends at byte {}", self.offset))?;
+        self.offset += 32;
+        Ok(word)
+    }
+
+    fn skip(&mut self, words: usize) -> Result<(), anyhow::Error> {
+        for _ in 0..words {
+            self.next()?;
+        }
+        Ok(())
+    }
+
+    fn next_u8(&mut self) -> Result<u8, anyhow::Error> {
+        Ok(self.next()?[31])
+    }
+
+    fn next_address(&mut self) -> Result<Vec<u8>, anyhow::Error> {
+        Ok(self.next()?[12..].to_vec())
+    }
+}
+
+/// Decodes the pools touched by a long form order directive, in the order they are visited.
+///
+/// Every hop of the order trades the pair formed by the previous and the current settlement
+/// token, the base token being the lower address. A hop may visit several pools of that pair.
+pub fn decode_order_pools(order: &[u8]) -> Result<Vec<[u8; 32]>, anyhow::Error> {
+    let mut words = Words { data: order, offset: 0 };
+    let schema = words.next_u8()?;
+    if schema != LONG_FORM_SCHEMA {
+        bail!("Unsupported LongPath order schema: {}", schema);
+    }
+    let mut settle_token = words.next_address()?;
+    words.skip(SETTLEMENT_WORDS - 1)?;
+
+    let mut pools = Vec::new();
+    let hop_count = words.next_u8()?;
+    for _ in 0..hop_count {
+        let mut hop_pool_indices = Vec::new();
+        let pool_count = words.next_u8()?;
+        for _ in 0..pool_count {
+            hop_pool_indices.push(words.next()?.to_vec());
+            words.skip(AMBIENT_WORDS)?;
+            let conc_count = words.next_u8()? as usize;
+            words.skip(conc_count * CONCENTRATED_WORDS + SWAP_WORDS + CHAINING_WORDS)?;
+        }
+
+        let next_token = words.next_address()?;
+        words.skip(SETTLEMENT_WORDS - 1 + IMPROVE_WORDS)?;
+        let (base_token, quote_token) = if settle_token < next_token {
+            (settle_token.clone(), next_token.clone())
+        } else {
+            (next_token.clone(), settle_token.clone())
+        };
+        pools.extend(hop_pool_indices.into_iter().map(|pool_index| {
+            encode_pool_hash(base_token.clone(), quote_token.clone(), pool_index)
+        }));
+        settle_token = next_token;
+    }
+    Ok(pools)
+}
+
+/// Decodes a LongPath `userCmd` call and attributes the base and quote flows of each pool it
+/// visits.
+///
+/// The LongPath sidecar delegates the curve operations of every pool hop to the MicroPaths
+/// sidecar, so the per pool flows are those of the MicroPaths calls nested under this call,
+/// given in `nested_flows`. Pools of the order that were not touched report zero flows. Flows of
+/// pools that are not part of the decoded order are kept as is, so that no flow is dropped.
+pub fn decode_long_path_call(
+    call: &Call,
+    nested_flows: &[([u8; 32], ethabi::Int, ethabi::Int)],
+) -> Result<Vec<([u8; 32], ethabi::Int, ethabi::Int)>, anyhow::Error> {
+    let external_cmd = decode(USER_CMD_EXTERNAL_ABI, &call.input[4..])
+        .map_err(|_| anyhow!("Failed to decode inputs for LongPath userCmd call."))?;
+    let order = external_cmd[0]
+        .to_owned()
+        .into_bytes()
+        .ok_or_else(|| anyhow!("Failed to LongPath userCmd input data.".to_string()))?;
+
+    let mut pool_flows: Vec<([u8; 32], ethabi::Int, ethabi::Int)> = Vec::new();
+    for pool_hash in decode_order_pools(&order)? {
+        if !pool_flows
+            .iter()
+            .any(|(hash, _, _)| *hash == pool_hash)
+        {
+            pool_flows.push((pool_hash, U256::zero(), U256::zero()));
+        }
+    }
+    for (pool_hash, base_flow, quote_flow) in nested_flows {
+        match pool_flows
+            .iter_mut()
+            .find(|(hash, _, _)| hash == pool_hash)
+        {
+            // Flows are two's complement encoded, wrapping addition keeps their sign.
+            Some((_, base, quote)) => {
+                *base = base.overflowing_add(*base_flow).0;
+                *quote = quote.overflowing_add(*quote_flow).0;
+            }
+            None => pool_flows.push((*pool_hash, *base_flow, *quote_flow)),
+        }
+    }
+    Ok(pool_flows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethabi::{encode, Token};
+
+    const ETH: [u8; 20] = [0u8; 20];
+    const USDC: [u8; 20] = hex!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+    const WBTC: [u8; 20] = hex!("2260fac5e5542a773aa44fbcfedf7c193bc2c599");
+
+    fn word(value: u64) -> Token {
+        Token::Uint(value.into())
+    }
+
+    fn settlement(token: [u8; 20]) -> Vec<Token> {
+        vec![Token::Address(token.into()), word(0), word(0), word(0)]
+    }
+
+    /// A pool directive with an ambient and `conc_count` concentrated directives and a swap.
+    fn pool(pool_idx: u64, conc_count: u64) -> Vec<Token> {
+        let mut tokens = vec![word(pool_idx), Token::Bool(true), word(0), word(1_000)];
+        tokens.push(word(conc_count));
+        for _ in 0..conc_count {
+            tokens.extend([word(0), word(64), Token::Bool(true), Token::Bool(true), word(0), word(10)]);
+        }
+        tokens.extend([word(3), word(0), word(1_000), word(u64::MAX), word(0)]);
+        tokens
+    }
+
+    /// Encodes a long form order from `open` through `hops` of (pools, settlement token).
+    fn order(open: [u8; 20], hops: Vec<(Vec<Vec<Token>>, [u8; 20])>) -> Vec<u8> {
+        let mut tokens = vec![word(LONG_FORM_SCHEMA as u64)];
+        tokens.extend(settlement(open));
+        tokens.push(word(hops.len() as u64));
+        for (pools, token) in hops {
+            tokens.push(word(pools.len() as u64));
+            tokens.extend(pools.into_iter().flatten());
+            tokens.extend(settlement(token));
+            tokens.push(word(0));
+        }
+        encode(&tokens)
+    }
+
+    fn long_path_call(order: Vec<u8>) -> Call {
+        let mut input = USER_CMD_LONGPATH_FN_SIG.to_vec();
+        input.extend(encode(&[Token::Bytes(order)]));
+        Call { input, ..Default::default() }
+    }
+
+    fn pool_hash(base: [u8; 20], quote: [u8; 20], pool_idx: u64) -> [u8; 32] {
+        encode_pool_hash(base.to_vec(), quote.to_vec(), encode(&[word(pool_idx)]))
+    }
+
+    #[test]
+    fn test_decode_order_pools_multi_hop() {
+        // USDC -> ETH through two pools, then ETH -> WBTC.
+        let order = order(
+            USDC,
+            vec![(vec![pool(420, 0), pool(36000, 2)], ETH), (vec![pool(420, 1)], WBTC)],
+        );
+
+        assert_eq!(
+            decode_order_pools(&order).unwrap(),
+            vec![pool_hash(ETH, USDC, 420), pool_hash(ETH, USDC, 36000), pool_hash(ETH, WBTC, 420)]
+        );
+    }
+
+    #[test]
+    fn test_decode_order_pools_truncated() {
+        let mut order = order(USDC, vec![(vec![pool(420, 0)], ETH)]);
+        order.truncate(order.len() - 32);
+
+        assert!(decode_order_pools(&order).is_err());
+        assert!(decode_order_pools(&[0u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_decode_long_path_call() {
+        let call = long_path_call(order(
+            USDC,
+            vec![(vec![pool(420, 0), pool(36000, 0)], ETH), (vec![pool(420, 0)], WBTC)],
+        ));
+        let usdc_eth = pool_hash(ETH, USDC, 420);
+        let eth_wbtc = pool_hash(ETH, WBTC, 420);
+        let unknown = [7u8; 32];
+        let minus = |value: u64| U256::zero().overflowing_sub(value.into()).0;
+        let nested_flows = vec![
+            (usdc_eth, minus(5), U256::from(10_000)),
+            (eth_wbtc, U256::from(5), minus(1)),
+            (usdc_eth, minus(1), U256::from(2_000)),
+            (unknown, U256::from(1), U256::from(1)),
+        ];
+
+        let flows = decode_long_path_call(&call, &nested_flows).unwrap();
+
+        assert_eq!(
+            flows,
+            vec![
+                (usdc_eth, minus(6), U256::from(12_000)),
+                (pool_hash(ETH, USDC, 36000), U256::zero(), U256::zero()),
+                (eth_wbtc, U256::from(5), minus(1)),
+                (unknown, U256::from(1), U256::from(1)),
+            ]
+        );
+    }
+}
@@ -28,6 +28,48 @@ const USER_CMD_INTERNAL_ABI: &[ParamType] = &[
 
 pub const INIT_POOL_CODE: u8 = 71;
 
+pub const PROTOCOL_CMD_FN_SIG: [u8; 4] = hex!("13fd34f4");
+
+const PROTOCOL_CMD_EXTERNAL_ABI: &[ParamType] = &[
+    ParamType::Uint(16), // index of the proxy sidecar the command is being called on
+    ParamType::Bytes,    // call data for the sidecar's protocolCmd method
+    ParamType::Bool,     // sudo
+];
+const UPGRADE_PROXY_ABI: &[ParamType] = &[
+    ParamType::Uint(8),  // command
+    ParamType::Address,  // new sidecar
+    ParamType::Uint(16), // index of the upgraded callpath
+];
+
+/// Callpath of the boot sidecar, the only one allowed to upgrade sidecars.
+pub const BOOT_PROXY_IDX: u16 = 0;
+pub const UPGRADE_DEX_CODE: u8 = 21;
+
+/// Callpath of the cold path sidecar, which handles pool template and revision commands.
+pub const COLD_PROXY_IDX: u16 = 3;
+pub const POOL_TEMPLATE_CODE: u8 = 110;
+pub const POOL_REVISE_CODE: u8 = 111;
+
+const POOL_TEMPLATE_ABI: &[ParamType] = &[
+    ParamType::Uint(8),   // command
+    ParamType::Uint(256), // pool index
+    ParamType::Uint(16),  // feeRate
+    ParamType::Uint(16),  // tickSize
+    ParamType::Uint(8),   // jitThresh
+    ParamType::Uint(8),   // knockout
+    ParamType::Uint(8),   // oracleFlags
+];
+const POOL_REVISE_ABI: &[ParamType] = &[
+    ParamType::Uint(8),   // command
+    ParamType::Address,   // base
+    ParamType::Address,   // quote
+    ParamType::Uint(256), // pool index
+    ParamType::Uint(16),  // feeRate
+    ParamType::Uint(16),  // tickSize
+    ParamType::Uint(8),   // jitThresh
+    ParamType::Uint(8),   // knockout
+];
+
 pub const SWAP_ABI_INPUT: &[ParamType] = &[
     ParamType::Address,   // base
     ParamType::Address,   // quote
@@ -94,6 +136,7 @@ pub fn decode_direct_swap_call(
 pub fn decode_pool_init(
     call: &Call,
     tx: Transaction,
+    dex: [u8; 20],
 ) -> Result<Option<ProtocolComponent>, anyhow::Error> {
     // Decode external call to UserCmd
     if let Ok(external_params) = decode(USER_CMD_EXTERNAL_ABI, &call.input[4..]) {
@@ -146,7 +189,7 @@ pub fn decode_pool_init(
                 let new_component = ProtocolComponent {
                     id: hex::encode(pool_hash),
                     tokens,
-                    contracts: vec![AMBIENT_CONTRACT.to_vec()],
+                    contracts: vec![dex.to_vec()],
                     static_att: vec![static_attribute],
                     change: ChangeType::Creation.into(),
                     protocol_type: Some(ProtocolType {
@@ -168,3 +211,197 @@ pub fn decode_pool_init(
         bail!("Failed to decode ABI external call.".to_string());
     }
 }
+
+/// Decodes a `protocolCmd` call that installs a proxy sidecar, returning the upgraded callpath
+/// index and the address of the new sidecar.
+///
+/// Sidecars are upgraded through the boot callpath, any other protocol command is ignored.
+pub fn decode_upgrade_proxy(call: &Call) -> Option<(u16, [u8; 20])> {
+    let external_params = decode(PROTOCOL_CMD_EXTERNAL_ABI, call.input.get(4..)?).ok()?;
+    let callpath = external_params[0]
+        .to_owned()
+        .into_uint()?
+        .as_u32();
+    let cmd = external_params[1]
+        .to_owned()
+        .into_bytes()?;
+    if callpath != BOOT_PROXY_IDX as u32 || cmd.get(31) != Some(&UPGRADE_DEX_CODE) {
+        return None;
+    }
+
+    let upgrade_params = decode(UPGRADE_PROXY_ABI, &cmd).ok()?;
+    let proxy = upgrade_params[1]
+        .to_owned()
+        .into_address()?
+        .to_fixed_bytes();
+    let proxy_idx = upgrade_params[2]
+        .to_owned()
+        .into_uint()?
+        .as_u32() as u16;
+    Some((proxy_idx, proxy))
+}
+
+/// The parameters of a pool that governance may change, see `PoolSpecs.Pool`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolParams {
+    pub fee_rate: u16,
+    pub tick_size: u16,
+    pub jit_thresh: u8,
+    pub knockout: u8,
+}
+
+impl PoolParams {
+    pub fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(6);
+        bytes.extend(self.fee_rate.to_be_bytes());
+        bytes.extend(self.tick_size.to_be_bytes());
+        bytes.extend([self.jit_thresh, self.knockout]);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: [u8; 6] = bytes.try_into().ok()?;
+        Some(Self {
+            fee_rate: u16::from_be_bytes([bytes[0], bytes[1]]),
+            tick_size: u16::from_be_bytes([bytes[2], bytes[3]]),
+            jit_thresh: bytes[4],
+            knockout: bytes[5],
+        })
+    }
+}
+
+/// A protocol command changing the parameters of pools.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PoolCmd {
+    /// Sets the template new pools of the pool index are initialised with.
+    SetTemplate { pool_index: Vec<u8>, params: PoolParams },
+    /// Revises the parameters of an existing pool.
+    RevisePool { pool_hash: [u8; 32], params: PoolParams },
+}
+
+/// Decodes a `protocolCmd` call that sets a pool template or revises a pool.
+///
+/// Pool commands are handled by the cold path, any other protocol command is ignored.
+pub fn decode_pool_cmd(call: &Call) -> Option<PoolCmd> {
+    let external_params = decode(PROTOCOL_CMD_EXTERNAL_ABI, call.input.get(4..)?).ok()?;
+    let callpath = external_params[0]
+        .to_owned()
+        .into_uint()?
+        .as_u32();
+    let cmd = external_params[1]
+        .to_owned()
+        .into_bytes()?;
+    if callpath != COLD_PROXY_IDX as u32 {
+        return None;
+    }
+
+    let code = *cmd.get(31)?;
+    let (abi, offset) = match code {
+        POOL_TEMPLATE_CODE => (POOL_TEMPLATE_ABI, 2),
+        POOL_REVISE_CODE => (POOL_REVISE_ABI, 4),
+        _ => return None,
+    };
+    let params = decode(abi, &cmd).ok()?;
+    let uint = |index: usize| params[index].to_owned().into_uint();
+    let pool_params = PoolParams {
+        fee_rate: uint(offset)?.as_u32() as u16,
+        tick_size: uint(offset + 1)?.as_u32() as u16,
+        jit_thresh: uint(offset + 2)?.as_u32() as u8,
+        knockout: uint(offset + 3)?.as_u32() as u8,
+    };
+    let mut pool_index = [0u8; 32];
+    uint(offset - 1)?.to_big_endian(&mut pool_index);
+
+    match code {
+        POOL_TEMPLATE_CODE => {
+            Some(PoolCmd::SetTemplate { pool_index: pool_index.to_vec(), params: pool_params })
+        }
+        _ => {
+            let base = params[1]
+                .to_owned()
+                .into_address()?
+                .to_fixed_bytes()
+                .to_vec();
+            let quote = params[2]
+                .to_owned()
+                .into_address()?
+                .to_fixed_bytes()
+                .to_vec();
+            Some(PoolCmd::RevisePool {
+                pool_hash: encode_pool_hash(base, quote, pool_index.to_vec()),
+                params: pool_params,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethabi::{encode, Token};
+
+    fn protocol_cmd_call(callpath: u16, cmd: Vec<u8>) -> Call {
+        let mut input = PROTOCOL_CMD_FN_SIG.to_vec();
+        input.extend(encode(&[
+            Token::Uint(callpath.into()),
+            Token::Bytes(cmd),
+            Token::Bool(true),
+        ]));
+        Call { input, ..Default::default() }
+    }
+
+    #[test]
+    fn test_decode_upgrade_proxy() {
+        let cmd = encode(&[
+            Token::Uint(UPGRADE_DEX_CODE.into()),
+            Token::Address([2u8; 20].into()),
+            Token::Uint(4.into()),
+        ]);
+
+        assert_eq!(
+            decode_upgrade_proxy(&protocol_cmd_call(BOOT_PROXY_IDX, cmd.clone())),
+            Some((4, [2u8; 20]))
+        );
+        // Only the boot callpath upgrades sidecars.
+        assert_eq!(decode_upgrade_proxy(&protocol_cmd_call(3, cmd)), None);
+    }
+
+    #[test]
+    fn test_decode_pool_cmd() {
+        let params = PoolParams { fee_rate: 2500, tick_size: 16, jit_thresh: 3, knockout: 0x24 };
+        let template = encode(&[
+            Token::Uint(POOL_TEMPLATE_CODE.into()),
+            Token::Uint(420.into()),
+            Token::Uint(2500.into()),
+            Token::Uint(16.into()),
+            Token::Uint(3.into()),
+            Token::Uint(0x24.into()),
+            Token::Uint(0.into()),
+        ]);
+        let revise = encode(&[
+            Token::Uint(POOL_REVISE_CODE.into()),
+            Token::Address([0u8; 20].into()),
+            Token::Address([1u8; 20].into()),
+            Token::Uint(420.into()),
+            Token::Uint(2500.into()),
+            Token::Uint(16.into()),
+            Token::Uint(3.into()),
+            Token::Uint(0x24.into()),
+        ]);
+        let pool_index = encode(&[Token::Uint(420.into())]);
+
+        assert_eq!(
+            decode_pool_cmd(&protocol_cmd_call(COLD_PROXY_IDX, template.clone())),
+            Some(PoolCmd::SetTemplate { pool_index: pool_index.clone(), params })
+        );
+        assert_eq!(
+            decode_pool_cmd(&protocol_cmd_call(COLD_PROXY_IDX, revise)),
+            Some(PoolCmd::RevisePool {
+                pool_hash: encode_pool_hash(vec![0u8; 20], vec![1u8; 20], pool_index),
+                params
+            })
+        );
+        assert_eq!(decode_pool_cmd(&protocol_cmd_call(BOOT_PROXY_IDX, template)), None);
+        assert_eq!(PoolParams::from_bytes(&params.to_bytes()), Some(params));
+    }
+}
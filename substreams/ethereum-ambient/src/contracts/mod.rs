@@ -11,6 +11,9 @@ pub mod knockout {
 pub mod hotproxy {
     include!("hotproxy.rs");
 }
+pub mod longpath {
+    include!("longpath.rs");
+}
 pub mod main {
     include!("main.rs");
 }
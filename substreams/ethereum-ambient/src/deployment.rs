@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer};
+use substreams::store::{StoreGet, StoreGetRaw};
+use substreams_ethereum::pb::eth::v2::Block;
+
+use crate::contracts::{
+    hotproxy::AMBIENT_HOTPROXY_CONTRACT,
+    knockout::AMBIENT_KNOCKOUT_CONTRACT,
+    main::{decode_upgrade_proxy, AMBIENT_CONTRACT, PROTOCOL_CMD_FN_SIG},
+    micropaths::AMBIENT_MICROPATHS_CONTRACT,
+    warmpath::AMBIENT_WARMPATH_CONTRACT,
+};
+
+/// The contracts of an Ambient deployment: the dex itself and its proxy sidecars.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AmbientContract {
+    Dex,
+    HotProxy,
+    MicroPaths,
+    WarmPath,
+    Knockout,
+    LongPath,
+}
+
+impl AmbientContract {
+    /// Sidecars and the dex's callpath index they are installed at, see `CrocSlots`.
+    const SIDECARS: [(AmbientContract, u16); 5] = [
+        (AmbientContract::HotProxy, 1),
+        (AmbientContract::WarmPath, 2),
+        (AmbientContract::LongPath, 4),
+        (AmbientContract::MicroPaths, 5),
+        (AmbientContract::Knockout, 7),
+    ];
+}
+
+/// Returns the key of the sidecar installed at `callpath` in `store_proxy_paths`.
+pub fn proxy_path_key(callpath: u16) -> String {
+    format!("proxy:{callpath}")
+}
+
+/// Addresses of an Ambient (CrocSwap) deployment, configured through module params, e.g.
+/// `dex=aaaaaaaaa24eeeb8d57d431224f73832bc34f688&
+/// warmpath=d268767BE4597151Ce2BB4a70A9E368ff26cB195`.
+///
+/// Addresses that are not configured default to the mainnet deployment. The LongPath sidecar has
+/// no default: it is only known once configured or once its installation is recorded by
+/// `store_proxy_paths`, which requires the modules to start no later than that installation.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Deployment {
+    #[serde(default = "default_dex", deserialize_with = "deserialize_address")]
+    pub dex: [u8; 20],
+    #[serde(default = "default_hotproxy", deserialize_with = "deserialize_address")]
+    pub hotproxy: [u8; 20],
+    #[serde(default = "default_micropaths", deserialize_with = "deserialize_address")]
+    pub micropaths: [u8; 20],
+    #[serde(default = "default_warmpath", deserialize_with = "deserialize_address")]
+    pub warmpath: [u8; 20],
+    #[serde(default = "default_knockout", deserialize_with = "deserialize_address")]
+    pub knockout: [u8; 20],
+    #[serde(default, deserialize_with = "deserialize_optional_address")]
+    pub longpath: Option<[u8; 20]>,
+}
+
+impl Default for Deployment {
+    fn default() -> Self {
+        Self {
+            dex: AMBIENT_CONTRACT,
+            hotproxy: AMBIENT_HOTPROXY_CONTRACT,
+            micropaths: AMBIENT_MICROPATHS_CONTRACT,
+            warmpath: AMBIENT_WARMPATH_CONTRACT,
+            knockout: AMBIENT_KNOCKOUT_CONTRACT,
+            longpath: None,
+        }
+    }
+}
+
+impl Deployment {
+    pub fn parse(params: &str) -> Result<Self> {
+        serde_qs::from_str(params).context("Unable to deserialize params")
+    }
+
+    /// Resolves the address of the dex and each of its sidecars as of `ordinal`.
+    ///
+    /// Sidecars upgraded through the dex, as recorded by `store_proxy_paths`, take precedence over
+    /// the configured addresses. Resolve at the ordinal of the call to decode, so calls before an
+    /// upgrade within the same block are still attributed to the previous sidecar.
+    pub fn resolve(&self, proxy_paths: &StoreGetRaw, ordinal: u64) -> Contracts {
+        let mut contracts = self.contracts(|callpath| {
+            proxy_paths
+                .get_at(ordinal, proxy_path_key(callpath))
+                .and_then(|address| address.try_into().ok())
+        });
+        contracts.retain(|address, _| *address != [0u8; 20]);
+        Contracts(contracts)
+    }
+
+    /// Sidecar upgrades within `block`, as ordinal of the upgrading call and the callpath and
+    /// address of the new sidecar.
+    pub fn upgrades<'a>(
+        &'a self,
+        block: &'a Block,
+    ) -> impl Iterator<Item = (u64, (u16, [u8; 20]))> + 'a {
+        block
+            .transactions()
+            .flat_map(|tx| {
+                tx.calls
+                    .iter()
+                    .filter(|call| !call.state_reverted)
+            })
+            .filter(|call| {
+                call.address == self.dex && call.input.get(0..4) == Some(&PROTOCOL_CMD_FN_SIG)
+            })
+            .filter_map(|call| Some((call.begin_ordinal, decode_upgrade_proxy(call)?)))
+    }
+
+    fn contracts(
+        &self,
+        upgraded: impl Fn(u16) -> Option<[u8; 20]>,
+    ) -> HashMap<[u8; 20], AmbientContract> {
+        AmbientContract::SIDECARS
+            .iter()
+            .filter_map(|(contract, callpath)| {
+                let configured = match contract {
+                    AmbientContract::Dex => Some(self.dex),
+                    AmbientContract::HotProxy => Some(self.hotproxy),
+                    AmbientContract::WarmPath => Some(self.warmpath),
+                    AmbientContract::MicroPaths => Some(self.micropaths),
+                    AmbientContract::Knockout => Some(self.knockout),
+                    AmbientContract::LongPath => self.longpath,
+                };
+                Some((upgraded(*callpath).or(configured)?, *contract))
+            })
+            .chain([(self.dex, AmbientContract::Dex)])
+            .collect()
+    }
+}
+
+/// The current addresses of an Ambient deployment's contracts.
+pub struct Contracts(HashMap<[u8; 20], AmbientContract>);
+
+impl Contracts {
+    /// Returns which contract of the deployment lives at `address`, if any.
+    pub fn get(&self, address: &[u8]) -> Option<AmbientContract> {
+        let address: [u8; 20] = address.try_into().ok()?;
+        self.0.get(&address).copied()
+    }
+}
+
+/// Resolves the contracts of a deployment for the calls of a block.
+///
+/// Resolving reads each sidecar from `store_proxy_paths`, the contracts are therefore only
+/// resolved again for calls following one of the block's sidecar upgrades.
+pub struct BlockContracts<F> {
+    upgrade_ordinals: Vec<u64>,
+    resolve: F,
+    resolved: Option<(usize, Contracts)>,
+}
+
+impl<F: Fn(u64) -> Contracts> BlockContracts<F> {
+    /// `resolve` returns the contracts as of an ordinal, e.g. through [`Deployment::resolve`].
+    pub fn new(mut upgrade_ordinals: Vec<u64>, resolve: F) -> Self {
+        upgrade_ordinals.sort_unstable();
+        Self { upgrade_ordinals, resolve, resolved: None }
+    }
+
+    /// Returns the contracts as of `ordinal`.
+    pub fn at(&mut self, ordinal: u64) -> &Contracts {
+        let upgrades = self
+            .upgrade_ordinals
+            .partition_point(|upgrade| *upgrade <= ordinal);
+        if !matches!(&self.resolved, Some((resolved, _)) if *resolved == upgrades) {
+            self.resolved = Some((upgrades, (self.resolve)(ordinal)));
+        }
+        &self
+            .resolved
+            .as_ref()
+            .expect("Contracts are resolved above")
+            .1
+    }
+}
+
+fn default_dex() -> [u8; 20] {
+    AMBIENT_CONTRACT
+}
+
+fn default_hotproxy() -> [u8; 20] {
+    AMBIENT_HOTPROXY_CONTRACT
+}
+
+fn default_micropaths() -> [u8; 20] {
+    AMBIENT_MICROPATHS_CONTRACT
+}
+
+fn default_warmpath() -> [u8; 20] {
+    AMBIENT_WARMPATH_CONTRACT
+}
+
+fn default_knockout() -> [u8; 20] {
+    AMBIENT_KNOCKOUT_CONTRACT
+}
+
+fn deserialize_optional_address<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<[u8; 20]>, D::Error> {
+    deserialize_address(deserializer).map(Some)
+}
+
+fn deserialize_address<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 20], D::Error> {
+    let address = String::deserialize(deserializer)?;
+    hex::decode(address.trim_start_matches("0x"))
+        .map_err(serde::de::Error::custom)?
+        .try_into()
+        .map_err(|_| serde::de::Error::custom("Expected a 20 byte address"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_parse_defaults() {
+        assert_eq!(Deployment::parse("").unwrap(), Deployment::default());
+    }
+
+    #[test]
+    fn test_parse_addresses() {
+        let deployment = Deployment::parse(
+            "dex=0xaaaaaaaaa24eeeb8d57d431224f73832bc34f688&warmpath=0101010101010101010101010101010101010101",
+        )
+        .unwrap();
+
+        assert_eq!(deployment.warmpath, [1u8; 20]);
+        assert_eq!(deployment.knockout, AMBIENT_KNOCKOUT_CONTRACT);
+        assert_eq!(deployment.longpath, None);
+        assert!(Deployment::parse("dex=0x01").is_err());
+    }
+
+    #[test]
+    fn test_contracts_upgraded_sidecar() {
+        let deployment = Deployment::default();
+
+        let contracts =
+            Contracts(deployment.contracts(|callpath| (callpath == 2).then_some([1u8; 20])));
+
+        assert_eq!(
+            contracts.get(&hex!("aaaaaaaaa24eeeb8d57d431224f73832bc34f688")),
+            Some(AmbientContract::Dex)
+        );
+        assert_eq!(contracts.get(&[1u8; 20]), Some(AmbientContract::WarmPath));
+        assert_eq!(contracts.get(&AMBIENT_WARMPATH_CONTRACT), None);
+        assert_eq!(contracts.get(&AMBIENT_HOTPROXY_CONTRACT), Some(AmbientContract::HotProxy));
+    }
+
+    #[test]
+    fn test_contracts_longpath() {
+        let deployment = Deployment::default();
+        let configured =
+            Deployment::parse(&format!("longpath={}", hex::encode([2u8; 20]))).unwrap();
+
+        let unknown = Contracts(deployment.contracts(|_| None));
+        let installed =
+            Contracts(deployment.contracts(|callpath| (callpath == 4).then_some([1u8; 20])));
+
+        assert_eq!(unknown.get(&[0u8; 20]), None);
+        assert_eq!(installed.get(&[1u8; 20]), Some(AmbientContract::LongPath));
+        assert_eq!(
+            Contracts(configured.contracts(|_| None)).get(&[2u8; 20]),
+            Some(AmbientContract::LongPath)
+        );
+    }
+
+    #[test]
+    fn test_block_contracts_resolve_after_upgrades_only() {
+        let resolved = std::cell::Cell::new(0);
+        let mut contracts = BlockContracts::new(vec![10], |ordinal| {
+            resolved.set(resolved.get() + 1);
+            let warmpath = if ordinal < 10 { [1u8; 20] } else { [2u8; 20] };
+            Contracts(HashMap::from([(warmpath, AmbientContract::WarmPath)]))
+        });
+
+        assert_eq!(contracts.at(1).get(&[1u8; 20]), Some(AmbientContract::WarmPath));
+        assert_eq!(contracts.at(5).get(&[1u8; 20]), Some(AmbientContract::WarmPath));
+        assert_eq!(resolved.get(), 1);
+        assert_eq!(contracts.at(10).get(&[2u8; 20]), Some(AmbientContract::WarmPath));
+        assert_eq!(contracts.at(12).get(&[1u8; 20]), None);
+        assert_eq!(resolved.get(), 2);
+    }
+}
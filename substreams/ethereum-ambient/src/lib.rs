@@ -1,4 +1,7 @@
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
 mod contracts;
+mod deployment;
 
 pub use modules::*;
 mod modules;
@@ -0,0 +1,37 @@
+use substreams::store::{StoreNew, StoreSet, StoreSetRaw};
+use substreams_ethereum::pb::eth;
+
+use crate::{
+    contracts::main::{decode_pool_cmd, PoolCmd, PROTOCOL_CMD_FN_SIG},
+    deployment::Deployment,
+};
+
+/// Tracks the template of each pool index, new pools are initialised with the parameters of their
+/// index's template.
+///
+/// Templates are set by the protocol authority through `protocolCmd` on the dex, the encoded
+/// `PoolParams` are stored under `template:{pool_index}`.
+#[substreams::handlers::store]
+pub fn store_pool_templates(params: String, block: eth::v2::Block, store: StoreSetRaw) {
+    let deployment = Deployment::parse(&params).expect("Invalid deployment params");
+    block
+        .transactions()
+        .flat_map(|tx| {
+            tx.calls
+                .iter()
+                .filter(|call| !call.state_reverted)
+        })
+        .filter(|call| {
+            call.address == deployment.dex && call.input.get(0..4) == Some(&PROTOCOL_CMD_FN_SIG)
+        })
+        .for_each(|call| {
+            if let Some(PoolCmd::SetTemplate { pool_index, params }) = decode_pool_cmd(call) {
+                store.set(call.begin_ordinal, template_key(&pool_index), &params.to_bytes());
+            }
+        });
+}
+
+/// Returns the key of the template of `pool_index` in `store_pool_templates`.
+pub fn template_key(pool_index: &[u8]) -> String {
+    format!("template:{}", hex::encode(pool_index))
+}
@@ -0,0 +1,18 @@
+use substreams::store::{StoreNew, StoreSet, StoreSetRaw};
+use substreams_ethereum::pb::eth;
+
+use crate::deployment::{proxy_path_key, Deployment};
+
+/// Tracks the sidecar installed at each of the dex's callpaths.
+///
+/// Sidecars are upgraded by the protocol authority through `protocolCmd` on the dex, the new
+/// address is stored under `proxy:{callpath}`.
+#[substreams::handlers::store]
+pub fn store_proxy_paths(params: String, block: eth::v2::Block, store: StoreSetRaw) {
+    let deployment = Deployment::parse(&params).expect("Invalid deployment params");
+    deployment
+        .upgrades(&block)
+        .for_each(|(ordinal, (callpath, proxy))| {
+            store.set(ordinal, proxy_path_key(callpath), &proxy.to_vec());
+        });
+}
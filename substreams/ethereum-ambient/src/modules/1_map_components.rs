@@ -0,0 +1,36 @@
+use substreams_ethereum::pb::eth;
+use tycho_substreams::prelude::*;
+
+use crate::{
+    contracts::main::{decode_pool_init, USER_CMD_FN_SIG},
+    deployment::Deployment,
+};
+
+/// Extracts the pools initialised through the dex's `userCmd`, grouped by transaction.
+#[substreams::handlers::map]
+fn map_components(
+    params: String,
+    block: eth::v2::Block,
+) -> Result<BlockTransactionProtocolComponents, substreams::errors::Error> {
+    let deployment = Deployment::parse(&params)?;
+    let mut tx_components = Vec::new();
+    for block_tx in block.transactions() {
+        let tx: Transaction = block_tx.into();
+        let mut components = Vec::new();
+        for call in block_tx
+            .calls
+            .iter()
+            .filter(|call| !call.state_reverted)
+        {
+            if call.address == deployment.dex && call.input.get(0..4) == Some(&USER_CMD_FN_SIG) {
+                if let Some(component) = decode_pool_init(call, tx.clone(), deployment.dex)? {
+                    components.push(component);
+                }
+            }
+        }
+        if !components.is_empty() {
+            tx_components.push(TransactionProtocolComponents { tx: Some(tx), components });
+        }
+    }
+    Ok(BlockTransactionProtocolComponents { tx_components })
+}
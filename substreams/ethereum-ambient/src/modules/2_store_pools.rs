@@ -1,9 +1,16 @@
 use substreams::store::{StoreNew, StoreSet, StoreSetProto};
-use tycho_substreams::models::{BlockPoolChanges, ProtocolComponent};
+use tycho_substreams::models::{BlockTransactionProtocolComponents, ProtocolComponent};
 
 #[substreams::handlers::store]
-pub fn store_pools(changes: BlockPoolChanges, component_store: StoreSetProto<ProtocolComponent>) {
-    for component in changes.protocol_components {
+pub fn store_pools(
+    map: BlockTransactionProtocolComponents,
+    component_store: StoreSetProto<ProtocolComponent>,
+) {
+    for component in map
+        .tx_components
+        .into_iter()
+        .flat_map(|tx_components| tx_components.components)
+    {
         component_store.set(0, component.id.clone(), &component);
     }
 }
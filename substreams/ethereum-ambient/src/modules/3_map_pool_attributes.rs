@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+
+use substreams::{
+    scalar::BigInt,
+    store::{StoreGet, StoreGetProto, StoreGetRaw},
+};
+use substreams_ethereum::pb::eth;
+use tycho_substreams::prelude::*;
+
+use super::store_pool_templates::template_key;
+use crate::{
+    contracts::main::{
+        decode_pool_cmd, decode_pool_init, PoolCmd, PoolParams, PROTOCOL_CMD_FN_SIG,
+        USER_CMD_FN_SIG,
+    },
+    deployment::Deployment,
+};
+
+/// Extracts the governance controlled parameters of each pool as state attributes.
+///
+/// Pools are initialised with the template of their pool index, the parameters are emitted as
+/// `fee_rate`, `tick_size`, `jit_thresh` and `knockout` on creation and updated whenever the pool
+/// is revised through `protocolCmd`.
+#[substreams::handlers::map]
+fn map_pool_attributes(
+    params: String,
+    block: eth::v2::Block,
+    templates_store: StoreGetRaw,
+    pool_store: StoreGetProto<ProtocolComponent>,
+) -> Result<BlockChanges, substreams::errors::Error> {
+    let deployment = Deployment::parse(&params)?;
+    let changes = pool_params_transaction_changes(
+        &block,
+        deployment.dex,
+        |ordinal, pool_index| {
+            templates_store
+                .get_at(ordinal, template_key(pool_index))
+                .and_then(|bytes| PoolParams::from_bytes(&bytes))
+        },
+        |component_id| {
+            pool_store
+                .get_last(component_id)
+                .is_some()
+        },
+    )?;
+    Ok(BlockChanges { block: Some((&block).into()), changes })
+}
+
+/// Collects the pool parameter changes of each transaction, in transaction order.
+///
+/// `template_at` returns the template of a pool index as of an ordinal, `is_known_pool` whether a
+/// component exists.
+fn pool_params_transaction_changes(
+    block: &eth::v2::Block,
+    dex: [u8; 20],
+    template_at: impl Fn(u64, &[u8]) -> Option<PoolParams>,
+    is_known_pool: impl Fn(&str) -> bool,
+) -> Result<Vec<TransactionChanges>, anyhow::Error> {
+    let mut transaction_changes: BTreeMap<u64, TransactionChangesBuilder> = BTreeMap::new();
+    for block_tx in block.transactions() {
+        let tx: Transaction = block_tx.into();
+        for call in block_tx
+            .calls
+            .iter()
+            .filter(|call| !call.state_reverted && call.address == dex)
+        {
+            let entity_change = match call.input.get(0..4) {
+                Some(selector) if selector == USER_CMD_FN_SIG => {
+                    let Some(component) = decode_pool_init(call, tx.clone(), dex)? else {
+                        continue;
+                    };
+                    let pool_index = component
+                        .get_attribute_value("pool_index")
+                        .expect("Pool index is missing");
+                    let Some(template) = template_at(call.begin_ordinal, &pool_index) else {
+                        continue;
+                    };
+                    pool_params_changes(&component.id, template, ChangeType::Creation)
+                }
+                Some(selector) if selector == PROTOCOL_CMD_FN_SIG => {
+                    let Some(PoolCmd::RevisePool { pool_hash, params }) = decode_pool_cmd(call)
+                    else {
+                        continue;
+                    };
+                    let component_id = hex::encode(pool_hash);
+                    if !is_known_pool(&component_id) {
+                        continue;
+                    }
+                    pool_params_changes(&component_id, params, ChangeType::Update)
+                }
+                _ => continue,
+            };
+            transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(&tx))
+                .add_entity_change(&entity_change);
+        }
+    }
+    Ok(transaction_changes
+        .into_values()
+        .filter_map(|builder| builder.build())
+        .collect())
+}
+
+fn pool_params_changes(
+    component_id: &str,
+    params: PoolParams,
+    change: ChangeType,
+) -> EntityChanges {
+    let attributes = [
+        ("fee_rate", BigInt::from(params.fee_rate as u64)),
+        ("tick_size", BigInt::from(params.tick_size as u64)),
+        ("jit_thresh", BigInt::from(params.jit_thresh as u64)),
+        ("knockout", BigInt::from(params.knockout as u64)),
+    ];
+    EntityChanges {
+        component_id: component_id.to_string(),
+        attributes: attributes
+            .into_iter()
+            .map(|(name, value)| Attribute {
+                name: name.to_string(),
+                value: value.to_signed_bytes_be(),
+                change: change.into(),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        contracts::main::{COLD_PROXY_IDX, INIT_POOL_CODE, POOL_REVISE_CODE},
+        utils::encode_pool_hash,
+    };
+    use ethabi::{encode, Token};
+    use substreams_ethereum::pb::eth::v2::{Call, TransactionTrace, TransactionTraceStatus};
+
+    const DEX: [u8; 20] = [0xaa; 20];
+    const BASE: [u8; 20] = [0u8; 20];
+    const QUOTE: [u8; 20] = [1u8; 20];
+
+    fn call(selector: [u8; 4], params: Vec<Token>, begin_ordinal: u64) -> Call {
+        let mut input = selector.to_vec();
+        input.extend(encode(&params));
+        Call { address: DEX.to_vec(), input, begin_ordinal, ..Default::default() }
+    }
+
+    fn transaction(index: u32, calls: Vec<Call>) -> TransactionTrace {
+        TransactionTrace {
+            hash: vec![index as u8],
+            index,
+            status: TransactionTraceStatus::Succeeded.into(),
+            calls,
+            ..Default::default()
+        }
+    }
+
+    fn attributes(changes: &TransactionChanges) -> Vec<(String, Vec<u8>, ChangeType)> {
+        let mut attributes = changes
+            .entity_changes
+            .iter()
+            .flat_map(|entity_change| entity_change.attributes.iter())
+            .map(|attr| (attr.name.clone(), attr.value.clone(), attr.change()))
+            .collect::<Vec<_>>();
+        attributes.sort();
+        attributes
+    }
+
+    #[test]
+    fn test_pool_params_transaction_changes() {
+        let template = PoolParams { fee_rate: 2500, tick_size: 16, jit_thresh: 3, knockout: 0x24 };
+        let init_pool = call(
+            USER_CMD_FN_SIG,
+            vec![
+                Token::Uint(3.into()),
+                Token::Bytes(encode(&[
+                    Token::Uint(INIT_POOL_CODE.into()),
+                    Token::Address(BASE.into()),
+                    Token::Address(QUOTE.into()),
+                    Token::Uint(420.into()),
+                    Token::Uint(1.into()),
+                ])),
+            ],
+            10,
+        );
+        let revise_pool = call(
+            PROTOCOL_CMD_FN_SIG,
+            vec![
+                Token::Uint(COLD_PROXY_IDX.into()),
+                Token::Bytes(encode(&[
+                    Token::Uint(POOL_REVISE_CODE.into()),
+                    Token::Address(BASE.into()),
+                    Token::Address(QUOTE.into()),
+                    Token::Uint(420.into()),
+                    Token::Uint(500.into()),
+                    Token::Uint(16.into()),
+                    Token::Uint(3.into()),
+                    Token::Uint(0x24.into()),
+                ])),
+                Token::Bool(true),
+            ],
+            20,
+        );
+        let block = eth::v2::Block {
+            transaction_traces: vec![
+                transaction(2, vec![revise_pool]),
+                transaction(1, vec![init_pool]),
+            ],
+            ..Default::default()
+        };
+        let pool_id = hex::encode(encode_pool_hash(
+            BASE.to_vec(),
+            QUOTE.to_vec(),
+            encode(&[Token::Uint(420.into())]),
+        ));
+
+        let res = pool_params_transaction_changes(
+            &block,
+            DEX,
+            |ordinal, _| (ordinal == 10).then_some(template),
+            |component_id| component_id == pool_id,
+        )
+        .unwrap();
+
+        let value = |value: u64| BigInt::from(value).to_signed_bytes_be();
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].tx.as_ref().unwrap().index, 1);
+        assert_eq!(res[0].entity_changes[0].component_id, pool_id);
+        assert_eq!(
+            attributes(&res[0]),
+            vec![
+                ("fee_rate".to_string(), value(2500), ChangeType::Creation),
+                ("jit_thresh".to_string(), value(3), ChangeType::Creation),
+                ("knockout".to_string(), value(0x24), ChangeType::Creation),
+                ("tick_size".to_string(), value(16), ChangeType::Creation),
+            ]
+        );
+        assert_eq!(res[1].tx.as_ref().unwrap().index, 2);
+        assert_eq!(
+            attributes(&res[1]),
+            vec![
+                ("fee_rate".to_string(), value(500), ChangeType::Update),
+                ("jit_thresh".to_string(), value(3), ChangeType::Update),
+                ("knockout".to_string(), value(0x24), ChangeType::Update),
+                ("tick_size".to_string(), value(16), ChangeType::Update),
+            ]
+        );
+    }
+}
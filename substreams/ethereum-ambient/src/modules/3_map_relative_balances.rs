@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use substreams::store::{StoreGet, StoreGetProto, StoreGetRaw};
+use substreams_ethereum::pb::eth::{self};
+
+use crate::{
+    contracts::{
+        hotproxy::{decode_direct_swap_hotproxy_call, USER_CMD_HOTPROXY_FN_SIG},
+        knockout::{decode_knockout_call, USER_CMD_KNOCKOUT_FN_SIG},
+        longpath::{decode_long_path_call, USER_CMD_LONGPATH_FN_SIG},
+        main::{decode_direct_swap_call, SWAP_FN_SIG},
+        micropaths::{
+            decode_burn_ambient_call, decode_burn_range_call, decode_mint_ambient_call,
+            decode_mint_range_call, decode_sweep_swap_call, BURN_AMBIENT_FN_SIG, BURN_RANGE_FN_SIG,
+            MINT_AMBIENT_FN_SIG, MINT_RANGE_FN_SIG, SWEEP_SWAP_FN_SIG,
+        },
+        warmpath::{decode_warm_path_user_cmd_call, USER_CMD_WARMPATH_FN_SIG},
+    },
+    deployment::{AmbientContract, BlockContracts, Deployment},
+    utils::from_u256_to_vec,
+};
+use tycho_substreams::prelude::*;
+
+/// Extracts the base and quote flows of every pool touched by the dex or one of its sidecars.
+///
+/// Deltas are ordered by the ordinal of the call they were decoded from, the base and quote token
+/// are resolved from the pool's component.
+#[substreams::handlers::map]
+fn map_relative_balances(
+    params: String,
+    block: eth::v2::Block,
+    proxy_paths_store: StoreGetRaw,
+    pool_store: StoreGetProto<ProtocolComponent>,
+) -> Result<BlockBalanceDeltas, substreams::errors::Error> {
+    let deployment = Deployment::parse(&params)?;
+    let mut contracts = BlockContracts::new(
+        deployment
+            .upgrades(&block)
+            .map(|(ordinal, _)| ordinal)
+            .collect(),
+        |ordinal| deployment.resolve(&proxy_paths_store, ordinal),
+    );
+    let mut balance_deltas = Vec::new();
+    for block_tx in block.transactions() {
+        let tx: Transaction = block_tx.into();
+
+        let block_calls = block_tx
+            .calls
+            .iter()
+            .filter(|call| !call.state_reverted)
+            .collect::<Vec<_>>();
+
+        // Enclosing LongPath call of each call nested under one, by call index.
+        let mut long_paths: HashMap<u32, u32> = HashMap::new();
+        // MicroPaths flows of the pool hops of each LongPath call, by the LongPath call index.
+        let mut long_path_flows: HashMap<u32, Vec<_>> = HashMap::new();
+        let mut long_path_calls = Vec::new();
+
+        for call in block_calls {
+            if let Some(long_path) = long_paths
+                .get(&call.parent_index)
+                .copied()
+            {
+                long_paths.insert(call.index, long_path);
+            }
+            if call.input.len() < 4 {
+                continue;
+            }
+            let selector: [u8; 4] = call.input[0..4].try_into().unwrap();
+            let Some(contract) = contracts
+                .at(call.begin_ordinal)
+                .get(&call.address)
+            else {
+                continue;
+            };
+
+            if contract == AmbientContract::LongPath && selector == USER_CMD_LONGPATH_FN_SIG {
+                // The flows of its pool hops are attributed once its nested calls are decoded
+                long_paths.insert(call.index, call.index);
+                long_path_calls.push(call);
+                continue;
+            }
+
+            // Extract TVL changes
+            let result = match (contract, selector) {
+                (AmbientContract::Dex, SWAP_FN_SIG) => Some(decode_direct_swap_call(call)?),
+                (AmbientContract::HotProxy, USER_CMD_HOTPROXY_FN_SIG) => {
+                    Some(decode_direct_swap_hotproxy_call(call)?)
+                }
+                (AmbientContract::MicroPaths, SWEEP_SWAP_FN_SIG) => {
+                    Some(decode_sweep_swap_call(call)?)
+                }
+                (AmbientContract::WarmPath, USER_CMD_WARMPATH_FN_SIG) => {
+                    decode_warm_path_user_cmd_call(call)?
+                }
+                (AmbientContract::MicroPaths, MINT_RANGE_FN_SIG) => {
+                    Some(decode_mint_range_call(call)?)
+                }
+                (AmbientContract::MicroPaths, MINT_AMBIENT_FN_SIG) => {
+                    Some(decode_mint_ambient_call(call)?)
+                }
+                (AmbientContract::MicroPaths, BURN_RANGE_FN_SIG) => {
+                    Some(decode_burn_range_call(call)?)
+                }
+                (AmbientContract::MicroPaths, BURN_AMBIENT_FN_SIG) => {
+                    Some(decode_burn_ambient_call(call)?)
+                }
+                (AmbientContract::Knockout, USER_CMD_KNOCKOUT_FN_SIG) => {
+                    Some(decode_knockout_call(call)?)
+                }
+                _ => None,
+            };
+            let Some((pool_hash, base_flow, quote_flow)) = result else {
+                continue;
+            };
+            match long_paths.get(&call.index) {
+                Some(long_path) => long_path_flows
+                    .entry(*long_path)
+                    .or_default()
+                    .push((pool_hash, base_flow, quote_flow)),
+                None => balance_deltas.extend(pool_balance_deltas(
+                    &pool_store,
+                    pool_hash,
+                    base_flow,
+                    quote_flow,
+                    call,
+                    &tx,
+                )?),
+            }
+        }
+
+        for call in long_path_calls {
+            let nested_flows = long_path_flows
+                .remove(&call.index)
+                .unwrap_or_default();
+            for (pool_hash, base_flow, quote_flow) in decode_long_path_call(call, &nested_flows)? {
+                balance_deltas.extend(pool_balance_deltas(
+                    &pool_store,
+                    pool_hash,
+                    base_flow,
+                    quote_flow,
+                    call,
+                    &tx,
+                )?);
+            }
+        }
+    }
+    balance_deltas.sort_by_key(|delta| delta.ord);
+    Ok(BlockBalanceDeltas { balance_deltas })
+}
+
+/// Builds the deltas of the pool's base and quote token, the pool's tokens are sorted so the base
+/// token comes first.
+fn pool_balance_deltas(
+    pool_store: &StoreGetProto<ProtocolComponent>,
+    pool_hash: [u8; 32],
+    base_flow: ethabi::Int,
+    quote_flow: ethabi::Int,
+    call: &eth::v2::Call,
+    tx: &Transaction,
+) -> Result<[BalanceDelta; 2], substreams::errors::Error> {
+    let component_id = hex::encode(pool_hash);
+    let pool = pool_store
+        .get_last(&component_id)
+        .ok_or_else(|| anyhow!("Pool not found in store for given hash: {}", component_id))?;
+    let [base, quote] = [0, 1].map(|index| pool.tokens[index].clone());
+    Ok([(base, base_flow), (quote, quote_flow)].map(|(token, flow)| BalanceDelta {
+        ord: call.begin_ordinal,
+        tx: Some(tx.clone()),
+        token,
+        delta: from_u256_to_vec(flow),
+        component_id: component_id.as_bytes().to_vec(),
+    }))
+}
@@ -0,0 +1,7 @@
+use substreams::store::{StoreAddBigInt, StoreNew};
+use tycho_substreams::models::BlockBalanceDeltas;
+
+#[substreams::handlers::store]
+pub fn store_pool_balances(deltas: BlockBalanceDeltas, balance_store: StoreAddBigInt) {
+    tycho_substreams::balances::store_balance_changes(deltas, balance_store);
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use substreams::pb::substreams::StoreDeltas;
+use substreams_ethereum::pb::eth;
+use tycho_substreams::{
+    aggregation::AggregationMode, balances::aggregate_balances_changes,
+    contract::extract_contract_changes_builder, prelude::*,
+};
+
+use crate::deployment::Deployment;
+
+/// Emits all changes relevant to vm simulations as `BlockChanges`.
+///
+/// New pools, pool parameters, absolute pool balances and the storage, balance and code changes of
+/// the dex are merged by transaction and emitted in transaction order. Sidecars run delegated by
+/// the dex, so their changes are recorded as changes of the dex.
+#[substreams::handlers::map]
+fn map_changes(
+    params: String,
+    block: eth::v2::Block,
+    grouped_components: BlockTransactionProtocolComponents,
+    pool_attributes: BlockChanges,
+    deltas: BlockBalanceDeltas,
+    balance_store: StoreDeltas, // Note, this map module is using the `deltas` mode for the store.
+) -> Result<BlockChanges, substreams::errors::Error> {
+    let deployment = Deployment::parse(&params)?;
+    // We merge contract changes by transaction (identified by transaction index) making it easy to
+    //  sort them at the very end.
+    let mut transaction_changes: HashMap<_, TransactionChangesBuilder> = HashMap::new();
+
+    grouped_components
+        .tx_components
+        .iter()
+        .for_each(|tx_component| {
+            let tx = tx_component.tx.as_ref().unwrap();
+            let builder = transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(tx));
+            tx_component
+                .components
+                .iter()
+                .for_each(|component| builder.add_protocol_component(component));
+        });
+
+    pool_attributes
+        .changes
+        .iter()
+        .for_each(|tx_change| {
+            let tx = tx_change.tx.as_ref().unwrap();
+            let builder = transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(tx));
+            tx_change
+                .entity_changes
+                .iter()
+                .for_each(|entity_change| builder.add_entity_change(entity_change));
+        });
+
+    aggregate_balances_changes(balance_store, deltas)
+        .into_iter()
+        .for_each(|(_, (tx, balances))| {
+            let builder = transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(&tx));
+            balances
+                .values()
+                .flat_map(|token_bc_map| token_bc_map.values())
+                .for_each(|bc| builder.add_balance_change(bc));
+        });
+
+    extract_contract_changes_builder(
+        &block,
+        |addr| addr == deployment.dex,
+        &mut transaction_changes,
+    );
+
+    let mut changes = transaction_changes
+        .into_iter()
+        .collect::<Vec<_>>();
+    changes.sort_unstable_by_key(|(index, _)| *index);
+    Ok(AggregationMode::from_params(&params)?.apply(BlockChanges {
+        block: Some((&block).into()),
+        changes: changes
+            .into_iter()
+            .filter_map(|(_, builder)| builder.build())
+            .collect(),
+    }))
+}
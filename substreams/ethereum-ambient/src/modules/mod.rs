@@ -1,16 +1,32 @@
 pub use map_changes::map_changes;
-pub use map_pool_changes::map_pool_changes;
+pub use map_components::map_components;
+pub use map_pool_attributes::map_pool_attributes;
+pub use map_relative_balances::map_relative_balances;
 pub use store_pool_balances::store_pool_balances;
+pub use store_pool_templates::store_pool_templates;
 pub use store_pools::store_pools;
+pub use store_proxy_paths::store_proxy_paths;
 
-#[path = "1_map_pool_changes.rs"]
-mod map_pool_changes;
+#[path = "0_store_proxy_paths.rs"]
+mod store_proxy_paths;
+
+#[path = "0_store_pool_templates.rs"]
+mod store_pool_templates;
+
+#[path = "1_map_components.rs"]
+mod map_components;
 
 #[path = "2_store_pools.rs"]
 mod store_pools;
 
-#[path = "2_store_pool_balances.rs"]
+#[path = "3_map_relative_balances.rs"]
+mod map_relative_balances;
+
+#[path = "3_map_pool_attributes.rs"]
+mod map_pool_attributes;
+
+#[path = "4_store_pool_balances.rs"]
 mod store_pool_balances;
 
-#[path = "3_map_changes.rs"]
+#[path = "5_map_changes.rs"]
 mod map_changes;
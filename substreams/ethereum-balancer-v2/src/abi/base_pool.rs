@@ -0,0 +1,770 @@
+    const INTERNAL_ERR: &'static str = "`ethabi_derive` internal error";
+    /// Contract's functions.
+    #[allow(dead_code, unused_imports, unused_variables)]
+    pub mod functions {
+        use super::INTERNAL_ERR;
+    }
+    /// Contract's events.
+    #[allow(dead_code, unused_imports, unused_variables)]
+    pub mod events {
+        use super::INTERNAL_ERR;
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct AmpUpdateStarted {
+            pub start_value: substreams::scalar::BigInt,
+            pub end_value: substreams::scalar::BigInt,
+            pub start_time: substreams::scalar::BigInt,
+            pub end_time: substreams::scalar::BigInt,
+        }
+        impl AmpUpdateStarted {
+            const TOPIC_ID: [u8; 32] = [
+                24u8,
+                53u8,
+                136u8,
+                46u8,
+                231u8,
+                163u8,
+                74u8,
+                193u8,
+                148u8,
+                247u8,
+                23u8,
+                163u8,
+                94u8,
+                9u8,
+                187u8,
+                29u8,
+                36u8,
+                200u8,
+                42u8,
+                59u8,
+                157u8,
+                133u8,
+                74u8,
+                182u8,
+                201u8,
+                116u8,
+                149u8,
+                37u8,
+                183u8,
+                20u8,
+                205u8,
+                242u8,
+            ];
+            pub fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                if log.topics.len() != 1usize {
+                    return false;
+                }
+                if log.data.len() != 128usize {
+                    return false;
+                }
+                return log.topics.get(0).expect("bounds already checked").as_ref()
+                    == Self::TOPIC_ID;
+            }
+            pub fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                let mut values = ethabi::decode(
+                        &[
+                            ethabi::ParamType::Uint(256usize),
+                            ethabi::ParamType::Uint(256usize),
+                            ethabi::ParamType::Uint(256usize),
+                            ethabi::ParamType::Uint(256usize),
+                        ],
+                        log.data.as_ref(),
+                    )
+                    .map_err(|e| format!("unable to decode log.data: {:?}", e))?;
+                values.reverse();
+                Ok(Self {
+                    start_value: {
+                        let mut v = [0 as u8; 32];
+                        values
+                            .pop()
+                            .expect(INTERNAL_ERR)
+                            .into_uint()
+                            .expect(INTERNAL_ERR)
+                            .to_big_endian(v.as_mut_slice());
+                        substreams::scalar::BigInt::from_unsigned_bytes_be(&v)
+                    },
+                    end_value: {
+                        let mut v = [0 as u8; 32];
+                        values
+                            .pop()
+                            .expect(INTERNAL_ERR)
+                            .into_uint()
+                            .expect(INTERNAL_ERR)
+                            .to_big_endian(v.as_mut_slice());
+                        substreams::scalar::BigInt::from_unsigned_bytes_be(&v)
+                    },
+                    start_time: {
+                        let mut v = [0 as u8; 32];
+                        values
+                            .pop()
+                            .expect(INTERNAL_ERR)
+                            .into_uint()
+                            .expect(INTERNAL_ERR)
+                            .to_big_endian(v.as_mut_slice());
+                        substreams::scalar::BigInt::from_unsigned_bytes_be(&v)
+                    },
+                    end_time: {
+                        let mut v = [0 as u8; 32];
+                        values
+                            .pop()
+                            .expect(INTERNAL_ERR)
+                            .into_uint()
+                            .expect(INTERNAL_ERR)
+                            .to_big_endian(v.as_mut_slice());
+                        substreams::scalar::BigInt::from_unsigned_bytes_be(&v)
+                    },
+                })
+            }
+        }
+        impl substreams_ethereum::Event for AmpUpdateStarted {
+            const NAME: &'static str = "AmpUpdateStarted";
+            fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                Self::match_log(log)
+            }
+            fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                Self::decode(log)
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct AmpUpdateStopped {
+            pub current_value: substreams::scalar::BigInt,
+        }
+        impl AmpUpdateStopped {
+            const TOPIC_ID: [u8; 32] = [
+                160u8,
+                208u8,
+                21u8,
+                147u8,
+                228u8,
+                126u8,
+                105u8,
+                208u8,
+                126u8,
+                12u8,
+                205u8,
+                135u8,
+                190u8,
+                206u8,
+                9u8,
+                65u8,
+                30u8,
+                7u8,
+                221u8,
+                30u8,
+                212u8,
+                12u8,
+                168u8,
+                242u8,
+                231u8,
+                175u8,
+                41u8,
+                118u8,
+                84u8,
+                42u8,
+                2u8,
+                51u8,
+            ];
+            pub fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                if log.topics.len() != 1usize {
+                    return false;
+                }
+                if log.data.len() != 32usize {
+                    return false;
+                }
+                return log.topics.get(0).expect("bounds already checked").as_ref()
+                    == Self::TOPIC_ID;
+            }
+            pub fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                let mut values = ethabi::decode(
+                        &[ethabi::ParamType::Uint(256usize)],
+                        log.data.as_ref(),
+                    )
+                    .map_err(|e| format!("unable to decode log.data: {:?}", e))?;
+                values.reverse();
+                Ok(Self {
+                    current_value: {
+                        let mut v = [0 as u8; 32];
+                        values
+                            .pop()
+                            .expect(INTERNAL_ERR)
+                            .into_uint()
+                            .expect(INTERNAL_ERR)
+                            .to_big_endian(v.as_mut_slice());
+                        substreams::scalar::BigInt::from_unsigned_bytes_be(&v)
+                    },
+                })
+            }
+        }
+        impl substreams_ethereum::Event for AmpUpdateStopped {
+            const NAME: &'static str = "AmpUpdateStopped";
+            fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                Self::match_log(log)
+            }
+            fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                Self::decode(log)
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct GradualWeightUpdateScheduled {
+            pub start_time: substreams::scalar::BigInt,
+            pub end_time: substreams::scalar::BigInt,
+            pub start_weights: Vec<substreams::scalar::BigInt>,
+            pub end_weights: Vec<substreams::scalar::BigInt>,
+        }
+        impl GradualWeightUpdateScheduled {
+            const TOPIC_ID: [u8; 32] = [
+                15u8,
+                54u8,
+                49u8,
+                249u8,
+                218u8,
+                176u8,
+                129u8,
+                105u8,
+                209u8,
+                219u8,
+                33u8,
+                198u8,
+                220u8,
+                95u8,
+                50u8,
+                83u8,
+                111u8,
+                178u8,
+                176u8,
+                166u8,
+                185u8,
+                187u8,
+                83u8,
+                48u8,
+                215u8,
+                28u8,
+                82u8,
+                19u8,
+                47u8,
+                150u8,
+                139u8,
+                224u8,
+            ];
+            pub fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                if log.topics.len() != 1usize {
+                    return false;
+                }
+                if log.data.len() < 192usize {
+                    return false;
+                }
+                return log.topics.get(0).expect("bounds already checked").as_ref()
+                    == Self::TOPIC_ID;
+            }
+            pub fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                let mut values = ethabi::decode(
+                        &[
+                            ethabi::ParamType::Uint(256usize),
+                            ethabi::ParamType::Uint(256usize),
+                            ethabi::ParamType::Array(
+                                Box::new(ethabi::ParamType::Uint(256usize)),
+                            ),
+                            ethabi::ParamType::Array(
+                                Box::new(ethabi::ParamType::Uint(256usize)),
+                            ),
+                        ],
+                        log.data.as_ref(),
+                    )
+                    .map_err(|e| format!("unable to decode log.data: {:?}", e))?;
+                values.reverse();
+                Ok(Self {
+                    start_time: {
+                        let mut v = [0 as u8; 32];
+                        values
+                            .pop()
+                            .expect(INTERNAL_ERR)
+                            .into_uint()
+                            .expect(INTERNAL_ERR)
+                            .to_big_endian(v.as_mut_slice());
+                        substreams::scalar::BigInt::from_unsigned_bytes_be(&v)
+                    },
+                    end_time: {
+                        let mut v = [0 as u8; 32];
+                        values
+                            .pop()
+                            .expect(INTERNAL_ERR)
+                            .into_uint()
+                            .expect(INTERNAL_ERR)
+                            .to_big_endian(v.as_mut_slice());
+                        substreams::scalar::BigInt::from_unsigned_bytes_be(&v)
+                    },
+                    start_weights: values
+                        .pop()
+                        .expect(INTERNAL_ERR)
+                        .into_array()
+                        .expect(INTERNAL_ERR)
+                        .into_iter()
+                        .map(|inner| {
+                            let mut v = [0 as u8; 32];
+                            inner
+                                .into_uint()
+                                .expect(INTERNAL_ERR)
+                                .to_big_endian(v.as_mut_slice());
+                            substreams::scalar::BigInt::from_unsigned_bytes_be(&v)
+                        })
+                        .collect(),
+                    end_weights: values
+                        .pop()
+                        .expect(INTERNAL_ERR)
+                        .into_array()
+                        .expect(INTERNAL_ERR)
+                        .into_iter()
+                        .map(|inner| {
+                            let mut v = [0 as u8; 32];
+                            inner
+                                .into_uint()
+                                .expect(INTERNAL_ERR)
+                                .to_big_endian(v.as_mut_slice());
+                            substreams::scalar::BigInt::from_unsigned_bytes_be(&v)
+                        })
+                        .collect(),
+                })
+            }
+        }
+        impl substreams_ethereum::Event for GradualWeightUpdateScheduled {
+            const NAME: &'static str = "GradualWeightUpdateScheduled";
+            fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                Self::match_log(log)
+            }
+            fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                Self::decode(log)
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct PausedStateChanged {
+            pub paused: bool,
+        }
+        impl PausedStateChanged {
+            const TOPIC_ID: [u8; 32] = [
+                158u8,
+                58u8,
+                94u8,
+                55u8,
+                34u8,
+                69u8,
+                50u8,
+                222u8,
+                166u8,
+                123u8,
+                137u8,
+                250u8,
+                206u8,
+                24u8,
+                87u8,
+                3u8,
+                115u8,
+                138u8,
+                34u8,
+                138u8,
+                110u8,
+                138u8,
+                35u8,
+                222u8,
+                229u8,
+                70u8,
+                150u8,
+                1u8,
+                128u8,
+                211u8,
+                190u8,
+                100u8,
+            ];
+            pub fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                if log.topics.len() != 1usize {
+                    return false;
+                }
+                if log.data.len() != 32usize {
+                    return false;
+                }
+                return log.topics.get(0).expect("bounds already checked").as_ref()
+                    == Self::TOPIC_ID;
+            }
+            pub fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                let mut values = ethabi::decode(
+                        &[ethabi::ParamType::Bool],
+                        log.data.as_ref(),
+                    )
+                    .map_err(|e| format!("unable to decode log.data: {:?}", e))?;
+                values.reverse();
+                Ok(Self {
+                    paused: values
+                        .pop()
+                        .expect(INTERNAL_ERR)
+                        .into_bool()
+                        .expect(INTERNAL_ERR),
+                })
+            }
+        }
+        impl substreams_ethereum::Event for PausedStateChanged {
+            const NAME: &'static str = "PausedStateChanged";
+            fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                Self::match_log(log)
+            }
+            fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                Self::decode(log)
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct RecoveryModeStateChanged {
+            pub enabled: bool,
+        }
+        impl RecoveryModeStateChanged {
+            const TOPIC_ID: [u8; 32] = [
+                239u8,
+                243u8,
+                212u8,
+                210u8,
+                21u8,
+                180u8,
+                43u8,
+                240u8,
+                150u8,
+                11u8,
+                233u8,
+                198u8,
+                213u8,
+                224u8,
+                92u8,
+                34u8,
+                203u8,
+                164u8,
+                223u8,
+                102u8,
+                39u8,
+                163u8,
+                165u8,
+                35u8,
+                226u8,
+                172u8,
+                238u8,
+                115u8,
+                59u8,
+                88u8,
+                84u8,
+                200u8,
+            ];
+            pub fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                if log.topics.len() != 1usize {
+                    return false;
+                }
+                if log.data.len() != 32usize {
+                    return false;
+                }
+                return log.topics.get(0).expect("bounds already checked").as_ref()
+                    == Self::TOPIC_ID;
+            }
+            pub fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                let mut values = ethabi::decode(
+                        &[ethabi::ParamType::Bool],
+                        log.data.as_ref(),
+                    )
+                    .map_err(|e| format!("unable to decode log.data: {:?}", e))?;
+                values.reverse();
+                Ok(Self {
+                    enabled: values
+                        .pop()
+                        .expect(INTERNAL_ERR)
+                        .into_bool()
+                        .expect(INTERNAL_ERR),
+                })
+            }
+        }
+        impl substreams_ethereum::Event for RecoveryModeStateChanged {
+            const NAME: &'static str = "RecoveryModeStateChanged";
+            fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                Self::match_log(log)
+            }
+            fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                Self::decode(log)
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct SwapEnabledSet {
+            pub swap_enabled: bool,
+        }
+        impl SwapEnabledSet {
+            const TOPIC_ID: [u8; 32] = [
+                90u8,
+                158u8,
+                132u8,
+                247u8,
+                143u8,
+                121u8,
+                87u8,
+                203u8,
+                78u8,
+                215u8,
+                71u8,
+                142u8,
+                176u8,
+                252u8,
+                173u8,
+                53u8,
+                238u8,
+                78u8,
+                203u8,
+                226u8,
+                224u8,
+                242u8,
+                152u8,
+                66u8,
+                11u8,
+                40u8,
+                163u8,
+                149u8,
+                83u8,
+                146u8,
+                87u8,
+                63u8,
+            ];
+            pub fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                if log.topics.len() != 1usize {
+                    return false;
+                }
+                if log.data.len() != 32usize {
+                    return false;
+                }
+                return log.topics.get(0).expect("bounds already checked").as_ref()
+                    == Self::TOPIC_ID;
+            }
+            pub fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                let mut values = ethabi::decode(
+                        &[ethabi::ParamType::Bool],
+                        log.data.as_ref(),
+                    )
+                    .map_err(|e| format!("unable to decode log.data: {:?}", e))?;
+                values.reverse();
+                Ok(Self {
+                    swap_enabled: values
+                        .pop()
+                        .expect(INTERNAL_ERR)
+                        .into_bool()
+                        .expect(INTERNAL_ERR),
+                })
+            }
+        }
+        impl substreams_ethereum::Event for SwapEnabledSet {
+            const NAME: &'static str = "SwapEnabledSet";
+            fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                Self::match_log(log)
+            }
+            fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                Self::decode(log)
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct SwapFeePercentageChanged {
+            pub swap_fee_percentage: substreams::scalar::BigInt,
+        }
+        impl SwapFeePercentageChanged {
+            const TOPIC_ID: [u8; 32] = [
+                169u8,
+                186u8,
+                63u8,
+                254u8,
+                11u8,
+                108u8,
+                54u8,
+                107u8,
+                129u8,
+                35u8,
+                44u8,
+                170u8,
+                179u8,
+                134u8,
+                5u8,
+                160u8,
+                105u8,
+                154u8,
+                213u8,
+                57u8,
+                141u8,
+                108u8,
+                206u8,
+                118u8,
+                249u8,
+                30u8,
+                232u8,
+                9u8,
+                227u8,
+                34u8,
+                218u8,
+                252u8,
+            ];
+            pub fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                if log.topics.len() != 1usize {
+                    return false;
+                }
+                if log.data.len() != 32usize {
+                    return false;
+                }
+                return log.topics.get(0).expect("bounds already checked").as_ref()
+                    == Self::TOPIC_ID;
+            }
+            pub fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                let mut values = ethabi::decode(
+                        &[ethabi::ParamType::Uint(256usize)],
+                        log.data.as_ref(),
+                    )
+                    .map_err(|e| format!("unable to decode log.data: {:?}", e))?;
+                values.reverse();
+                Ok(Self {
+                    swap_fee_percentage: {
+                        let mut v = [0 as u8; 32];
+                        values
+                            .pop()
+                            .expect(INTERNAL_ERR)
+                            .into_uint()
+                            .expect(INTERNAL_ERR)
+                            .to_big_endian(v.as_mut_slice());
+                        substreams::scalar::BigInt::from_unsigned_bytes_be(&v)
+                    },
+                })
+            }
+        }
+        impl substreams_ethereum::Event for SwapFeePercentageChanged {
+            const NAME: &'static str = "SwapFeePercentageChanged";
+            fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                Self::match_log(log)
+            }
+            fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                Self::decode(log)
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct TokenRateCacheUpdated {
+            pub token_index: substreams::scalar::BigInt,
+            pub rate: substreams::scalar::BigInt,
+        }
+        impl TokenRateCacheUpdated {
+            const TOPIC_ID: [u8; 32] = [
+                183u8,
+                122u8,
+                131u8,
+                32u8,
+                76u8,
+                162u8,
+                130u8,
+                224u8,
+                141u8,
+                195u8,
+                166u8,
+                91u8,
+                10u8,
+                28u8,
+                163u8,
+                46u8,
+                164u8,
+                230u8,
+                135u8,
+                92u8,
+                56u8,
+                239u8,
+                11u8,
+                245u8,
+                191u8,
+                117u8,
+                229u8,
+                42u8,
+                103u8,
+                53u8,
+                79u8,
+                172u8,
+            ];
+            pub fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                if log.topics.len() != 2usize {
+                    return false;
+                }
+                if log.data.len() != 32usize {
+                    return false;
+                }
+                return log.topics.get(0).expect("bounds already checked").as_ref()
+                    == Self::TOPIC_ID;
+            }
+            pub fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                let mut values = ethabi::decode(
+                        &[ethabi::ParamType::Uint(256usize)],
+                        log.data.as_ref(),
+                    )
+                    .map_err(|e| format!("unable to decode log.data: {:?}", e))?;
+                values.reverse();
+                Ok(Self {
+                    token_index: {
+                        let mut v = [0 as u8; 32];
+                        ethabi::decode(
+                                &[ethabi::ParamType::Uint(256usize)],
+                                log.topics[1usize].as_ref(),
+                            )
+                            .map_err(|e| {
+                                format!(
+                                    "unable to decode param 'token_index' from topic of type 'uint256': {:?}",
+                                    e
+                                )
+                            })?
+                            .pop()
+                            .expect(INTERNAL_ERR)
+                            .into_uint()
+                            .expect(INTERNAL_ERR)
+                            .to_big_endian(v.as_mut_slice());
+                        substreams::scalar::BigInt::from_unsigned_bytes_be(&v)
+                    },
+                    rate: {
+                        let mut v = [0 as u8; 32];
+                        values
+                            .pop()
+                            .expect(INTERNAL_ERR)
+                            .into_uint()
+                            .expect(INTERNAL_ERR)
+                            .to_big_endian(v.as_mut_slice());
+                        substreams::scalar::BigInt::from_unsigned_bytes_be(&v)
+                    },
+                })
+            }
+        }
+        impl substreams_ethereum::Event for TokenRateCacheUpdated {
+            const NAME: &'static str = "TokenRateCacheUpdated";
+            fn match_log(log: &substreams_ethereum::pb::eth::v2::Log) -> bool {
+                Self::match_log(log)
+            }
+            fn decode(
+                log: &substreams_ethereum::pb::eth::v2::Log,
+            ) -> Result<Self, String> {
+                Self::decode(log)
+            }
+        }
+    }
\ No newline at end of file
@@ -12,3 +12,7 @@ pub mod weighted_pool_factory_v3;
 pub mod weighted_pool_factory_v4;
 pub mod silo_linear_pool_factory;
 pub mod composable_stable_pool_factory;
+pub mod base_pool;
+pub mod stable_pool_factory;
+pub mod meta_stable_pool_factory;
+pub mod liquidity_bootstrapping_pool_factory;
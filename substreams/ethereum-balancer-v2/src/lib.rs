@@ -1,3 +1,6 @@
 mod abi;
 mod modules;
+mod native;
+mod params;
+mod pool_events;
 mod pool_factories;
@@ -1,21 +1,31 @@
-use crate::{abi, pool_factories};
+use crate::{
+    abi, native,
+    params::{Implementation, Params},
+    pool_events, pool_factories,
+};
 use anyhow::Result;
 use itertools::Itertools;
 use std::collections::HashMap;
 use substreams::{
-    hex,
     pb::substreams::StoreDeltas,
-    store::{StoreAddBigInt, StoreGet, StoreGetString, StoreNew, StoreSet, StoreSetString},
+    scalar::BigInt,
+    store::{
+        StoreAddBigInt, StoreGet, StoreGetProto, StoreGetString, StoreNew, StoreSet, StoreSetProto,
+        StoreSetString,
+    },
 };
 use substreams_ethereum::{pb::eth, Event};
 use tycho_substreams::{
-    balances::aggregate_balances_changes, contract::extract_contract_changes_builder, prelude::*,
+    aggregation::AggregationMode, balances::aggregate_balances_changes,
+    contract::extract_contract_changes_builder, prelude::*,
 };
 
-pub const VAULT_ADDRESS: &[u8] = &hex!("BA12222222228d8Ba445958a75a0704d566BF2C8");
-
 #[substreams::handlers::map]
-pub fn map_components(block: eth::v2::Block) -> Result<BlockTransactionProtocolComponents> {
+pub fn map_components(
+    params: String,
+    block: eth::v2::Block,
+) -> Result<BlockTransactionProtocolComponents> {
+    let params = Params::parse(&params)?;
     // Gather contract changes by indexing `PoolCreated` events and analysing the `Create` call
     // We store these as a hashmap by tx hash since we need to agg by tx hash later
     Ok(BlockTransactionProtocolComponents {
@@ -25,12 +35,22 @@ pub fn map_components(block: eth::v2::Block) -> Result<BlockTransactionProtocolC
                 let components = tx
                     .logs_with_calls()
                     .filter_map(|(log, call)| {
-                        pool_factories::address_map(
-                            call.call.address.as_slice(),
+                        let factory = params.factory(&call.call.address)?;
+                        let component = pool_factories::address_map(
+                            factory,
+                            &params.vault,
                             log,
                             call.call,
                             tx,
-                        )
+                        )?;
+                        if params.implementation == Implementation::Custom &&
+                            factory.supports_native()
+                        {
+                            return Some(
+                                native::into_native(component.clone(), tx).unwrap_or(component),
+                            );
+                        }
+                        Some(component)
                     })
                     .collect::<Vec<_>>();
 
@@ -57,16 +77,50 @@ pub fn store_components(map: BlockTransactionProtocolComponents, store: StoreSet
         });
 }
 
+/// Stores the natively simulated `ProtocolComponent`s by id, see `crate::native`.
+#[substreams::handlers::store]
+pub fn store_native_components(
+    map: BlockTransactionProtocolComponents,
+    store: StoreSetProto<ProtocolComponent>,
+) {
+    map.tx_components
+        .into_iter()
+        .flat_map(|tx_pc| tx_pc.components)
+        .filter(|pc| {
+            pc.protocol_type
+                .as_ref()
+                .is_some_and(|protocol_type| {
+                    protocol_type.implementation_type == i32::from(ImplementationType::Custom)
+                })
+        })
+        .for_each(|pc| store.set(0, pc.id.clone(), &pc));
+}
+
+/// The balance deltas of the pool tokens on a join or exit.
+///
+/// The `deltas` of `PoolBalanceChanged` are the amounts the user paid or received. Protocol fees
+/// due are paid from the pool balances on top, see `protocolFeeAmounts`, so they are subtracted.
+fn pool_balance_deltas(ev: &abi::vault::events::PoolBalanceChanged) -> Vec<(Vec<u8>, BigInt)> {
+    ev.tokens
+        .iter()
+        .zip(ev.deltas.iter())
+        .zip(ev.protocol_fee_amounts.iter())
+        .map(|((token, delta), protocol_fee)| (token.clone(), delta.clone() - protocol_fee.clone()))
+        .collect()
+}
+
 /// Since the `PoolBalanceChanged` and `Swap` events administer only deltas, we need to leverage a
 /// map and a  store to be able to tally up final balances for tokens in a pool.
 #[substreams::handlers::map]
 pub fn map_relative_balances(
+    params: String,
     block: eth::v2::Block,
     store: StoreGetString,
 ) -> Result<BlockBalanceDeltas, anyhow::Error> {
+    let params = Params::parse(&params)?;
     let balance_deltas = block
         .logs()
-        .filter(|log| log.address() == VAULT_ADDRESS)
+        .filter(|log| log.address() == params.vault)
         .flat_map(|vault_log| {
             let mut deltas = Vec::new();
 
@@ -79,16 +133,14 @@ pub fn map_relative_balances(
                     .get_last(format!("pool:{}", &component_id[..42]))
                     .is_some()
                 {
-                    for (token, delta) in ev
-                        .tokens
-                        .iter()
-                        .zip(ev.deltas.iter())
-                        .filter(|(token, _)| **token != hex::decode(&component_id[2..42]).unwrap())
+                    for (token, delta) in pool_balance_deltas(&ev)
+                        .into_iter()
+                        .filter(|(token, _)| *token != hex::decode(&component_id[2..42]).unwrap())
                     {
                         deltas.push(BalanceDelta {
                             ord: vault_log.ordinal(),
                             tx: Some(vault_log.receipt.transaction.into()),
-                            token: token.to_vec(),
+                            token,
                             delta: delta.to_signed_bytes_be(),
                             component_id: component_id.as_bytes().to_vec(),
                         });
@@ -158,22 +210,39 @@ pub fn store_balances(deltas: BlockBalanceDeltas, store: StoreAddBigInt) {
 /// `BlockChanges`  is ordered by transactions properly.
 #[substreams::handlers::map]
 pub fn map_protocol_changes(
+    params: String,
     block: eth::v2::Block,
     grouped_components: BlockTransactionProtocolComponents,
     deltas: BlockBalanceDeltas,
     components_store: StoreGetString,
+    native_store: StoreGetProto<ProtocolComponent>,
     balance_store: StoreDeltas, // Note, this map module is using the `deltas` mode for the store.
 ) -> Result<BlockChanges> {
+    let vault = Params::parse(&params)?.vault;
     // We merge contract changes by transaction (identified by transaction index) making it easy to
     //  sort them at the very end.
     let mut transaction_changes: HashMap<_, TransactionChangesBuilder> = HashMap::new();
+    // Attributes set in the transaction that created the component are creations.
+    let change_type = |tx: &Transaction, component_id: &str| {
+        let created = grouped_components
+            .tx_components
+            .iter()
+            .filter(|tx_component| tx_component.tx.as_ref() == Some(tx))
+            .flat_map(|tx_component| tx_component.components.iter())
+            .any(|component| component.id == component_id);
+        if created {
+            ChangeType::Creation
+        } else {
+            ChangeType::Update
+        }
+    };
 
     // `ProtocolComponents` are gathered from `map_pools_created` which just need a bit of work to
     //   convert into `TransactionChanges`
     let default_attributes = vec![
         Attribute {
             name: "balance_owner".to_string(),
-            value: VAULT_ADDRESS.to_vec(),
+            value: vault.clone(),
             change: ChangeType::Creation.into(),
         },
         Attribute {
@@ -198,14 +267,53 @@ pub fn map_protocol_changes(
                 .iter()
                 .for_each(|component| {
                     builder.add_protocol_component(component);
-                    let entity_change = EntityChanges {
-                        component_id: component.id.clone(),
-                        attributes: default_attributes.clone(),
-                    };
+                    let mut attributes = default_attributes.clone();
+                    if native_store
+                        .get_last(&component.id)
+                        .is_some()
+                    {
+                        attributes.extend(native::initial_rates(component));
+                    }
+                    let entity_change =
+                        EntityChanges { component_id: component.id.clone(), attributes };
                     builder.add_entity_change(&entity_change)
                 });
         });
 
+    // Pool parameters that change over time (fee, amplification, pause and recovery mode, and the
+    //  token rates of natively simulated pools) are tracked as state attributes. Logs are
+    //  processed in order so the latest change of a transaction wins.
+    block.logs().for_each(|log| {
+        let Some(component_id) =
+            components_store.get_last(format!("pool:0x{}", hex::encode(log.address())))
+        else {
+            return;
+        };
+        let mut attributes = pool_events::pool_attributes(log.log, block.timestamp_seconds())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect::<Vec<_>>();
+        if let Some(component) = native_store.get_last(&component_id) {
+            attributes.extend(native::rate_attribute(log.log, &component));
+        }
+        if attributes.is_empty() {
+            return;
+        }
+        let tx: Transaction = log.receipt.transaction.into();
+        let change = change_type(&tx, &component_id);
+        transaction_changes
+            .entry(tx.index)
+            .or_insert_with(|| TransactionChangesBuilder::new(&tx))
+            .add_entity_change(&EntityChanges {
+                component_id,
+                attributes: attributes
+                    .into_iter()
+                    .map(|(name, value)| Attribute { name, value, change: change.into() })
+                    .collect(),
+            });
+    });
+
     // Balance changes are gathered by the `StoreDelta` based on `PoolBalanceChanged` creating
     //  `BlockBalanceDeltas`. We essentially just process the changes that occurred to the `store`
     // this  block. Then, these balance changes are merged onto the existing map of tx contract
@@ -223,16 +331,35 @@ pub fn map_protocol_changes(
                         .values()
                         .for_each(|bc| builder.add_balance_change(bc))
                 });
+            // Natively simulated pools additionally get their balances scaled to 18 decimals.
+            balances
+                .iter()
+                .filter_map(|(component_id, token_bc_map)| {
+                    let component_id = std::str::from_utf8(component_id).ok()?;
+                    Some((native_store.get_last(component_id)?, token_bc_map))
+                })
+                .for_each(|(component, token_bc_map)| {
+                    let change = change_type(&tx, &component.id);
+                    builder.add_entity_change(&EntityChanges {
+                        component_id: component.id.clone(),
+                        attributes: token_bc_map
+                            .values()
+                            .filter_map(|bc| native::scaled_balance_attribute(&component, bc))
+                            .map(|(name, value)| Attribute { name, value, change: change.into() })
+                            .collect(),
+                    });
+                });
         });
 
-    // Extract and insert any storage changes that happened for any of the components.
+    // Extract and insert any storage changes that happened for any of the components simulated in
+    //  the VM.
     extract_contract_changes_builder(
         &block,
         |addr| {
             components_store
                 .get_last(format!("pool:0x{0}", hex::encode(addr)))
-                .is_some() ||
-                addr.eq(VAULT_ADDRESS)
+                .is_some_and(|id| native_store.get_last(id).is_none()) ||
+                addr.eq(vault.as_slice())
         },
         &mut transaction_changes,
     );
@@ -248,7 +375,7 @@ pub fn map_protocol_changes(
             addresses
                 .into_iter()
                 .for_each(|address| {
-                    if address != VAULT_ADDRESS {
+                    if address != vault {
                         // We reconstruct the component_id from the address here
                         let id = components_store
                             .get_last(format!("pool:0x{}", hex::encode(address)))
@@ -261,12 +388,33 @@ pub fn map_protocol_changes(
 
     // Process all `transaction_changes` for final output in the `BlockChanges`,
     //  sorted by transaction index (the key).
-    Ok(BlockChanges {
+    Ok(AggregationMode::from_params(&params)?.apply(BlockChanges {
         block: Some((&block).into()),
         changes: transaction_changes
             .drain()
             .sorted_unstable_by_key(|(index, _)| *index)
             .filter_map(|(_, builder)| builder.build())
             .collect::<Vec<_>>(),
-    })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_balance_deltas_subtract_protocol_fees() {
+        let ev = abi::vault::events::PoolBalanceChanged {
+            pool_id: [0u8; 32],
+            liquidity_provider: vec![1u8; 20],
+            tokens: vec![vec![2u8; 20], vec![3u8; 20]],
+            deltas: vec![BigInt::from(1_000), BigInt::from(-500)],
+            protocol_fee_amounts: vec![BigInt::from(10), BigInt::from(5)],
+        };
+
+        assert_eq!(
+            pool_balance_deltas(&ev),
+            vec![(vec![2u8; 20], BigInt::from(990)), (vec![3u8; 20], BigInt::from(-505))]
+        );
+    }
 }
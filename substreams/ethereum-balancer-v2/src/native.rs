@@ -0,0 +1,236 @@
+use crate::abi::base_pool::events::TokenRateCacheUpdated;
+use substreams::{hex, scalar::BigInt};
+use substreams_ethereum::{
+    pb::eth::v2::{Log, TransactionTrace},
+    Event,
+};
+use tycho_substreams::prelude::*;
+
+const DECIMALS_FN_SIG: [u8; 4] = hex!("313ce567");
+
+/// `1` in the 18 decimals fixed point math of the pools.
+fn one() -> BigInt {
+    BigInt::from(10).pow(18)
+}
+
+/// Switches a weighted or composable stable pool to native simulation.
+///
+/// Pools scale all amounts to 18 decimals before doing their math. The pool constructor computes
+/// the scaling factor of each token from its `decimals()`, we take them from these calls and add
+/// them as `scaling_factors/{token}` attributes. The pool's own BPT, which composable stable pools
+/// register as token, has 18 decimals.
+///
+/// Returns `None` if the scaling factor of a token is missing, the pool then stays simulated in
+/// the VM.
+pub fn into_native(
+    mut component: ProtocolComponent,
+    tx: &TransactionTrace,
+) -> Option<ProtocolComponent> {
+    let pool = hex::decode(&component.id[2..42]).ok()?;
+    for token in component.tokens.clone() {
+        let scaling_factor = if token == pool {
+            one()
+        } else {
+            let decimals = tx
+                .calls
+                .iter()
+                .find(|call| {
+                    call.caller == pool &&
+                        call.address == token &&
+                        call.input.starts_with(&DECIMALS_FN_SIG)
+                })
+                .map(|call| BigInt::from_unsigned_bytes_be(&call.return_data).to_u64())
+                .filter(|decimals| *decimals <= 18)?;
+            one() * BigInt::from(10).pow(18 - decimals as u32)
+        };
+        component.static_att.push(Attribute {
+            name: format!("scaling_factors/0x{}", hex::encode(&token)),
+            value: scaling_factor.to_signed_bytes_be(),
+            change: ChangeType::Creation.into(),
+        });
+    }
+    component
+        .protocol_type
+        .as_mut()?
+        .implementation_type = ImplementationType::Custom.into();
+    Some(component)
+}
+
+/// The `rates/{token}` attributes of a pool created in this transaction, `1` for every token.
+///
+/// Composable stable pools emit `TokenRateCacheUpdated` for the tokens with a rate provider from
+/// their constructor, which overwrites these.
+pub fn initial_rates(component: &ProtocolComponent) -> Vec<Attribute> {
+    component
+        .tokens
+        .iter()
+        .map(|token| Attribute {
+            name: format!("rates/0x{}", hex::encode(token)),
+            value: one().to_signed_bytes_be(),
+            change: ChangeType::Creation.into(),
+        })
+        .collect()
+}
+
+/// Decodes `TokenRateCacheUpdated` of a composable stable pool into the `rates/{token}` attribute.
+///
+/// The pool prices swaps with its cached rates, which it refreshes from the rate providers once the
+/// cache expired, so the cached rate is the one to simulate with.
+pub fn rate_attribute(log: &Log, component: &ProtocolComponent) -> Option<(String, Vec<u8>)> {
+    let ev = TokenRateCacheUpdated::match_and_decode(log)?;
+    let token = component
+        .tokens
+        .get(ev.token_index.to_u64() as usize)?;
+    Some((format!("rates/0x{}", hex::encode(token)), ev.rate.to_signed_bytes_be()))
+}
+
+/// Converts an absolute raw balance of a pool into the `scaled_balances/{token}` attribute, the
+/// balance in 18 decimals as the pool sees it before applying rates.
+pub fn scaled_balance_attribute(
+    component: &ProtocolComponent,
+    balance_change: &BalanceChange,
+) -> Option<(String, Vec<u8>)> {
+    let token = hex::encode(&balance_change.token);
+    let scaling_factor = BigInt::from_signed_bytes_be(
+        &component.get_attribute_value(&format!("scaling_factors/0x{}", token))?,
+    );
+    let scaled_balance =
+        BigInt::from_unsigned_bytes_be(&balance_change.balance) * scaling_factor / one();
+    Some((format!("scaled_balances/0x{}", token), scaled_balance.to_signed_bytes_be()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use substreams_ethereum::pb::eth::v2::Call;
+
+    const POOL: [u8; 20] = [0xaa; 20];
+    const USDC: [u8; 20] = [0x01; 20];
+    const WETH: [u8; 20] = [0x02; 20];
+
+    /// A composable stable pool registering its own BPT between USDC and WETH.
+    fn component() -> ProtocolComponent {
+        ProtocolComponent::new(
+            &format!("0x{}000000000000000000000001", hex::encode(POOL)),
+            &Transaction::default(),
+        )
+        .with_tokens(&[USDC, POOL, WETH])
+        .as_swap_type("balancer_v2_pool", ImplementationType::Vm)
+    }
+
+    fn decimals_call(caller: [u8; 20], token: [u8; 20], decimals: u8) -> Call {
+        let mut return_data = vec![0u8; 32];
+        return_data[31] = decimals;
+        Call {
+            caller: caller.to_vec(),
+            address: token.to_vec(),
+            input: DECIMALS_FN_SIG.to_vec(),
+            return_data,
+            ..Default::default()
+        }
+    }
+
+    fn attribute(component: &ProtocolComponent, name: String) -> Option<BigInt> {
+        component
+            .get_attribute_value(&name)
+            .map(|value| BigInt::from_signed_bytes_be(&value))
+    }
+
+    #[test]
+    fn test_into_native_scaling_factors() {
+        let tx = TransactionTrace {
+            calls: vec![decimals_call(POOL, USDC, 6), decimals_call(POOL, WETH, 18)],
+            ..Default::default()
+        };
+
+        let native = into_native(component(), &tx).unwrap();
+
+        assert_eq!(
+            attribute(&native, format!("scaling_factors/0x{}", hex::encode(USDC))),
+            Some(BigInt::from(10).pow(30))
+        );
+        assert_eq!(
+            attribute(&native, format!("scaling_factors/0x{}", hex::encode(POOL))),
+            Some(one())
+        );
+        assert_eq!(
+            attribute(&native, format!("scaling_factors/0x{}", hex::encode(WETH))),
+            Some(one())
+        );
+        assert_eq!(
+            native
+                .protocol_type
+                .unwrap()
+                .implementation_type,
+            i32::from(ImplementationType::Custom)
+        );
+    }
+
+    #[test]
+    fn test_into_native_requires_decimals_of_every_token() {
+        // WETH's decimals are only queried by another contract.
+        let missing = TransactionTrace {
+            calls: vec![decimals_call(POOL, USDC, 6), decimals_call(USDC, WETH, 18)],
+            ..Default::default()
+        };
+        let too_many = TransactionTrace {
+            calls: vec![decimals_call(POOL, USDC, 24), decimals_call(POOL, WETH, 18)],
+            ..Default::default()
+        };
+
+        assert_eq!(into_native(component(), &missing), None);
+        assert_eq!(into_native(component(), &too_many), None);
+    }
+
+    #[test]
+    fn test_rate_attribute_maps_token_index() {
+        let rate = 1_050_000_000_000_000_000u64;
+        let rate_log = |index: u8| {
+            let mut token_index = vec![0u8; 32];
+            token_index[31] = index;
+            let mut data = vec![0u8; 32];
+            data[24..].copy_from_slice(&rate.to_be_bytes());
+            Log {
+                address: POOL.to_vec(),
+                topics: vec![
+                    hex!("b77a83204ca282e08dc3a65b0a1ca32ea4e6875c38ef0bf5bf75e52a67354fac")
+                        .to_vec(),
+                    token_index,
+                ],
+                data,
+                ..Default::default()
+            }
+        };
+
+        assert_eq!(
+            rate_attribute(&rate_log(2), &component()),
+            Some((
+                format!("rates/0x{}", hex::encode(WETH)),
+                BigInt::from(rate).to_signed_bytes_be()
+            ))
+        );
+        assert_eq!(rate_attribute(&rate_log(3), &component()), None);
+    }
+
+    #[test]
+    fn test_scaled_balance_attribute() {
+        let tx = TransactionTrace {
+            calls: vec![decimals_call(POOL, USDC, 6), decimals_call(POOL, WETH, 18)],
+            ..Default::default()
+        };
+        let native = into_native(component(), &tx).unwrap();
+        let balance_change = BalanceChange {
+            token: USDC.to_vec(),
+            balance: BigInt::from(5_000_000).to_bytes_be().1,
+            component_id: native.id.as_bytes().to_vec(),
+        };
+
+        assert_eq!(
+            scaled_balance_attribute(&native, &balance_change),
+            Some((
+                format!("scaled_balances/0x{}", hex::encode(USDC)),
+                (BigInt::from(5) * one()).to_signed_bytes_be()
+            ))
+        );
+    }
+}
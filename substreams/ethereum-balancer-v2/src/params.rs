@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use substreams::hex;
+
+use crate::pool_factories::{self, PoolFactory};
+
+/// The Vault is deployed at the same address on every chain.
+pub const DEFAULT_VAULT_ADDRESS: [u8; 20] = hex!("BA12222222228d8Ba445958a75a0704d566BF2C8");
+
+/// Params of the Balancer V2 modules, e.g. `chain=arbitrum&
+/// factories=c7E5ED1054A24Ef31D827E6F86caA58B3Bc168d7:WeightedPoolFactoryV4`.
+///
+/// `chain` selects the built-in factory registry of the chain. `factories` adds or overrides
+/// registry entries as comma separated `address:pool_type` pairs, so factories can be indexed
+/// without a new release. `implementation` selects how weighted and composable stable pools are
+/// simulated. Other params, such as `aggregation`, are ignored.
+#[derive(Debug, Deserialize, PartialEq)]
+struct RawParams {
+    #[serde(default = "default_chain")]
+    chain: String,
+    vault: Option<String>,
+    factories: Option<String>,
+    #[serde(default)]
+    implementation: Implementation,
+}
+
+/// How weighted and composable stable pools are simulated. Other pools are always simulated in
+/// the VM.
+#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Implementation {
+    /// By executing the Vault and pool bytecode.
+    #[default]
+    Vm,
+    /// Natively, from the state attributes maintained by `crate::native`.
+    Custom,
+}
+
+fn default_chain() -> String {
+    "ethereum".to_string()
+}
+
+/// The Balancer V2 deployment to index.
+#[derive(Debug, PartialEq)]
+pub struct Params {
+    pub vault: Vec<u8>,
+    pub implementation: Implementation,
+    factories: HashMap<Vec<u8>, PoolFactory>,
+}
+
+impl Params {
+    pub fn parse(input: &str) -> Result<Self> {
+        let raw: RawParams = serde_qs::from_str(input).context("Unable to deserialize params")?;
+        let vault = match raw.vault {
+            Some(vault) => decode_address(&vault)?,
+            None => DEFAULT_VAULT_ADDRESS.to_vec(),
+        };
+        let mut factories: HashMap<_, _> = pool_factories::registry(&raw.chain)?
+            .iter()
+            .map(|(address, factory)| (address.to_vec(), *factory))
+            .collect();
+        for entry in raw
+            .factories
+            .iter()
+            .flat_map(|factories| factories.split(','))
+            .filter(|entry| !entry.is_empty())
+        {
+            let (address, pool_type) = entry
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Expected `address:pool_type`, got {entry}"))?;
+            factories.insert(decode_address(address)?, pool_type.parse()?);
+        }
+        Ok(Self { vault, implementation: raw.implementation, factories })
+    }
+
+    /// Returns the factory deployed at `address`, if it is indexed.
+    pub fn factory(&self, address: &[u8]) -> Option<PoolFactory> {
+        self.factories.get(address).copied()
+    }
+}
+
+fn decode_address(address: &str) -> Result<Vec<u8>> {
+    let address = hex::decode(address.trim_start_matches("0x")).context("Invalid address")?;
+    if address.len() != 20 {
+        return Err(anyhow!("Expected a 20 byte address"));
+    }
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WEIGHTED_V4: [u8; 20] = hex!("897888115Ada5773E02aA29F775430BFB5F34c51");
+    const ARBITRUM_WEIGHTED_V4: [u8; 20] = hex!("c7E5ED1054A24Ef31D827E6F86caA58B3Bc168d7");
+
+    #[test]
+    fn test_parse_defaults() {
+        let params = Params::parse("").unwrap();
+
+        assert_eq!(params.vault, DEFAULT_VAULT_ADDRESS.to_vec());
+        assert_eq!(params.implementation, Implementation::Vm);
+        assert_eq!(params.factory(&WEIGHTED_V4), Some(PoolFactory::WeightedPoolFactoryV4));
+        assert_eq!(params.factory(&ARBITRUM_WEIGHTED_V4), None);
+    }
+
+    #[test]
+    fn test_parse_chain() {
+        let params =
+            Params::parse("chain=arbitrum&implementation=custom&aggregation=block").unwrap();
+
+        assert_eq!(params.implementation, Implementation::Custom);
+        assert_eq!(params.factory(&ARBITRUM_WEIGHTED_V4), Some(PoolFactory::WeightedPoolFactoryV4));
+        assert_eq!(params.factory(&WEIGHTED_V4), None);
+    }
+
+    #[test]
+    fn test_parse_factories() {
+        let params = Params::parse(
+            "factories=0x897888115Ada5773E02aA29F775430BFB5F34c51:ManagedPoolFactory,\
+             0000000000000000000000000000000000000001:StablePoolFactory",
+        )
+        .unwrap();
+
+        assert_eq!(params.factory(&WEIGHTED_V4), Some(PoolFactory::ManagedPoolFactory));
+        assert_eq!(
+            params.factory(&hex!("0000000000000000000000000000000000000001")),
+            Some(PoolFactory::StablePoolFactory)
+        );
+        assert_eq!(
+            params.factory(&hex!("DB8d758BCb971e482B2C45f7F8a7740283A1bd3A")),
+            Some(PoolFactory::ComposableStablePoolFactory)
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_factories() {
+        assert!(Params::parse("factories=897888115Ada5773E02aA29F775430BFB5F34c51").is_err());
+        assert!(
+            Params::parse("factories=897888115Ada5773E02aA29F775430BFB5F34c51:Unknown").is_err()
+        );
+        assert!(Params::parse("factories=8978:WeightedPoolFactoryV4").is_err());
+    }
+
+    #[test]
+    fn test_parse_unknown_chain() {
+        let err = Params::parse("chain=solana").unwrap_err();
+
+        assert_eq!(err.to_string(), "No factory registry for chain solana");
+    }
+}
@@ -0,0 +1,172 @@
+use crate::abi::base_pool::events::{
+    AmpUpdateStarted, AmpUpdateStopped, GradualWeightUpdateScheduled, PausedStateChanged,
+    RecoveryModeStateChanged, SwapEnabledSet, SwapFeePercentageChanged,
+};
+use substreams::scalar::BigInt;
+use substreams_ethereum::{pb::eth::v2::Log, Event};
+use tycho_substreams::attributes::json_serialize_bigint_list;
+
+/// Decodes the events through which a pool changes its parameters into `(name, value)` state
+/// attributes.
+///
+/// - `SwapFeePercentageChanged` sets `fee`.
+/// - `AmpUpdateStarted` sets the amplification ramp `amp_start_value`, `amp_end_value`,
+///   `amp_start_time` and `amp_end_time`. `AmpUpdateStopped` fixes the amplification at its current
+///   value from `timestamp` on, which is how the pool stores a stopped ramp.
+/// - `GradualWeightUpdateScheduled` sets the weight change schedule of liquidity bootstrapping and
+///   managed pools: `weights_start_time`, `weights_end_time`, `start_weights` and `end_weights`.
+/// - `PausedStateChanged`, `RecoveryModeStateChanged` and `SwapEnabledSet` set `paused`,
+///   `recovery_mode` and `swap_enabled`.
+///
+/// Pools emit these events from their constructor as well, so the initial values are covered.
+pub fn pool_attributes(log: &Log, timestamp: u64) -> Option<Vec<(&'static str, Vec<u8>)>> {
+    if let Some(ev) = SwapFeePercentageChanged::match_and_decode(log) {
+        Some(vec![(
+            "fee",
+            ev.swap_fee_percentage
+                .to_signed_bytes_be(),
+        )])
+    } else if let Some(ev) = AmpUpdateStarted::match_and_decode(log) {
+        Some(amp_attributes(ev.start_value, ev.end_value, ev.start_time, ev.end_time))
+    } else if let Some(ev) = AmpUpdateStopped::match_and_decode(log) {
+        let now = BigInt::from(timestamp);
+        Some(amp_attributes(ev.current_value.clone(), ev.current_value, now.clone(), now))
+    } else if let Some(ev) = GradualWeightUpdateScheduled::match_and_decode(log) {
+        Some(vec![
+            ("weights_start_time", ev.start_time.to_signed_bytes_be()),
+            ("weights_end_time", ev.end_time.to_signed_bytes_be()),
+            ("start_weights", json_serialize_bigint_list(&ev.start_weights)),
+            ("end_weights", json_serialize_bigint_list(&ev.end_weights)),
+        ])
+    } else if let Some(ev) = SwapEnabledSet::match_and_decode(log) {
+        Some(vec![("swap_enabled", vec![ev.swap_enabled as u8])])
+    } else if let Some(ev) = PausedStateChanged::match_and_decode(log) {
+        Some(vec![("paused", vec![ev.paused as u8])])
+    } else {
+        RecoveryModeStateChanged::match_and_decode(log)
+            .map(|ev| vec![("recovery_mode", vec![ev.enabled as u8])])
+    }
+}
+
+fn amp_attributes(
+    start_value: BigInt,
+    end_value: BigInt,
+    start_time: BigInt,
+    end_time: BigInt,
+) -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        ("amp_start_value", start_value.to_signed_bytes_be()),
+        ("amp_end_value", end_value.to_signed_bytes_be()),
+        ("amp_start_time", start_time.to_signed_bytes_be()),
+        ("amp_end_time", end_time.to_signed_bytes_be()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethabi::{encode, long_signature, ParamType, Token};
+
+    const TIMESTAMP: u64 = 1_700_000_000;
+
+    fn event_log(name: &str, params: &[ParamType], tokens: &[Token]) -> Log {
+        Log {
+            topics: vec![long_signature(name, params).0.to_vec()],
+            data: encode(tokens),
+            ..Default::default()
+        }
+    }
+
+    fn uint(value: u64) -> Token {
+        Token::Uint(value.into())
+    }
+
+    fn bigint(value: u64) -> Vec<u8> {
+        BigInt::from(value).to_signed_bytes_be()
+    }
+
+    #[test]
+    fn test_swap_fee_percentage_changed() {
+        let log = event_log(
+            "SwapFeePercentageChanged",
+            &[ParamType::Uint(256)],
+            &[uint(3 * 10u64.pow(15))],
+        );
+
+        assert_eq!(
+            pool_attributes(&log, TIMESTAMP),
+            Some(vec![("fee", bigint(3 * 10u64.pow(15)))])
+        );
+    }
+
+    #[test]
+    fn test_amp_update_started() {
+        let log = event_log(
+            "AmpUpdateStarted",
+            &vec![ParamType::Uint(256); 4],
+            &[uint(200_000), uint(500_000), uint(1_699_000_000), uint(1_701_000_000)],
+        );
+
+        assert_eq!(
+            pool_attributes(&log, TIMESTAMP),
+            Some(vec![
+                ("amp_start_value", bigint(200_000)),
+                ("amp_end_value", bigint(500_000)),
+                ("amp_start_time", bigint(1_699_000_000)),
+                ("amp_end_time", bigint(1_701_000_000)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_amp_update_stopped_fixes_amp_at_block_timestamp() {
+        let log = event_log("AmpUpdateStopped", &[ParamType::Uint(256)], &[uint(350_000)]);
+
+        assert_eq!(
+            pool_attributes(&log, TIMESTAMP),
+            Some(vec![
+                ("amp_start_value", bigint(350_000)),
+                ("amp_end_value", bigint(350_000)),
+                ("amp_start_time", bigint(TIMESTAMP)),
+                ("amp_end_time", bigint(TIMESTAMP)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_gradual_weight_update_scheduled() {
+        let weights = |values: [u64; 2]| Token::Array(values.map(uint).to_vec());
+        let log = event_log(
+            "GradualWeightUpdateScheduled",
+            &[
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Array(Box::new(ParamType::Uint(256))),
+                ParamType::Array(Box::new(ParamType::Uint(256))),
+            ],
+            &[uint(10), uint(20), weights([9, 1]), weights([5, 5])],
+        );
+
+        assert_eq!(
+            pool_attributes(&log, TIMESTAMP),
+            Some(vec![
+                ("weights_start_time", bigint(10)),
+                ("weights_end_time", bigint(20)),
+                ("start_weights", br#"["0x09","0x01"]"#.to_vec()),
+                ("end_weights", br#"["0x05","0x05"]"#.to_vec()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_flags() {
+        let flag = |name: &str, value: bool| {
+            pool_attributes(&event_log(name, &[ParamType::Bool], &[Token::Bool(value)]), TIMESTAMP)
+        };
+
+        assert_eq!(flag("SwapEnabledSet", false), Some(vec![("swap_enabled", vec![0])]));
+        assert_eq!(flag("PausedStateChanged", true), Some(vec![("paused", vec![1])]));
+        assert_eq!(flag("RecoveryModeStateChanged", true), Some(vec![("recovery_mode", vec![1])]));
+        assert_eq!(flag("OtherEvent", true), None);
+    }
+}
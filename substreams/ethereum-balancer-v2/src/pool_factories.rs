@@ -1,4 +1,6 @@
-use crate::{abi, modules::VAULT_ADDRESS};
+use crate::abi;
+use anyhow::{anyhow, bail, Result};
+use std::str::FromStr;
 use substreams::hex;
 use substreams_ethereum::{
     pb::eth::v2::{Call, Log, TransactionTrace},
@@ -9,13 +11,183 @@ use tycho_substreams::{
     prelude::*,
 };
 
+/// The pool factories this package can decode, named after the `pool_type` of their pools.
+#[allow(clippy::enum_variant_names)] // Variants are the `pool_type`s indexed so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolFactory {
+    WeightedPoolFactoryV1,
+    WeightedPoolFactoryV2,
+    WeightedPoolFactoryV3,
+    WeightedPoolFactoryV4,
+    WeightedPool2TokensFactory,
+    ComposableStablePoolFactory,
+    ComposableStablePoolFactoryV1,
+    ComposableStablePoolFactoryV2,
+    ComposableStablePoolFactoryV3,
+    ComposableStablePoolFactoryV4,
+    StablePoolFactory,
+    StablePoolFactoryV2,
+    MetaStablePoolFactory,
+    ERC4626LinearPoolFactory,
+    EulerLinearPoolFactory,
+    GearboxLinearPoolFactory,
+    SiloLinearPoolFactory,
+    YearnLinearPoolFactory,
+    ManagedPoolFactory,
+    LiquidityBootstrappingPoolFactory,
+    NoProtocolFeeLiquidityBootstrappingPoolFactory,
+}
+
+impl PoolFactory {
+    const ALL: [PoolFactory; 21] = [
+        PoolFactory::WeightedPoolFactoryV1,
+        PoolFactory::WeightedPoolFactoryV2,
+        PoolFactory::WeightedPoolFactoryV3,
+        PoolFactory::WeightedPoolFactoryV4,
+        PoolFactory::WeightedPool2TokensFactory,
+        PoolFactory::ComposableStablePoolFactory,
+        PoolFactory::ComposableStablePoolFactoryV1,
+        PoolFactory::ComposableStablePoolFactoryV2,
+        PoolFactory::ComposableStablePoolFactoryV3,
+        PoolFactory::ComposableStablePoolFactoryV4,
+        PoolFactory::StablePoolFactory,
+        PoolFactory::StablePoolFactoryV2,
+        PoolFactory::MetaStablePoolFactory,
+        PoolFactory::ERC4626LinearPoolFactory,
+        PoolFactory::EulerLinearPoolFactory,
+        PoolFactory::GearboxLinearPoolFactory,
+        PoolFactory::SiloLinearPoolFactory,
+        PoolFactory::YearnLinearPoolFactory,
+        PoolFactory::ManagedPoolFactory,
+        PoolFactory::LiquidityBootstrappingPoolFactory,
+        PoolFactory::NoProtocolFeeLiquidityBootstrappingPoolFactory,
+    ];
+
+    /// The `pool_type` attribute of pools created by this factory.
+    pub fn pool_type(&self) -> String {
+        format!("{self:?}")
+    }
+
+    /// Whether pools of this factory can be simulated natively, see `crate::native`.
+    pub fn supports_native(&self) -> bool {
+        matches!(
+            self,
+            PoolFactory::WeightedPoolFactoryV1 |
+                PoolFactory::WeightedPoolFactoryV2 |
+                PoolFactory::WeightedPoolFactoryV3 |
+                PoolFactory::WeightedPoolFactoryV4 |
+                PoolFactory::WeightedPool2TokensFactory |
+                PoolFactory::ComposableStablePoolFactory |
+                PoolFactory::ComposableStablePoolFactoryV1 |
+                PoolFactory::ComposableStablePoolFactoryV2 |
+                PoolFactory::ComposableStablePoolFactoryV3 |
+                PoolFactory::ComposableStablePoolFactoryV4
+        )
+    }
+}
+
+impl FromStr for PoolFactory {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|factory| factory.pool_type() == s)
+            .ok_or_else(|| anyhow!("Unknown pool factory: {s}"))
+    }
+}
+
+type Registry = &'static [([u8; 20], PoolFactory)];
+
+// Ref: https://docs.balancer.fi/reference/contracts/deployment-addresses/mainnet.html
+const ETHEREUM: Registry = &[
+    (hex!("8E9aa87E45e92bad84D5F8DD1bff34Fb92637dE9"), PoolFactory::WeightedPoolFactoryV1),
+    (hex!("cC508a455F5b0073973107Db6a878DdBDab957bC"), PoolFactory::WeightedPoolFactoryV2),
+    (hex!("5Dd94Da3644DDD055fcf6B3E1aa310Bb7801EB8b"), PoolFactory::WeightedPoolFactoryV3),
+    (hex!("897888115Ada5773E02aA29F775430BFB5F34c51"), PoolFactory::WeightedPoolFactoryV4),
+    (hex!("A5bf2ddF098bb0Ef6d120C98217dD6B141c74EE0"), PoolFactory::WeightedPool2TokensFactory),
+    (hex!("DB8d758BCb971e482B2C45f7F8a7740283A1bd3A"), PoolFactory::ComposableStablePoolFactory),
+    (hex!("f9ac7B9dF2b3454E841110CcE5550bD5AC6f875F"), PoolFactory::ComposableStablePoolFactoryV1),
+    (hex!("85a80afee867aDf27B50BdB7b76DA70f1E853062"), PoolFactory::ComposableStablePoolFactoryV2),
+    (hex!("dba127fBc23fb20F5929C546af220A991b5C6e01"), PoolFactory::ComposableStablePoolFactoryV3),
+    (hex!("fADa0f4547AB2de89D1304A668C39B3E09Aa7c76"), PoolFactory::ComposableStablePoolFactoryV4),
+    (hex!("c66Ba2B6595D3613CCab350C886aCE23866EDe24"), PoolFactory::StablePoolFactory),
+    (hex!("8df6EfEc5547e31B0eb7d1291B511FF8a2bf987c"), PoolFactory::StablePoolFactoryV2),
+    (hex!("67d27634E44793fE63c467035E31ea8635117cd4"), PoolFactory::MetaStablePoolFactory),
+    (hex!("813EE7a840CE909E7Fea2117A44a90b8063bd4fd"), PoolFactory::ERC4626LinearPoolFactory),
+    (hex!("5F43FBa61f63Fa6bFF101a0A0458cEA917f6B347"), PoolFactory::EulerLinearPoolFactory),
+    (hex!("39A79EB449Fc05C92c39aA6f0e9BfaC03BE8dE5B"), PoolFactory::GearboxLinearPoolFactory),
+    (hex!("4E11AEec21baF1660b1a46472963cB3DA7811C89"), PoolFactory::SiloLinearPoolFactory),
+    (hex!("5F5222Ffa40F2AEd6380D022184D6ea67C776eE0"), PoolFactory::YearnLinearPoolFactory),
+    (hex!("BF904F9F340745B4f0c4702c7B6Ab1e808eA6b93"), PoolFactory::ManagedPoolFactory),
+    (
+        hex!("751A0bC0e3f75b38e01Cf25bFCE7fF36DE1C87DE"),
+        PoolFactory::LiquidityBootstrappingPoolFactory,
+    ),
+    (
+        hex!("0F3e0c4218b7b0108a3643cFe9D3ec0d4F57c54e"),
+        PoolFactory::NoProtocolFeeLiquidityBootstrappingPoolFactory,
+    ),
+];
+
+const ARBITRUM: Registry = &[
+    (hex!("7dFdEF5f355096603419239CE743BfaF1120312B"), PoolFactory::WeightedPoolFactoryV1),
+    (hex!("c7E5ED1054A24Ef31D827E6F86caA58B3Bc168d7"), PoolFactory::WeightedPoolFactoryV4),
+    (hex!("CF0a32Bbef8F064969F21f7e02328FB577382018"), PoolFactory::WeightedPool2TokensFactory),
+    (hex!("A8920455934Da4D853faac1f94Fe7bEf72943eF1"), PoolFactory::ComposableStablePoolFactory),
+    (hex!("aEb406b0E430BF5Ea2Dc0B9Fe62E4E53f74B3a33"), PoolFactory::ComposableStablePoolFactoryV1),
+];
+
+const POLYGON: Registry = &[
+    (hex!("8E9aa87E45e92bad84D5F8DD1bff34Fb92637dE9"), PoolFactory::WeightedPoolFactoryV1),
+    (hex!("Fc8a407Bba312ac761D8BFe04CE1201904842B76"), PoolFactory::WeightedPoolFactoryV4),
+    (hex!("A5bf2ddF098bb0Ef6d120C98217dD6B141c74EE0"), PoolFactory::WeightedPool2TokensFactory),
+    (hex!("e2fa4e1d17725e72dcdAfe943Ecf45dF4B9E285b"), PoolFactory::ComposableStablePoolFactory),
+    (hex!("136FD06Fa01eCF624C7F2B3CB15742c1339dC2c4"), PoolFactory::ComposableStablePoolFactoryV1),
+];
+
+const GNOSIS: Registry = &[
+    (hex!("6CaD2ea22BFA7F4C14Aae92E47F510Cd5C509bc7"), PoolFactory::WeightedPoolFactoryV4),
+    (hex!("4bdCc2fb18AEb9e2d281b0278D946445070EAda7"), PoolFactory::ComposableStablePoolFactory),
+];
+
+const BASE: Registry = &[
+    (hex!("4C32a8a8fDa4E24139B51b456B42290f51d6A1c4"), PoolFactory::WeightedPoolFactoryV4),
+    (hex!("8df317a729fcaA260306d7de28888932cb579b88"), PoolFactory::ComposableStablePoolFactory),
+];
+
+const OPTIMISM: Registry = &[
+    (hex!("230a59F4d9ADc147480f03B0D3fFfeCd56c3289a"), PoolFactory::WeightedPoolFactoryV4),
+    (hex!("043A2daD730d585C44FB79D2614F295D2d625412"), PoolFactory::ComposableStablePoolFactory),
+];
+
+const AVALANCHE: Registry = &[
+    (hex!("230a59F4d9ADc147480f03B0D3fFfeCd56c3289a"), PoolFactory::WeightedPoolFactoryV4),
+    (hex!("E42FFA682A26EF8F25891db4882932711D42e467"), PoolFactory::ComposableStablePoolFactory),
+];
+
+/// Returns the factories deployed on `chain`, each mapped to the decoder of its pools.
+pub fn registry(chain: &str) -> Result<Registry> {
+    Ok(match chain {
+        "ethereum" => ETHEREUM,
+        "arbitrum" => ARBITRUM,
+        "polygon" => POLYGON,
+        "gnosis" => GNOSIS,
+        "base" => BASE,
+        "optimism" => OPTIMISM,
+        "avalanche" => AVALANCHE,
+        chain => bail!("No factory registry for chain {chain}"),
+    })
+}
+
 /// Helper function to get pool_registered event
 fn get_pool_registered(
     tx: &TransactionTrace,
+    vault: &[u8],
     pool_address: &Vec<u8>,
 ) -> abi::vault::events::PoolRegistered {
     tx.logs_with_calls()
-        .filter(|(log, _)| log.address == VAULT_ADDRESS)
+        .filter(|(log, _)| log.address == vault)
         .filter_map(|(log, _)| abi::vault::events::PoolRegistered::match_and_decode(log))
         .find(|pool| pool.pool_address == *pool_address)
         .unwrap()
@@ -24,10 +196,11 @@ fn get_pool_registered(
 
 fn get_token_registered(
     tx: &TransactionTrace,
+    vault: &[u8],
     pool_id: &[u8],
 ) -> abi::vault::events::TokensRegistered {
     tx.logs_with_calls()
-        .filter(|(log, _)| log.address == VAULT_ADDRESS)
+        .filter(|(log, _)| log.address == vault)
         .filter_map(|(log, _)| abi::vault::events::TokensRegistered::match_and_decode(log))
         .find(|ev| ev.pool_id == pool_id)
         .unwrap()
@@ -35,194 +208,166 @@ fn get_token_registered(
 }
 
 // This is the main function that handles the creation of `ProtocolComponent`s with `Attribute`s
-//  based on the specific factory address. There's 3 factory groups that are represented here:
+//  based on the factory that created the pool, see `registry` for the factories of each chain.
+//  There's 6 factory groups that are represented here:
 //  - Weighted Pool Factories
 //  - Linear Pool Factories
-//  - Stable Pool Factories
-// (Balancer does have a bit more (esp. in the deprecated section) that could be implemented as
-//  desired.)
+//  - Stable Pool Factories (incl. meta stable and composable stable)
+//  - Managed Pool Factory
+//  - Liquidity Bootstrapping Pool Factories
+//  - Deprecated factories that still have active pools
 // We use the specific ABIs to decode both the log event and corresponding call to gather
 //  `PoolCreated` event information alongside the `Create` call data that provide us details to
 //  fulfill both the required details + any extra `Attributes`
-// Ref: https://docs.balancer.fi/reference/contracts/deployment-addresses/mainnet.html
 pub fn address_map(
-    pool_factory_address: &[u8],
+    factory: PoolFactory,
+    vault: &[u8],
     log: &Log,
     call: &Call,
     tx: &TransactionTrace,
 ) -> Option<ProtocolComponent> {
-    match *pool_factory_address {
-        hex!("8E9aa87E45e92bad84D5F8DD1bff34Fb92637dE9") => {
+    match factory {
+        PoolFactory::WeightedPoolFactoryV1 => {
             let create_call =
                 abi::weighted_pool_factory_v1::functions::Create::match_and_decode(call)?;
             let pool_created =
                 abi::weighted_pool_factory_v1::events::PoolCreated::match_and_decode(log)?;
-            let pool_registered = get_pool_registered(tx, &pool_created.pool);
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
 
             Some(
                 ProtocolComponent::new(
                     &format!("0x{}", hex::encode(pool_registered.pool_id)),
                     &(tx.into()),
                 )
-                .with_contracts(&[pool_created.pool, VAULT_ADDRESS.to_vec()])
+                .with_contracts(&[pool_created.pool, vault.to_vec()])
                 .with_tokens(&create_call.tokens)
                 .with_attributes(&[
-                    ("pool_type", "WeightedPoolFactoryV1".as_bytes()),
+                    ("pool_type", factory.pool_type().as_bytes()),
                     ("normalized_weights", &json_serialize_bigint_list(&create_call.weights)),
-                    (
-                        "fee",
-                        &create_call
-                            .swap_fee_percentage
-                            .to_signed_bytes_be(),
-                    ),
                     ("manual_updates", &[1u8]),
                 ])
                 .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
             )
         }
-        hex!("cC508a455F5b0073973107Db6a878DdBDab957bC") => {
+        PoolFactory::WeightedPoolFactoryV2 => {
             let create_call =
                 abi::weighted_pool_factory_v2::functions::Create::match_and_decode(call)?;
             let pool_created =
                 abi::weighted_pool_factory_v2::events::PoolCreated::match_and_decode(log)?;
-            let pool_registered = get_pool_registered(tx, &pool_created.pool);
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
 
             Some(
                 ProtocolComponent::new(
                     &format!("0x{}", hex::encode(pool_registered.pool_id)),
                     &(tx.into()),
                 )
-                .with_contracts(&[pool_created.pool, VAULT_ADDRESS.to_vec()])
+                .with_contracts(&[pool_created.pool, vault.to_vec()])
                 .with_tokens(&create_call.tokens)
                 .with_attributes(&[
-                    ("pool_type", "WeightedPoolFactoryV2".as_bytes()),
+                    ("pool_type", factory.pool_type().as_bytes()),
                     (
                         "normalized_weights",
                         &json_serialize_bigint_list(&create_call.normalized_weights),
                     ),
                     ("rate_providers", &json_serialize_address_list(&create_call.rate_providers)),
-                    (
-                        "fee",
-                        &create_call
-                            .swap_fee_percentage
-                            .to_signed_bytes_be(),
-                    ),
                     ("manual_updates", &[1u8]),
                 ])
                 .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
             )
         }
-        hex!("5Dd94Da3644DDD055fcf6B3E1aa310Bb7801EB8b") => {
+        PoolFactory::WeightedPoolFactoryV3 => {
             let create_call =
                 abi::weighted_pool_factory_v3::functions::Create::match_and_decode(call)?;
             let pool_created =
                 abi::weighted_pool_factory_v3::events::PoolCreated::match_and_decode(log)?;
-            let pool_registered = get_pool_registered(tx, &pool_created.pool);
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
 
             Some(
                 ProtocolComponent::new(
                     &format!("0x{}", hex::encode(pool_registered.pool_id)),
                     &(tx.into()),
                 )
-                .with_contracts(&[pool_created.pool, VAULT_ADDRESS.to_vec()])
+                .with_contracts(&[pool_created.pool, vault.to_vec()])
                 .with_tokens(&create_call.tokens)
                 .with_attributes(&[
-                    ("pool_type", "WeightedPoolFactoryV3".as_bytes()),
+                    ("pool_type", factory.pool_type().as_bytes()),
                     (
                         "normalized_weights",
                         &json_serialize_bigint_list(&create_call.normalized_weights),
                     ),
                     ("rate_providers", &json_serialize_address_list(&create_call.rate_providers)),
-                    (
-                        "fee",
-                        &create_call
-                            .swap_fee_percentage
-                            .to_signed_bytes_be(),
-                    ),
                     ("manual_updates", &[1u8]),
                 ])
                 .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
             )
         }
-        hex!("897888115Ada5773E02aA29F775430BFB5F34c51") => {
+        PoolFactory::WeightedPoolFactoryV4 => {
             let create_call =
                 abi::weighted_pool_factory_v4::functions::Create::match_and_decode(call)?;
             let pool_created =
                 abi::weighted_pool_factory_v4::events::PoolCreated::match_and_decode(log)?;
-            let pool_registered = get_pool_registered(tx, &pool_created.pool);
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
 
             Some(
                 ProtocolComponent::new(
                     &format!("0x{}", hex::encode(pool_registered.pool_id)),
                     &(tx.into()),
                 )
-                .with_contracts(&[pool_created.pool, VAULT_ADDRESS.to_vec()])
+                .with_contracts(&[pool_created.pool, vault.to_vec()])
                 .with_tokens(&create_call.tokens)
                 .with_attributes(&[
-                    ("pool_type", "WeightedPoolFactoryV4".as_bytes()),
+                    ("pool_type", factory.pool_type().as_bytes()),
                     (
                         "normalized_weights",
                         &json_serialize_bigint_list(&create_call.normalized_weights),
                     ),
                     ("rate_providers", &json_serialize_address_list(&create_call.rate_providers)),
-                    (
-                        "fee",
-                        &create_call
-                            .swap_fee_percentage
-                            .to_signed_bytes_be(),
-                    ),
                     ("manual_updates", &[1u8]),
                 ])
                 .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
             )
         }
-        hex!("DB8d758BCb971e482B2C45f7F8a7740283A1bd3A") => {
+        PoolFactory::ComposableStablePoolFactory => {
             let create_call =
                 abi::composable_stable_pool_factory::functions::Create::match_and_decode(call)?;
             let pool_created =
                 abi::composable_stable_pool_factory::events::PoolCreated::match_and_decode(log)?;
-            let pool_registered = get_pool_registered(tx, &pool_created.pool);
-            let tokens_registered = get_token_registered(tx, &pool_registered.pool_id);
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
+            let tokens_registered = get_token_registered(tx, vault, &pool_registered.pool_id);
 
             Some(
                 ProtocolComponent::new(
                     &format!("0x{}", hex::encode(pool_registered.pool_id)),
                     &(tx.into()),
                 )
-                .with_contracts(&[pool_created.pool.clone(), VAULT_ADDRESS.to_vec()])
+                .with_contracts(&[pool_created.pool.clone(), vault.to_vec()])
                 .with_tokens(&tokens_registered.tokens)
                 .with_attributes(&[
-                    ("pool_type", "ComposableStablePoolFactory".as_bytes()),
+                    ("pool_type", factory.pool_type().as_bytes()),
                     ("bpt", &pool_created.pool),
-                    (
-                        "fee",
-                        &create_call
-                            .swap_fee_percentage
-                            .to_signed_bytes_be(),
-                    ),
                     ("rate_providers", &json_serialize_address_list(&create_call.rate_providers)),
                     ("manual_updates", &[1u8]),
                 ])
                 .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
             )
         }
-        hex!("813EE7a840CE909E7Fea2117A44a90b8063bd4fd") => {
+        PoolFactory::ERC4626LinearPoolFactory => {
             let create_call =
                 abi::erc_linear_pool_factory::functions::Create::match_and_decode(call)?;
             let pool_created =
                 abi::erc_linear_pool_factory::events::PoolCreated::match_and_decode(log)?;
-            let pool_registered = get_pool_registered(tx, &pool_created.pool);
-            let tokens_registered = get_token_registered(tx, &pool_registered.pool_id);
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
+            let tokens_registered = get_token_registered(tx, vault, &pool_registered.pool_id);
 
             Some(
                 ProtocolComponent::new(
                     &format!("0x{}", hex::encode(pool_registered.pool_id)),
                     &(tx.into()),
                 )
-                .with_contracts(&[pool_created.pool.clone(), VAULT_ADDRESS.to_vec()])
+                .with_contracts(&[pool_created.pool.clone(), vault.to_vec()])
                 .with_tokens(&tokens_registered.tokens)
                 .with_attributes(&[
-                    ("pool_type", "ERC4626LinearPoolFactory".as_bytes()),
+                    ("pool_type", factory.pool_type().as_bytes()),
                     (
                         "upper_target",
                         &create_call
@@ -233,33 +378,27 @@ pub fn address_map(
                     ("bpt", &pool_created.pool),
                     ("main_token", &create_call.main_token),
                     ("wrapped_token", &create_call.wrapped_token),
-                    (
-                        "fee",
-                        &create_call
-                            .swap_fee_percentage
-                            .to_signed_bytes_be(),
-                    ),
                 ])
                 .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
             )
         }
-        hex!("5F43FBa61f63Fa6bFF101a0A0458cEA917f6B347") => {
+        PoolFactory::EulerLinearPoolFactory => {
             let create_call =
                 abi::euler_linear_pool_factory::functions::Create::match_and_decode(call)?;
             let pool_created =
                 abi::euler_linear_pool_factory::events::PoolCreated::match_and_decode(log)?;
-            let pool_registered = get_pool_registered(tx, &pool_created.pool);
-            let tokens_registered = get_token_registered(tx, &pool_registered.pool_id);
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
+            let tokens_registered = get_token_registered(tx, vault, &pool_registered.pool_id);
 
             Some(
                 ProtocolComponent::new(
                     &format!("0x{}", hex::encode(pool_registered.pool_id)),
                     &(tx.into()),
                 )
-                .with_contracts(&[pool_created.pool.clone(), VAULT_ADDRESS.to_vec()])
+                .with_contracts(&[pool_created.pool.clone(), vault.to_vec()])
                 .with_tokens(&tokens_registered.tokens)
                 .with_attributes(&[
-                    ("pool_type", "EulerLinearPoolFactory".as_bytes()),
+                    ("pool_type", factory.pool_type().as_bytes()),
                     (
                         "upper_target",
                         &create_call
@@ -270,81 +409,99 @@ pub fn address_map(
                     ("bpt", &pool_created.pool),
                     ("main_token", &create_call.main_token),
                     ("wrapped_token", &create_call.wrapped_token),
+                ])
+                .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
+            )
+        }
+        // The Gearbox factory has been disabled, but pools created before remain active.
+        PoolFactory::GearboxLinearPoolFactory => {
+            let create_call =
+                abi::gearbox_linear_pool_factory::functions::Create::match_and_decode(call)?;
+            let pool_created =
+                abi::gearbox_linear_pool_factory::events::PoolCreated::match_and_decode(log)?;
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
+            let tokens_registered = get_token_registered(tx, vault, &pool_registered.pool_id);
+
+            Some(
+                ProtocolComponent::new(
+                    &format!("0x{}", hex::encode(pool_registered.pool_id)),
+                    &(tx.into()),
+                )
+                .with_contracts(&[pool_created.pool.clone(), vault.to_vec()])
+                .with_tokens(&tokens_registered.tokens)
+                .with_attributes(&[
+                    ("pool_type", factory.pool_type().as_bytes()),
                     (
-                        "fee",
+                        "upper_target",
                         &create_call
-                            .swap_fee_percentage
+                            .upper_target
                             .to_signed_bytes_be(),
                     ),
+                    ("manual_updates", &[1u8]),
+                    ("bpt", &pool_created.pool),
+                    ("main_token", &create_call.main_token),
+                    ("wrapped_token", &create_call.wrapped_token),
                 ])
                 .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
             )
         }
-        // ❌ Reading the deployed factory for Gearbox showcases that it's currently disabled
-        // hex!("39A79EB449Fc05C92c39aA6f0e9BfaC03BE8dE5B") => {
-        //     let create_call =
-        //         abi::gearbox_linear_pool_factory::functions::Create::match_and_decode(call)?;
-        //     let pool_created =
-        //         abi::gearbox_linear_pool_factory::events::PoolCreated::match_and_decode(log)?;
-
-        //     Some(tycho::ProtocolComponent {
-        //         id: hex::encode(&pool_created.pool),
-        //         tokens: vec![create_call.main_token, create_call.wrapped_token],
-        //         contracts: vec![pool_addr.into(), pool_created.pool],
-        //         static_att: vec![
-        //             tycho::Attribute {
-        //                 name: "pool_type".into(),
-        //                 value: "GearboxLinearPoolFactory".into(),
-        //                 change: tycho::ChangeType::Creation.into(),
-        //             },
-        //             tycho::Attribute {
-        //                 name: "upper_target".into(),
-        //                 value: create_call.upper_target.to_signed_bytes_be(),
-        //                 change: tycho::ChangeType::Creation.into(),
-        //             },
-        //         ],
-        //         change: tycho::ChangeType::Creation.into(),
-        //     })
-        // }
-        // ❌ The `ManagedPoolFactory` is a bit ✨ unique ✨, so we'll leave it commented out for
-        // now Take a look at it's `Create` call to see how the params are structured.
-        // hex!("BF904F9F340745B4f0c4702c7B6Ab1e808eA6b93") => {
-        //     let create_call =
-        // abi::managed_pool_factory::functions::Create::match_and_decode(call)?;
-        //     let pool_created =
-        //         abi::managed_pool_factory::events::PoolCreated::match_and_decode(log)?;
-
-        //     Some(tycho::ProtocolComponent {
-        //         id: hex::encode(&pool_created.pool),
-        //         tokens: create_call.tokens,
-        //         contracts: vec![pool_addr.into(), pool_created.pool],
-        //         static_att: vec![
-        //             tycho::Attribute {
-        //                 name: "pool_type".into(),
-        //                 value: "ManagedPoolFactory".into(),
-        //                 change: tycho::ChangeType::Creation.into(),
-        //             },
-        //         ],
-        //         change: tycho::ChangeType::Creation.into(),
-        //     })
-        // }
-        hex!("4E11AEec21baF1660b1a46472963cB3DA7811C89") => {
+        // The `ManagedPoolFactory` nests its params into a `(name, symbol, assetManagers)` and a
+        // settings tuple. Weights are not static, they follow the pool's weight change schedule
+        // which is tracked through its events.
+        PoolFactory::ManagedPoolFactory => {
+            let create_call = abi::managed_pool_factory::functions::Create::match_and_decode(call)?;
+            let pool_created =
+                abi::managed_pool_factory::events::PoolCreated::match_and_decode(log)?;
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
+            let tokens_registered = get_token_registered(tx, vault, &pool_registered.pool_id);
+            let (_, _, asset_managers) = create_call.params;
+            let (_, _, _, _, must_allowlist_lps, management_aum_fee_percentage, _) =
+                create_call.settings_params;
+            let mut attributes = vec![
+                ("pool_type", factory.pool_type().as_bytes().to_vec()),
+                ("asset_managers", json_serialize_address_list(&asset_managers)),
+                ("must_allowlist_lps", vec![must_allowlist_lps as u8]),
+                (
+                    "management_aum_fee_percentage",
+                    management_aum_fee_percentage.to_signed_bytes_be(),
+                ),
+                ("manual_updates", vec![1u8]),
+            ];
+            if tokens_registered
+                .tokens
+                .contains(&pool_created.pool)
+            {
+                attributes.push(("bpt", pool_created.pool.clone()));
+            }
+
+            Some(
+                ProtocolComponent::new(
+                    &format!("0x{}", hex::encode(pool_registered.pool_id)),
+                    &(tx.into()),
+                )
+                .with_contracts(&[pool_created.pool, vault.to_vec()])
+                .with_tokens(&tokens_registered.tokens)
+                .with_attributes(&attributes)
+                .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
+            )
+        }
+        PoolFactory::SiloLinearPoolFactory => {
             let create_call =
                 abi::silo_linear_pool_factory::functions::Create::match_and_decode(call)?;
             let pool_created =
                 abi::silo_linear_pool_factory::events::PoolCreated::match_and_decode(log)?;
-            let pool_registered = get_pool_registered(tx, &pool_created.pool);
-            let tokens_registered = get_token_registered(tx, &pool_registered.pool_id);
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
+            let tokens_registered = get_token_registered(tx, vault, &pool_registered.pool_id);
 
             Some(
                 ProtocolComponent::new(
                     &format!("0x{}", hex::encode(pool_registered.pool_id)),
                     &(tx.into()),
                 )
-                .with_contracts(&[pool_created.pool.clone(), VAULT_ADDRESS.to_vec()])
+                .with_contracts(&[pool_created.pool.clone(), vault.to_vec()])
                 .with_tokens(&tokens_registered.tokens)
                 .with_attributes(&[
-                    ("pool_type", "SiloLinearPoolFactory".as_bytes()),
+                    ("pool_type", factory.pool_type().as_bytes()),
                     (
                         "upper_target",
                         &create_call
@@ -355,33 +512,27 @@ pub fn address_map(
                     ("bpt", &pool_created.pool),
                     ("main_token", &create_call.main_token),
                     ("wrapped_token", &create_call.wrapped_token),
-                    (
-                        "fee",
-                        &create_call
-                            .swap_fee_percentage
-                            .to_signed_bytes_be(),
-                    ),
                 ])
                 .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
             )
         }
-        hex!("5F5222Ffa40F2AEd6380D022184D6ea67C776eE0") => {
+        PoolFactory::YearnLinearPoolFactory => {
             let create_call =
                 abi::yearn_linear_pool_factory::functions::Create::match_and_decode(call)?;
             let pool_created =
                 abi::yearn_linear_pool_factory::events::PoolCreated::match_and_decode(log)?;
-            let pool_registered = get_pool_registered(tx, &pool_created.pool);
-            let tokens_registered = get_token_registered(tx, &pool_registered.pool_id);
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
+            let tokens_registered = get_token_registered(tx, vault, &pool_registered.pool_id);
 
             Some(
                 ProtocolComponent::new(
                     &format!("0x{}", hex::encode(pool_registered.pool_id)),
                     &(tx.into()),
                 )
-                .with_contracts(&[pool_created.pool.clone(), VAULT_ADDRESS.to_vec()])
+                .with_contracts(&[pool_created.pool.clone(), vault.to_vec()])
                 .with_tokens(&tokens_registered.tokens)
                 .with_attributes(&[
-                    ("pool_type", "YearnLinearPoolFactory".as_bytes()),
+                    ("pool_type", factory.pool_type().as_bytes()),
                     (
                         "upper_target",
                         &create_call
@@ -392,46 +543,269 @@ pub fn address_map(
                     ("bpt", &pool_created.pool),
                     ("main_token", &create_call.main_token),
                     ("wrapped_token", &create_call.wrapped_token),
-                    (
-                        "fee",
-                        &create_call
-                            .swap_fee_percentage
-                            .to_signed_bytes_be(),
-                    ),
                 ])
                 .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
             )
         }
         // The `WeightedPool2TokenFactory` is a deprecated contract, but we've included
         // it to be able to track one of the highest TVL pools: 80BAL-20WETH.
-        hex!("A5bf2ddF098bb0Ef6d120C98217dD6B141c74EE0") => {
+        PoolFactory::WeightedPool2TokensFactory => {
             let create_call =
                 abi::weighted_pool_tokens_factory::functions::Create::match_and_decode(call)?;
             let pool_created =
                 abi::weighted_pool_tokens_factory::events::PoolCreated::match_and_decode(log)?;
-            let pool_registered = get_pool_registered(tx, &pool_created.pool);
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
 
             Some(
                 ProtocolComponent::new(
                     &format!("0x{}", hex::encode(pool_registered.pool_id)),
                     &(tx.into()),
                 )
-                .with_contracts(&[pool_created.pool, VAULT_ADDRESS.to_vec()])
+                .with_contracts(&[pool_created.pool, vault.to_vec()])
                 .with_tokens(&create_call.tokens)
                 .with_attributes(&[
-                    ("pool_type", "WeightedPool2TokensFactory".as_bytes()),
+                    ("pool_type", factory.pool_type().as_bytes()),
                     ("weights", &json_serialize_bigint_list(&create_call.weights)),
-                    (
-                        "fee",
-                        &create_call
-                            .swap_fee_percentage
-                            .to_signed_bytes_be(),
-                    ),
                     ("manual_updates", &[1u8]),
                 ])
                 .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
             )
         }
-        _ => None,
+        // Deprecated factories, new pools can't be created anymore but the existing ones are
+        // still active.
+        PoolFactory::StablePoolFactory | PoolFactory::StablePoolFactoryV2 => {
+            let create_call = abi::stable_pool_factory::functions::Create::match_and_decode(call)?;
+            let pool_created =
+                abi::stable_pool_factory::events::PoolCreated::match_and_decode(log)?;
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
+
+            Some(
+                ProtocolComponent::new(
+                    &format!("0x{}", hex::encode(pool_registered.pool_id)),
+                    &(tx.into()),
+                )
+                .with_contracts(&[pool_created.pool, vault.to_vec()])
+                .with_tokens(&create_call.tokens)
+                .with_attributes(&[
+                    ("pool_type", factory.pool_type().as_bytes()),
+                    ("manual_updates", &[1u8]),
+                ])
+                .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
+            )
+        }
+        PoolFactory::MetaStablePoolFactory => {
+            let create_call =
+                abi::meta_stable_pool_factory::functions::Create::match_and_decode(call)?;
+            let pool_created =
+                abi::meta_stable_pool_factory::events::PoolCreated::match_and_decode(log)?;
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
+
+            Some(
+                ProtocolComponent::new(
+                    &format!("0x{}", hex::encode(pool_registered.pool_id)),
+                    &(tx.into()),
+                )
+                .with_contracts(&[pool_created.pool, vault.to_vec()])
+                .with_tokens(&create_call.tokens)
+                .with_attributes(&[
+                    ("pool_type", factory.pool_type().as_bytes()),
+                    ("rate_providers", &json_serialize_address_list(&create_call.rate_providers)),
+                    ("manual_updates", &[1u8]),
+                ])
+                .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
+            )
+        }
+        // Liquidity bootstrapping pools change their weights over time, the weight change
+        // schedule is tracked through the pool's events.
+        PoolFactory::LiquidityBootstrappingPoolFactory |
+        PoolFactory::NoProtocolFeeLiquidityBootstrappingPoolFactory => {
+            let create_call =
+                abi::liquidity_bootstrapping_pool_factory::functions::Create::match_and_decode(
+                    call,
+                )?;
+            let pool_created =
+                abi::liquidity_bootstrapping_pool_factory::events::PoolCreated::match_and_decode(
+                    log,
+                )?;
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
+
+            Some(
+                ProtocolComponent::new(
+                    &format!("0x{}", hex::encode(pool_registered.pool_id)),
+                    &(tx.into()),
+                )
+                .with_contracts(&[pool_created.pool, vault.to_vec()])
+                .with_tokens(&create_call.tokens)
+                .with_attributes(&[
+                    ("pool_type", factory.pool_type().as_bytes()),
+                    ("manual_updates", &[1u8]),
+                ])
+                .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
+            )
+        }
+        // Older composable stable factories differ from the current one in their trailing
+        // `create` params only, the rate providers are decoded from the shared leading params.
+        PoolFactory::ComposableStablePoolFactoryV1 |
+        PoolFactory::ComposableStablePoolFactoryV2 |
+        PoolFactory::ComposableStablePoolFactoryV3 |
+        PoolFactory::ComposableStablePoolFactoryV4 => {
+            let rate_providers = decode_composable_stable_rate_providers(call)?;
+            let pool_created =
+                abi::composable_stable_pool_factory::events::PoolCreated::match_and_decode(log)?;
+            let pool_registered = get_pool_registered(tx, vault, &pool_created.pool);
+            let tokens_registered = get_token_registered(tx, vault, &pool_registered.pool_id);
+
+            Some(
+                ProtocolComponent::new(
+                    &format!("0x{}", hex::encode(pool_registered.pool_id)),
+                    &(tx.into()),
+                )
+                .with_contracts(&[pool_created.pool.clone(), vault.to_vec()])
+                .with_tokens(&tokens_registered.tokens)
+                .with_attributes(&[
+                    ("pool_type", factory.pool_type().as_bytes()),
+                    ("bpt", &pool_created.pool),
+                    ("rate_providers", &json_serialize_address_list(&rate_providers)),
+                    ("manual_updates", &[1u8]),
+                ])
+                .as_swap_type("balancer_v2_pool", ImplementationType::Vm),
+            )
+        }
+    }
+}
+
+/// Decodes the rate providers of a composable stable factory's `create` call.
+///
+/// All composable stable factory versions share the leading `create` params
+/// `(name, symbol, tokens, amplificationParameter, rateProviders)`.
+fn decode_composable_stable_rate_providers(call: &Call) -> Option<Vec<Vec<u8>>> {
+    let params = ethabi::decode(
+        &[
+            ethabi::ParamType::String,
+            ethabi::ParamType::String,
+            ethabi::ParamType::Array(Box::new(ethabi::ParamType::Address)),
+            ethabi::ParamType::Uint(256),
+            ethabi::ParamType::Array(Box::new(ethabi::ParamType::Address)),
+        ],
+        call.input.get(4..)?,
+    )
+    .ok()?;
+    params[4]
+        .clone()
+        .into_array()?
+        .into_iter()
+        .map(|token| {
+            Some(
+                token
+                    .into_address()?
+                    .as_bytes()
+                    .to_vec(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethabi::{encode, Address, Token};
+
+    fn address(byte: u8) -> Token {
+        Token::Address(Address::repeat_byte(byte))
+    }
+
+    fn create_call(trailing: Vec<Token>) -> Call {
+        let mut params = vec![
+            Token::String("Composable Stable".to_string()),
+            Token::String("CSP".to_string()),
+            Token::Array(vec![address(0x11), address(0x22)]),
+            Token::Uint(200.into()),
+            Token::Array(vec![address(0xaa), address(0x00)]),
+        ];
+        params.extend(trailing);
+        Call {
+            input: [vec![0xde, 0xad, 0xbe, 0xef], encode(&params)].concat(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_registry_older_factories() {
+        let factory = |chain: &str, address: [u8; 20]| {
+            registry(chain)
+                .unwrap()
+                .iter()
+                .find(|(registered, _)| *registered == address)
+                .map(|(_, factory)| *factory)
+        };
+
+        assert_eq!(
+            factory("arbitrum", hex!("7dFdEF5f355096603419239CE743BfaF1120312B")),
+            Some(PoolFactory::WeightedPoolFactoryV1)
+        );
+        assert_eq!(
+            factory("arbitrum", hex!("aEb406b0E430BF5Ea2Dc0B9Fe62E4E53f74B3a33")),
+            Some(PoolFactory::ComposableStablePoolFactoryV1)
+        );
+        assert_eq!(
+            factory("polygon", hex!("8E9aa87E45e92bad84D5F8DD1bff34Fb92637dE9")),
+            Some(PoolFactory::WeightedPoolFactoryV1)
+        );
+        assert_eq!(
+            factory("polygon", hex!("136FD06Fa01eCF624C7F2B3CB15742c1339dC2c4")),
+            Some(PoolFactory::ComposableStablePoolFactoryV1)
+        );
+    }
+
+    #[test]
+    fn test_pool_factory_from_str() {
+        for factory in PoolFactory::ALL {
+            assert_eq!(
+                factory
+                    .pool_type()
+                    .parse::<PoolFactory>()
+                    .unwrap(),
+                factory
+            );
+        }
+        assert_eq!(
+            "ComposableStablePoolFactoryV4"
+                .parse::<PoolFactory>()
+                .unwrap(),
+            PoolFactory::ComposableStablePoolFactoryV4
+        );
+        assert!("WeightedPoolFactoryV5"
+            .parse::<PoolFactory>()
+            .is_err());
+        assert!("weightedpoolfactoryv4"
+            .parse::<PoolFactory>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_decode_composable_stable_rate_providers() {
+        let expected = Some(vec![vec![0xaa; 20], vec![0x00; 20]]);
+
+        assert_eq!(decode_composable_stable_rate_providers(&create_call(vec![])), expected);
+        // Later factory versions append further params after the rate providers.
+        assert_eq!(
+            decode_composable_stable_rate_providers(&create_call(vec![
+                Token::Array(vec![Token::Uint(10800.into()); 2]),
+                Token::Bool(false),
+                Token::Uint(100.into()),
+                address(0x33),
+                Token::FixedBytes(vec![0x44; 32]),
+            ])),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_decode_composable_stable_rate_providers_invalid_input() {
+        let mut call = create_call(vec![]);
+        call.input.truncate(100);
+
+        assert_eq!(decode_composable_stable_rate_providers(&call), None);
+        assert_eq!(decode_composable_stable_rate_providers(&Call::default()), None);
     }
 }
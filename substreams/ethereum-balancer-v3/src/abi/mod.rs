@@ -0,0 +1,4 @@
+#![allow(clippy::all)]
+pub mod erc4626;
+pub mod protocol_fee_controller;
+pub mod vault;
@@ -0,0 +1,244 @@
+use crate::abi::{
+    erc4626::functions::Asset,
+    vault::events::{LiquidityAddedToBuffer, PoolRegistered},
+};
+use itertools::Itertools;
+use substreams_ethereum::{
+    pb::eth::v2::{Call, Log, TransactionTrace},
+    Event,
+};
+use tycho_substreams::{attributes::json_serialize_address_list, prelude::*};
+
+const ZERO_ADDRESS: [u8; 20] = [0u8; 20];
+
+/// Creates the `ProtocolComponent` of a pool registered at the vault through `PoolRegistered`.
+///
+/// Pools are identified by their address. Besides the pool and the vault, the hooks contract and
+/// the rate providers of the pool are added as contracts, since swaps call into them. The
+/// `rate_providers` attribute keeps one entry per token, tokens without a rate provider have the
+/// zero address.
+pub fn pool_component(log: &Log, vault: &[u8], tx: &TransactionTrace) -> Option<ProtocolComponent> {
+    let registered = PoolRegistered::match_and_decode(log)?;
+    let tokens = registered
+        .token_config
+        .iter()
+        .map(|(token, _, _, _)| token.clone())
+        .collect::<Vec<_>>();
+    let rate_providers = registered
+        .token_config
+        .iter()
+        .map(|(_, _, rate_provider, _)| rate_provider.clone())
+        .collect::<Vec<_>>();
+    let hooks = registered.hooks_config.10;
+
+    let mut attributes = vec![
+        ("factory", registered.factory.clone()),
+        ("rate_providers", json_serialize_address_list(&rate_providers)),
+    ];
+    if hooks != ZERO_ADDRESS {
+        attributes.push(("hooks", hooks.clone()));
+    }
+
+    let contracts = [registered.pool.clone(), vault.to_vec(), hooks]
+        .into_iter()
+        .chain(rate_providers)
+        .filter(|contract| contract != &ZERO_ADDRESS)
+        .unique()
+        .collect::<Vec<_>>();
+
+    Some(
+        ProtocolComponent::at_contract(&registered.pool, &(tx.into()))
+            .with_contracts(&contracts)
+            .with_tokens(&tokens)
+            .with_attributes(&attributes)
+            .as_swap_type("balancer_v3_pool", ImplementationType::Vm),
+    )
+}
+
+/// Creates the `ProtocolComponent` of an ERC4626 buffer initialised at the vault.
+///
+/// Buffers are identified by their wrapped token. The vault emits `LiquidityAddedToBuffer` when a
+/// buffer is initialised or topped up, only `initializeBuffer` asks the wrapped token for its
+/// `asset()`, which gives us the underlying token.
+pub fn buffer_component(
+    log: &Log,
+    call: &Call,
+    vault: &[u8],
+    tx: &TransactionTrace,
+) -> Option<ProtocolComponent> {
+    let added = LiquidityAddedToBuffer::match_and_decode(log)?;
+    let underlying = tx
+        .calls
+        .iter()
+        .filter(|nested| nested.parent_index == call.index && nested.address == added.wrapped_token)
+        .find(|nested| Asset::match_call(nested))
+        .and_then(|nested| Asset::output(&nested.return_data).ok())?;
+
+    Some(
+        ProtocolComponent::at_contract(&added.wrapped_token, &(tx.into()))
+            .with_contracts(&[added.wrapped_token.clone(), vault.to_vec()])
+            .with_tokens(&[underlying, added.wrapped_token])
+            .as_swap_type("balancer_v3_buffer", ImplementationType::Vm),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethabi::{encode, long_signature, Address, ParamType, Token};
+
+    const VAULT: [u8; 20] = [0xba; 20];
+    const POOL: [u8; 20] = [0x01; 20];
+    const FACTORY: [u8; 20] = [0x02; 20];
+    const HOOKS: [u8; 20] = [0x03; 20];
+    const RATE_PROVIDER: [u8; 20] = [0x04; 20];
+    const TOKEN_A: [u8; 20] = [0x0a; 20];
+    const TOKEN_B: [u8; 20] = [0x0b; 20];
+
+    fn address(address: [u8; 20]) -> Token {
+        Token::Address(Address::from(address))
+    }
+
+    fn topic(address: [u8; 20]) -> Vec<u8> {
+        [[0u8; 12].as_slice(), address.as_slice()].concat()
+    }
+
+    fn pool_registered(hooks: [u8; 20]) -> Log {
+        let token_config = ParamType::Tuple(vec![
+            ParamType::Address,
+            ParamType::Uint(8),
+            ParamType::Address,
+            ParamType::Bool,
+        ]);
+        let params = [
+            ParamType::Address,
+            ParamType::Address,
+            ParamType::Array(Box::new(token_config)),
+            ParamType::Uint(256),
+            ParamType::Uint(32),
+            ParamType::Tuple(vec![ParamType::Address; 3]),
+            ParamType::Tuple([vec![ParamType::Bool; 10], vec![ParamType::Address]].concat()),
+            ParamType::Tuple(vec![ParamType::Bool; 4]),
+        ];
+        let token = |token, rate_provider| {
+            Token::Tuple(vec![
+                address(token),
+                Token::Uint(1.into()),
+                address(rate_provider),
+                Token::Bool(false),
+            ])
+        };
+        Log {
+            address: VAULT.to_vec(),
+            topics: vec![
+                long_signature("PoolRegistered", &params)
+                    .0
+                    .to_vec(),
+                topic(POOL),
+                topic(FACTORY),
+            ],
+            data: encode(&[
+                Token::Array(vec![token(TOKEN_A, RATE_PROVIDER), token(TOKEN_B, ZERO_ADDRESS)]),
+                Token::Uint(10u64.pow(16).into()),
+                Token::Uint(0.into()),
+                Token::Tuple(vec![address(ZERO_ADDRESS); 3]),
+                Token::Tuple([vec![Token::Bool(false); 10], vec![address(hooks)]].concat()),
+                Token::Tuple(vec![Token::Bool(false); 4]),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pool_component() {
+        let tx = TransactionTrace { hash: vec![0x11; 32], ..Default::default() };
+
+        let component = pool_component(&pool_registered(HOOKS), &VAULT, &tx).unwrap();
+
+        assert_eq!(
+            component,
+            ProtocolComponent::at_contract(&POOL, &(&tx).into())
+                .with_contracts(&[POOL, VAULT, HOOKS, RATE_PROVIDER])
+                .with_tokens(&[TOKEN_A, TOKEN_B])
+                .with_attributes(&[
+                    ("factory", FACTORY.to_vec()),
+                    (
+                        "rate_providers",
+                        json_serialize_address_list(&[
+                            RATE_PROVIDER.to_vec(),
+                            ZERO_ADDRESS.to_vec()
+                        ])
+                    ),
+                    ("hooks", HOOKS.to_vec()),
+                ])
+                .as_swap_type("balancer_v3_pool", ImplementationType::Vm)
+        );
+    }
+
+    #[test]
+    fn test_pool_component_without_hooks() {
+        let tx = TransactionTrace::default();
+
+        let component = pool_component(&pool_registered(ZERO_ADDRESS), &VAULT, &tx).unwrap();
+
+        assert_eq!(
+            component.contracts,
+            vec![POOL.to_vec(), VAULT.to_vec(), RATE_PROVIDER.to_vec()]
+        );
+        assert!(!component
+            .static_att
+            .iter()
+            .any(|attribute| attribute.name == "hooks"));
+    }
+
+    #[test]
+    fn test_buffer_component() {
+        let params = [
+            ParamType::Address,
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+            ParamType::FixedBytes(32),
+        ];
+        let log = Log {
+            address: VAULT.to_vec(),
+            topics: vec![
+                long_signature("LiquidityAddedToBuffer", &params)
+                    .0
+                    .to_vec(),
+                topic(TOKEN_B),
+            ],
+            data: encode(&[
+                Token::Uint(100.into()),
+                Token::Uint(90.into()),
+                Token::FixedBytes(vec![0; 32]),
+            ]),
+            ..Default::default()
+        };
+        let initialize_buffer = Call { index: 1, address: VAULT.to_vec(), ..Default::default() };
+        let asset = Call {
+            index: 2,
+            parent_index: 1,
+            address: TOKEN_B.to_vec(),
+            input: long_signature("asset", &[]).0[..4].to_vec(),
+            return_data: encode(&[address(TOKEN_A)]),
+            ..Default::default()
+        };
+        let tx = TransactionTrace {
+            calls: vec![initialize_buffer.clone(), asset],
+            ..Default::default()
+        };
+
+        let component = buffer_component(&log, &initialize_buffer, &VAULT, &tx).unwrap();
+
+        assert_eq!(
+            component,
+            ProtocolComponent::at_contract(&TOKEN_B, &(&tx).into())
+                .with_contracts(&[TOKEN_B, VAULT])
+                .with_tokens(&[TOKEN_A, TOKEN_B])
+                .as_swap_type("balancer_v3_buffer", ImplementationType::Vm)
+        );
+        // Topping up a buffer does not ask the wrapped token for its asset.
+        let tx = TransactionTrace { calls: vec![initialize_buffer.clone()], ..Default::default() };
+        assert_eq!(buffer_component(&log, &initialize_buffer, &VAULT, &tx), None);
+    }
+}
@@ -0,0 +1,5 @@
+mod abi;
+mod components;
+mod modules;
+mod params;
+mod vault_events;
@@ -0,0 +1,464 @@
+use crate::{abi, components, params::Params, vault_events};
+use anyhow::Result;
+use itertools::Itertools;
+use std::collections::HashMap;
+use substreams::{
+    pb::substreams::StoreDeltas,
+    scalar::BigInt,
+    store::{
+        Appender, StoreAddBigInt, StoreAppend, StoreGet, StoreGetArray, StoreGetBigInt,
+        StoreGetProto, StoreNew, StoreSet, StoreSetBigInt, StoreSetProto,
+    },
+};
+use substreams_ethereum::{pb::eth, Event};
+use tycho_substreams::{
+    aggregation::AggregationMode, balances::aggregate_balances_changes,
+    contract::extract_contract_changes_builder, prelude::*,
+};
+
+/// Returns the key a component is stored at in `store_components`: pools and buffers are keyed by
+/// their pool and wrapped token address respectively.
+fn component_key(component: &ProtocolComponent) -> String {
+    match component.protocol_type.as_ref() {
+        Some(protocol_type) if protocol_type.name == "balancer_v3_buffer" => {
+            format!("buffer:{}", component.id)
+        }
+        _ => format!("pool:{}", component.id),
+    }
+}
+
+#[substreams::handlers::map]
+pub fn map_components(
+    params: String,
+    block: eth::v2::Block,
+) -> Result<BlockTransactionProtocolComponents> {
+    let params = Params::parse(&params)?;
+    // Pools are registered at the vault through `PoolRegistered`, so unlike V2 there is no need
+    //  to follow the pool factories. Buffers are created by `initializeBuffer` of the vault.
+    Ok(BlockTransactionProtocolComponents {
+        tx_components: block
+            .transactions()
+            .filter_map(|tx| {
+                let components = tx
+                    .logs_with_calls()
+                    .filter(|(log, _)| log.address == params.vault)
+                    .filter_map(|(log, call)| {
+                        components::pool_component(log, &params.vault, tx).or_else(|| {
+                            components::buffer_component(log, call.call, &params.vault, tx)
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                if !components.is_empty() {
+                    Some(TransactionProtocolComponents { tx: Some(tx.into()), components })
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Stores the `ProtocolComponent`s, pools at `pool:{address}` and buffers at
+/// `buffer:{wrapped_token}`.
+#[substreams::handlers::store]
+pub fn store_components(
+    map: BlockTransactionProtocolComponents,
+    store: StoreSetProto<ProtocolComponent>,
+) {
+    map.tx_components
+        .into_iter()
+        .flat_map(|tx_pc| tx_pc.components)
+        .for_each(|pc| store.set(0, component_key(&pc), &pc));
+}
+
+/// Stores the ids of the components that use a contract, keyed by `contract:{address}`.
+///
+/// Hooks and rate providers may be shared by several pools, so a change to one of them marks all
+/// of these components as updated. The vault is left out, as every component uses it.
+#[substreams::handlers::store]
+pub fn store_contracts(
+    params: String,
+    map: BlockTransactionProtocolComponents,
+    store: StoreAppend<String>,
+) {
+    let vault = Params::parse(&params)
+        .expect("Invalid params")
+        .vault;
+    map.tx_components
+        .into_iter()
+        .flat_map(|tx_pc| tx_pc.components)
+        .for_each(|pc| {
+            pc.contracts
+                .iter()
+                .filter(|contract| **contract != vault)
+                .for_each(|contract| {
+                    store.append(0, format!("contract:0x{}", hex::encode(contract)), pc.id.clone())
+                })
+        });
+}
+
+/// Stores the aggregate swap fee percentage of each pool at `aggregate_swap_fee:{pool}` and
+/// whether it is in recovery mode at `recovery_mode:{pool}`, at the ordinal of the change.
+#[substreams::handlers::store]
+pub fn store_aggregate_fees(params: String, block: eth::v2::Block, store: StoreSetBigInt) {
+    let vault = Params::parse(&params)
+        .expect("Invalid params")
+        .vault;
+    block
+        .transactions()
+        .flat_map(|tx| tx.logs_with_calls())
+        .for_each(|(log, call)| {
+            if let Some((pool, percentage)) =
+                vault_events::aggregate_swap_fee_percentage(log, call.call, &vault)
+            {
+                store.set(
+                    log.ordinal,
+                    format!("aggregate_swap_fee:0x{}", hex::encode(pool)),
+                    &percentage,
+                );
+            } else if log.address == vault {
+                if let Some(ev) =
+                    abi::vault::events::PoolRecoveryModeStateChanged::match_and_decode(log)
+                {
+                    store.set(
+                        log.ordinal,
+                        format!("recovery_mode:0x{}", hex::encode(ev.pool)),
+                        &BigInt::from(ev.recovery_mode as i32),
+                    );
+                }
+            }
+        });
+}
+
+/// Returns the aggregate fee the vault deducts from the balance of pool `component_id` for
+/// `swap_fee_amount` at `ordinal`.
+fn aggregate_fee(
+    store: &StoreGetBigInt,
+    component_id: &str,
+    ordinal: u64,
+    swap_fee_amount: &BigInt,
+) -> BigInt {
+    let recovery_mode = store
+        .get_at(ordinal, format!("recovery_mode:{}", component_id))
+        .is_some_and(|recovery_mode| recovery_mode != BigInt::zero());
+    store
+        .get_at(ordinal, format!("aggregate_swap_fee:{}", component_id))
+        .map(|percentage| {
+            vault_events::aggregate_fee_amount(swap_fee_amount, &percentage, recovery_mode)
+        })
+        .unwrap_or_else(BigInt::zero)
+}
+
+/// The `Swap`, `LiquidityAdded` and `LiquidityRemoved` events of the vault administer only
+/// deltas, we need to leverage a map and a store to be able to tally up final balances for tokens
+/// in a pool.
+///
+/// The vault charges the aggregate share of the swap fees, which the events include, to the pool
+/// balances, so it is subtracted from the amounts going into the pool and added to the amounts
+/// leaving it. Swap fees are always paid in the token in.
+#[substreams::handlers::map]
+pub fn map_relative_balances(
+    params: String,
+    block: eth::v2::Block,
+    store: StoreGetProto<ProtocolComponent>,
+    aggregate_fees_store: StoreGetBigInt,
+) -> Result<BlockBalanceDeltas, anyhow::Error> {
+    let params = Params::parse(&params)?;
+    let balance_deltas = block
+        .logs()
+        .filter(|log| log.address() == params.vault)
+        .flat_map(|vault_log| {
+            let mut deltas = Vec::new();
+
+            if let Some(ev) = abi::vault::events::Swap::match_and_decode(vault_log.log) {
+                let component_id = format!("0x{}", hex::encode(&ev.pool));
+
+                if store
+                    .get_last(format!("pool:{}", component_id))
+                    .is_some()
+                {
+                    let aggregate_fee = aggregate_fee(
+                        &aggregate_fees_store,
+                        &component_id,
+                        vault_log.ordinal(),
+                        &ev.swap_fee_amount,
+                    );
+                    deltas.extend_from_slice(&[
+                        BalanceDelta {
+                            ord: vault_log.ordinal(),
+                            tx: Some(vault_log.receipt.transaction.into()),
+                            token: ev.token_in.to_vec(),
+                            delta: (ev.amount_in - aggregate_fee).to_signed_bytes_be(),
+                            component_id: component_id.as_bytes().to_vec(),
+                        },
+                        BalanceDelta {
+                            ord: vault_log.ordinal(),
+                            tx: Some(vault_log.receipt.transaction.into()),
+                            token: ev.token_out.to_vec(),
+                            delta: ev.amount_out.neg().to_signed_bytes_be(),
+                            component_id: component_id.as_bytes().to_vec(),
+                        },
+                    ]);
+                }
+            } else if let Some(ev) =
+                abi::vault::events::LiquidityAdded::match_and_decode(vault_log.log)
+            {
+                let component_id = format!("0x{}", hex::encode(&ev.pool));
+
+                if let Some(pool) = store.get_last(format!("pool:{}", component_id)) {
+                    // Amounts are given in the token order of the pool.
+                    for ((token, amount), swap_fee_amount) in pool
+                        .tokens
+                        .iter()
+                        .zip(ev.amounts_added_raw.iter())
+                        .zip(ev.swap_fee_amounts_raw.iter())
+                    {
+                        let aggregate_fee = aggregate_fee(
+                            &aggregate_fees_store,
+                            &component_id,
+                            vault_log.ordinal(),
+                            swap_fee_amount,
+                        );
+                        deltas.push(BalanceDelta {
+                            ord: vault_log.ordinal(),
+                            tx: Some(vault_log.receipt.transaction.into()),
+                            token: token.to_vec(),
+                            delta: (amount.clone() - aggregate_fee).to_signed_bytes_be(),
+                            component_id: component_id.as_bytes().to_vec(),
+                        });
+                    }
+                }
+            } else if let Some(ev) =
+                abi::vault::events::LiquidityRemoved::match_and_decode(vault_log.log)
+            {
+                let component_id = format!("0x{}", hex::encode(&ev.pool));
+
+                if let Some(pool) = store.get_last(format!("pool:{}", component_id)) {
+                    for ((token, amount), swap_fee_amount) in pool
+                        .tokens
+                        .iter()
+                        .zip(ev.amounts_removed_raw.iter())
+                        .zip(ev.swap_fee_amounts_raw.iter())
+                    {
+                        let aggregate_fee = aggregate_fee(
+                            &aggregate_fees_store,
+                            &component_id,
+                            vault_log.ordinal(),
+                            swap_fee_amount,
+                        );
+                        deltas.push(BalanceDelta {
+                            ord: vault_log.ordinal(),
+                            tx: Some(vault_log.receipt.transaction.into()),
+                            token: token.to_vec(),
+                            delta: (amount.clone() + aggregate_fee)
+                                .neg()
+                                .to_signed_bytes_be(),
+                            component_id: component_id.as_bytes().to_vec(),
+                        });
+                    }
+                }
+            }
+
+            deltas
+        })
+        .collect::<Vec<_>>();
+
+    Ok(BlockBalanceDeltas { balance_deltas })
+}
+
+/// It's significant to include both the `pool_id` and the `token_id` for each balance delta as the
+///  store key to ensure that there's a unique balance being tallied for each.
+#[substreams::handlers::store]
+pub fn store_balances(deltas: BlockBalanceDeltas, store: StoreAddBigInt) {
+    tycho_substreams::balances::store_balance_changes(deltas, store);
+}
+
+/// This is the main map that handles most of the indexing of this substream.
+/// Every change is grouped by transaction index via the `transaction_changes` map, which at the
+///  very end is sorted by index to ensure the final `BlockChanges` is ordered by transactions.
+#[substreams::handlers::map]
+pub fn map_protocol_changes(
+    params: String,
+    block: eth::v2::Block,
+    grouped_components: BlockTransactionProtocolComponents,
+    deltas: BlockBalanceDeltas,
+    components_store: StoreGetProto<ProtocolComponent>,
+    contracts_store: StoreGetArray<String>,
+    balance_store: StoreDeltas, // Note, this map module is using the `deltas` mode for the store.
+) -> Result<BlockChanges> {
+    let vault = Params::parse(&params)?.vault;
+    // We merge contract changes by transaction (identified by transaction index) making it easy to
+    //  sort them at the very end.
+    let mut transaction_changes: HashMap<_, TransactionChangesBuilder> = HashMap::new();
+
+    let default_attributes = vec![
+        Attribute {
+            name: "balance_owner".to_string(),
+            value: vault.clone(),
+            change: ChangeType::Creation.into(),
+        },
+        Attribute {
+            name: "update_marker".to_string(),
+            value: vec![1u8],
+            change: ChangeType::Creation.into(),
+        },
+    ];
+    grouped_components
+        .tx_components
+        .iter()
+        .for_each(|tx_component| {
+            // initialise builder if not yet present for this tx
+            let tx = tx_component.tx.as_ref().unwrap();
+            let builder = transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(tx));
+
+            // iterate over individual components created within this tx
+            tx_component
+                .components
+                .iter()
+                .for_each(|component| {
+                    builder.add_protocol_component(component);
+                    let entity_change = EntityChanges {
+                        component_id: component.id.clone(),
+                        attributes: default_attributes.clone(),
+                    };
+                    builder.add_entity_change(&entity_change)
+                });
+        });
+
+    // The vault keeps the parameters of all pools, its events are processed in order so the
+    //  latest change of a transaction wins. Attributes set in the transaction that registered the
+    //  pool are creations.
+    block
+        .logs()
+        .filter(|log| log.address() == vault)
+        .for_each(|log| {
+            let Some((pool, attributes)) = vault_events::pool_attributes(log.log) else {
+                return;
+            };
+            let component_id = format!("0x{}", hex::encode(pool));
+            if components_store
+                .get_last(format!("pool:{}", component_id))
+                .is_none()
+            {
+                return;
+            }
+            let tx: Transaction = log.receipt.transaction.into();
+            let created = grouped_components
+                .tx_components
+                .iter()
+                .filter(|tx_component| tx_component.tx.as_ref() == Some(&tx))
+                .flat_map(|tx_component| tx_component.components.iter())
+                .any(|component| component.id == component_id);
+            let change = if created { ChangeType::Creation } else { ChangeType::Update };
+            transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(&tx))
+                .add_entity_change(&EntityChanges {
+                    component_id,
+                    attributes: attributes
+                        .into_iter()
+                        .map(|(name, value)| Attribute {
+                            name: name.to_string(),
+                            value,
+                            change: change.into(),
+                        })
+                        .collect(),
+                });
+        });
+
+    // Pool balance changes are gathered by the `StoreDelta` based on the `BlockBalanceDeltas`. We
+    //  essentially just process the changes that occurred to the `store` this block.
+    aggregate_balances_changes(balance_store, deltas)
+        .into_iter()
+        .for_each(|(_, (tx, balances))| {
+            let builder = transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(&tx));
+            balances
+                .values()
+                .for_each(|token_bc_map| {
+                    token_bc_map
+                        .values()
+                        .for_each(|bc| builder.add_balance_change(bc))
+                });
+        });
+
+    // Buffer events carry the absolute balances of the buffer, the last event of a transaction
+    //  overwrites the balances of the previous ones.
+    block
+        .logs()
+        .filter(|log| log.address() == vault)
+        .for_each(|log| {
+            let Some((wrapped_token, underlying_balance, wrapped_balance)) =
+                vault_events::buffer_balances(log.log)
+            else {
+                return;
+            };
+            let Some(buffer) =
+                components_store.get_last(format!("buffer:0x{}", hex::encode(&wrapped_token)))
+            else {
+                return;
+            };
+            let tx: Transaction = log.receipt.transaction.into();
+            let builder = transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(&tx));
+            for (token, balance) in buffer
+                .tokens
+                .iter()
+                .zip([underlying_balance, wrapped_balance])
+            {
+                builder.add_balance_change(&BalanceChange {
+                    token: token.clone(),
+                    balance: balance.to_signed_bytes_be(),
+                    component_id: buffer.id.as_bytes().to_vec(),
+                });
+            }
+        });
+
+    // Extract and insert any storage changes that happened for the vault or any of the pools,
+    //  hooks, rate providers and wrapped tokens of the components.
+    extract_contract_changes_builder(
+        &block,
+        |addr| {
+            addr.eq(vault.as_slice()) ||
+                contracts_store
+                    .get_last(format!("contract:0x{}", hex::encode(addr)))
+                    .is_some()
+        },
+        &mut transaction_changes,
+    );
+
+    transaction_changes
+        .iter_mut()
+        .for_each(|(_, change)| {
+            // this indirection is necessary due to borrowing rules.
+            let addresses = change
+                .changed_contracts()
+                .map(|e| e.to_vec())
+                .collect::<Vec<_>>();
+            addresses
+                .into_iter()
+                .filter(|address| *address != vault)
+                .flat_map(|address| {
+                    contracts_store
+                        .get_last(format!("contract:0x{}", hex::encode(address)))
+                        .unwrap_or_default()
+                })
+                .for_each(|id| change.mark_component_as_updated(&id));
+        });
+
+    // Process all `transaction_changes` for final output in the `BlockChanges`,
+    //  sorted by transaction index (the key).
+    Ok(AggregationMode::from_params(&params)?.apply(BlockChanges {
+        block: Some((&block).into()),
+        changes: transaction_changes
+            .drain()
+            .sorted_unstable_by_key(|(index, _)| *index)
+            .filter_map(|(_, builder)| builder.build())
+            .collect::<Vec<_>>(),
+    }))
+}
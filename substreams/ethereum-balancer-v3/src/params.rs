@@ -0,0 +1,60 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use substreams::hex;
+
+/// The V3 Vault is deployed at the same address on every chain.
+pub const DEFAULT_VAULT_ADDRESS: [u8; 20] = hex!("bA1333333333a1BA1108E8412f11850A5C319bA9");
+
+/// Params of the Balancer V3 modules, e.g. `vault=bA1333333333a1BA1108E8412f11850A5C319bA9`.
+///
+/// Other params, such as `aggregation`, are ignored.
+#[derive(Debug, Deserialize, PartialEq)]
+struct RawParams {
+    vault: Option<String>,
+}
+
+/// The Balancer V3 deployment to index.
+#[derive(Debug, PartialEq)]
+pub struct Params {
+    pub vault: Vec<u8>,
+}
+
+impl Params {
+    pub fn parse(input: &str) -> Result<Self> {
+        let raw: RawParams = serde_qs::from_str(input).context("Unable to deserialize params")?;
+        let vault = match raw.vault {
+            Some(vault) => decode_address(&vault)?,
+            None => DEFAULT_VAULT_ADDRESS.to_vec(),
+        };
+        Ok(Self { vault })
+    }
+}
+
+fn decode_address(address: &str) -> Result<Vec<u8>> {
+    let address = hex::decode(address.trim_start_matches("0x")).context("Invalid address")?;
+    if address.len() != 20 {
+        return Err(anyhow!("Expected a 20 byte address"));
+    }
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Params::parse("").unwrap(), Params { vault: DEFAULT_VAULT_ADDRESS.to_vec() });
+        assert_eq!(
+            Params::parse("vault=0x0000000000000000000000000000000000000001&aggregation=block")
+                .unwrap(),
+            Params { vault: hex!("0000000000000000000000000000000000000001").to_vec() }
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_vault() {
+        assert!(Params::parse("vault=bA13").is_err());
+        assert!(Params::parse("vault=not_an_address").is_err());
+    }
+}
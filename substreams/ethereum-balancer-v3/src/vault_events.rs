@@ -0,0 +1,224 @@
+use crate::abi::{
+    protocol_fee_controller::events::InitialPoolAggregateSwapFeePercentage,
+    vault::events::{
+        AggregateSwapFeePercentageChanged, LiquidityAddedToBuffer, LiquidityRemovedFromBuffer,
+        PoolPausedStateChanged, PoolRecoveryModeStateChanged, PoolRegistered,
+        SwapFeePercentageChanged, Unwrap, Wrap,
+    },
+};
+use substreams::scalar::BigInt;
+use substreams_ethereum::{
+    pb::eth::v2::{Call, Log},
+    Event,
+};
+
+/// A pool address and its `(name, value)` state attributes.
+type PoolAttributes = (Vec<u8>, Vec<(&'static str, Vec<u8>)>);
+
+/// Decodes the vault events through which a pool changes its parameters into `PoolAttributes`.
+///
+/// - `PoolRegistered` and `SwapFeePercentageChanged` set `fee`.
+/// - `PoolPausedStateChanged` and `PoolRecoveryModeStateChanged` set `paused` and `recovery_mode`.
+pub fn pool_attributes(log: &Log) -> Option<PoolAttributes> {
+    if let Some(ev) = PoolRegistered::match_and_decode(log) {
+        Some((
+            ev.pool,
+            vec![(
+                "fee",
+                ev.swap_fee_percentage
+                    .to_signed_bytes_be(),
+            )],
+        ))
+    } else if let Some(ev) = SwapFeePercentageChanged::match_and_decode(log) {
+        Some((
+            ev.pool,
+            vec![(
+                "fee",
+                ev.swap_fee_percentage
+                    .to_signed_bytes_be(),
+            )],
+        ))
+    } else if let Some(ev) = PoolPausedStateChanged::match_and_decode(log) {
+        Some((ev.pool, vec![("paused", vec![ev.paused as u8])]))
+    } else {
+        PoolRecoveryModeStateChanged::match_and_decode(log)
+            .map(|ev| (ev.pool, vec![("recovery_mode", vec![ev.recovery_mode as u8])]))
+    }
+}
+
+/// Decodes the buffer events of the vault into the wrapped token and the absolute underlying and
+/// wrapped balances of its buffer.
+///
+/// Wrapping through a buffer may deposit to or withdraw from the ERC4626 vault to rebalance the
+/// buffer, so the amounts of the events are not the buffer's balance deltas. The events carry the
+/// packed balances of the buffer though: the underlying balance in the lower and the wrapped
+/// balance in the upper 128 bits.
+pub fn buffer_balances(log: &Log) -> Option<(Vec<u8>, BigInt, BigInt)> {
+    let (wrapped_token, packed) = if let Some(ev) = Wrap::match_and_decode(log) {
+        (ev.wrapped_token, ev.buffer_balances)
+    } else if let Some(ev) = Unwrap::match_and_decode(log) {
+        (ev.wrapped_token, ev.buffer_balances)
+    } else if let Some(ev) = LiquidityAddedToBuffer::match_and_decode(log) {
+        (ev.wrapped_token, ev.buffer_balances)
+    } else {
+        let ev = LiquidityRemovedFromBuffer::match_and_decode(log)?;
+        (ev.wrapped_token, ev.buffer_balances)
+    };
+    Some((
+        wrapped_token,
+        BigInt::from_unsigned_bytes_be(&packed[16..]),
+        BigInt::from_unsigned_bytes_be(&packed[..16]),
+    ))
+}
+
+/// Decodes the aggregate swap fee percentage of a pool, the share of its swap fees the vault
+/// charges as protocol and pool creator fees.
+///
+/// The vault emits `AggregateSwapFeePercentageChanged` on updates. The initial percentage is set
+/// on registration, where only the protocol fee controller called by the vault emits it through
+/// `InitialPoolAggregateSwapFeePercentage`.
+pub fn aggregate_swap_fee_percentage(
+    log: &Log,
+    call: &Call,
+    vault: &[u8],
+) -> Option<(Vec<u8>, BigInt)> {
+    if log.address == vault {
+        AggregateSwapFeePercentageChanged::match_and_decode(log)
+            .map(|ev| (ev.pool, ev.aggregate_swap_fee_percentage))
+    } else if call.caller == vault {
+        InitialPoolAggregateSwapFeePercentage::match_and_decode(log)
+            .map(|ev| (ev.pool, ev.aggregate_swap_fee_percentage))
+    } else {
+        None
+    }
+}
+
+/// Returns the aggregate fee the vault deducts from the pool balance for `swap_fee_amount`,
+/// rounded down like the vault does. Pools in recovery mode are not charged.
+pub fn aggregate_fee_amount(
+    swap_fee_amount: &BigInt,
+    aggregate_swap_fee_percentage: &BigInt,
+    recovery_mode: bool,
+) -> BigInt {
+    if recovery_mode {
+        return BigInt::zero();
+    }
+    swap_fee_amount.clone() * aggregate_swap_fee_percentage.clone() / BigInt::from(10u64.pow(18))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethabi::{encode, long_signature, ParamType, Token};
+
+    const VAULT: [u8; 20] = [0xba; 20];
+    const POOL: [u8; 20] = [0x01; 20];
+    const WRAPPED: [u8; 20] = [0x0b; 20];
+
+    fn topic(address: [u8; 20]) -> Vec<u8> {
+        [[0u8; 12].as_slice(), address.as_slice()].concat()
+    }
+
+    fn pool_log(name: &str, params: &[ParamType], data: &[Token]) -> Log {
+        let params = [&[ParamType::Address], params].concat();
+        Log {
+            address: VAULT.to_vec(),
+            topics: vec![long_signature(name, &params).0.to_vec(), topic(POOL)],
+            data: encode(data),
+            ..Default::default()
+        }
+    }
+
+    fn buffer_log(name: &str, packed: [u8; 32]) -> Log {
+        let params = [
+            ParamType::Address,
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+            ParamType::FixedBytes(32),
+        ];
+        Log {
+            address: VAULT.to_vec(),
+            topics: vec![long_signature(name, &params).0.to_vec(), topic(WRAPPED)],
+            data: encode(&[
+                Token::Uint(1.into()),
+                Token::Uint(2.into()),
+                Token::FixedBytes(packed.to_vec()),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pool_attributes() {
+        let log = pool_log(
+            "SwapFeePercentageChanged",
+            &[ParamType::Uint(256)],
+            &[Token::Uint(3_000_000_000_000_000u64.into())],
+        );
+        assert_eq!(
+            pool_attributes(&log),
+            Some((
+                POOL.to_vec(),
+                vec![("fee", BigInt::from(3_000_000_000_000_000u64).to_signed_bytes_be())]
+            ))
+        );
+
+        let log = pool_log("PoolPausedStateChanged", &[ParamType::Bool], &[Token::Bool(true)]);
+        assert_eq!(pool_attributes(&log), Some((POOL.to_vec(), vec![("paused", vec![1])])));
+
+        let log =
+            pool_log("PoolRecoveryModeStateChanged", &[ParamType::Bool], &[Token::Bool(false)]);
+        assert_eq!(pool_attributes(&log), Some((POOL.to_vec(), vec![("recovery_mode", vec![0])])));
+    }
+
+    #[test]
+    fn test_buffer_balances() {
+        // Underlying balance in the lower, wrapped balance in the upper 128 bits.
+        let mut packed = [0u8; 32];
+        packed[..16].copy_from_slice(&u128::MAX.to_be_bytes());
+        packed[16..].copy_from_slice(&1_000u128.to_be_bytes());
+
+        for name in ["Wrap", "Unwrap", "LiquidityAddedToBuffer", "LiquidityRemovedFromBuffer"] {
+            assert_eq!(
+                buffer_balances(&buffer_log(name, packed)),
+                Some((
+                    WRAPPED.to_vec(),
+                    BigInt::from(1_000),
+                    BigInt::from_unsigned_bytes_be(&u128::MAX.to_be_bytes())
+                )),
+                "{name}"
+            );
+        }
+        assert_eq!(buffer_balances(&buffer_log("Other", packed)), None);
+    }
+
+    #[test]
+    fn test_aggregate_swap_fee_percentage() {
+        let percentage = || Token::Uint(500_000_000_000_000_000u64.into());
+        let changed =
+            pool_log("AggregateSwapFeePercentageChanged", &[ParamType::Uint(256)], &[percentage()]);
+        let initial = Log {
+            address: [0xfc; 20].to_vec(),
+            ..pool_log(
+                "InitialPoolAggregateSwapFeePercentage",
+                &[ParamType::Uint(256), ParamType::Bool],
+                &[percentage(), Token::Bool(false)],
+            )
+        };
+        let expected = Some((POOL.to_vec(), BigInt::from(500_000_000_000_000_000u64)));
+
+        assert_eq!(aggregate_swap_fee_percentage(&changed, &Call::default(), &VAULT), expected);
+        let called_by_vault = Call { caller: VAULT.to_vec(), ..Default::default() };
+        assert_eq!(aggregate_swap_fee_percentage(&initial, &called_by_vault, &VAULT), expected);
+        // The controller event is only trusted when the vault called the emitting contract.
+        assert_eq!(aggregate_swap_fee_percentage(&initial, &Call::default(), &VAULT), None);
+    }
+
+    #[test]
+    fn test_aggregate_fee_amount() {
+        let half = BigInt::from(500_000_000_000_000_000u64);
+
+        assert_eq!(aggregate_fee_amount(&BigInt::from(1_001), &half, false), BigInt::from(500));
+        assert_eq!(aggregate_fee_amount(&BigInt::from(1_001), &half, true), BigInt::zero());
+    }
+}
@@ -19,6 +19,7 @@ use crate::{
     pools::emit_specific_pools,
 };
 use tycho_substreams::{
+    aggregation::AggregationMode,
     balances::{extract_balance_deltas_from_tx, store_balance_changes},
     contract::extract_contract_changes,
     prelude::*,
@@ -177,7 +178,7 @@ pub fn map_relative_balances(
 
             // Keep it consistent with how it's inserted in the store. This step is important
             // because we use a zip on the store deltas and balance deltas later.
-            deltas.sort_unstable_by(|a, b| a.ord.cmp(&b.ord));
+            deltas.sort_unstable_by_key(|d| d.ord);
 
             deltas
         },
@@ -199,6 +200,7 @@ pub fn store_balances(deltas: BlockBalanceDeltas, store: StoreAddBigInt) {
 /// `BlockContractChanges` is ordered by transactions properly.
 #[substreams::handlers::map]
 pub fn map_protocol_changes(
+    params: String,
     block: eth::v2::Block,
     grouped_components: BlockChanges,
     deltas: BlockBalanceDeltas,
@@ -324,7 +326,7 @@ pub fn map_protocol_changes(
 
     // Process all `transaction_changes` for final output in the `BlockContractChanges`,
     //  sorted by transaction index (the key).
-    Ok(BlockChanges {
+    Ok(AggregationMode::from_params(&params)?.apply(BlockChanges {
         block: Some(Block {
             number: block.number,
             hash: block.hash.clone(),
@@ -350,7 +352,7 @@ pub fn map_protocol_changes(
                 }
             })
             .collect::<Vec<_>>(),
-    })
+    }))
 }
 
 fn replace_eth_address(token: &mut Vec<u8>) {
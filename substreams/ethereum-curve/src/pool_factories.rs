@@ -708,12 +708,8 @@ pub fn address_map(
                         asset_type: BigInt::from(0),
                         implementation_idx: BigInt::from(0),
                     }
-                } else if let Some(pool) =
-                    abi::stableswap_factory::functions::DeployPlainPool3::match_and_decode(call)
-                {
-                    pool
                 } else {
-                    return None;
+                    abi::stableswap_factory::functions::DeployPlainPool3::match_and_decode(call)?
                 };
                 let component_id = &call.return_data[12..];
 
@@ -1001,3 +997,88 @@ fn extract_proxy_impl(call: &Call, tx: &TransactionTrace, index: usize) -> Optio
         .first()?;
     Some(extract_eip1167_target_from_code(&code_change.new_code))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethabi::{ethereum_types::Address, Token};
+    use substreams::hex;
+
+    const POOL: [u8; 20] = [0xaa; 20];
+    const DAI: [u8; 20] = [0x01; 20];
+    const USDC: [u8; 20] = [0x02; 20];
+
+    fn coins() -> [Vec<u8>; 4] {
+        [DAI.to_vec(), USDC.to_vec(), vec![0; 20], vec![0; 20]]
+    }
+
+    fn plain_pool_deployed() -> Log {
+        Log {
+            address: STABLESWAP_FACTORY.to_vec(),
+            topics: vec![
+                hex!("b8f6972d6e56d21c47621efd7f02fe68f07a17c999c42245b3abd300f34d61eb").to_vec()
+            ],
+            data: ethabi::encode(&[
+                Token::FixedArray(
+                    coins()
+                        .iter()
+                        .map(|coin| Token::Address(Address::from_slice(coin)))
+                        .collect(),
+                ),
+                Token::Uint(100.into()),
+                Token::Uint(4_000_000.into()),
+                Token::Address(Address::repeat_byte(0xde)),
+                Token::Address(Address::from_slice(&POOL)),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    fn deploy_call(input: Vec<u8>) -> Call {
+        let mut return_data = vec![0u8; 12];
+        return_data.extend_from_slice(&POOL);
+        Call { address: STABLESWAP_FACTORY.to_vec(), input, return_data, ..Default::default() }
+    }
+
+    #[test]
+    fn test_stableswap_factory_plain_pool() {
+        let call = deploy_call(
+            abi::stableswap_factory::functions::DeployPlainPool3 {
+                name: "DAI/USDC".into(),
+                symbol: "DAIUSDC".into(),
+                coins: coins(),
+                a: BigInt::from(100),
+                fee: BigInt::from(4_000_000),
+                asset_type: BigInt::from(0),
+                implementation_idx: BigInt::from(1),
+            }
+            .encode(),
+        );
+
+        let (component, _) = address_map(
+            &STABLESWAP_FACTORY,
+            &plain_pool_deployed(),
+            &call,
+            &TransactionTrace::default(),
+        )
+        .unwrap();
+
+        assert_eq!(component.id, hex::encode(POOL));
+        assert_eq!(component.tokens, vec![DAI.to_vec(), USDC.to_vec()]);
+        assert_eq!(component.get_attribute_value("name"), Some(b"DAI/USDC".to_vec()));
+    }
+
+    #[test]
+    fn test_stableswap_factory_requires_deploy_call() {
+        let call = deploy_call(vec![0xde, 0xad, 0xbe, 0xef]);
+
+        let res = address_map(
+            &STABLESWAP_FACTORY,
+            &plain_pool_deployed(),
+            &call,
+            &TransactionTrace::default(),
+        );
+
+        assert_eq!(res, None);
+    }
+}
@@ -12,7 +12,8 @@ use substreams_ethereum::{
     Event,
 };
 use tycho_substreams::{
-    balances::aggregate_balances_changes, contract::extract_contract_changes, prelude::*,
+    aggregation::AggregationMode, balances::aggregate_balances_changes,
+    contract::extract_contract_changes, prelude::*,
 };
 
 #[substreams::handlers::map]
@@ -191,6 +192,7 @@ pub fn store_balances(deltas: BlockBalanceDeltas, store: StoreAddBigInt) {
 
 #[substreams::handlers::map]
 pub fn map_protocol_changes(
+    params: String,
     block: eth::v2::Block,
     grouped_components: BlockTransactionProtocolComponents,
     deltas: BlockBalanceDeltas,
@@ -237,7 +239,7 @@ pub fn map_protocol_changes(
         &mut transaction_contract,
     );
 
-    Ok(BlockChanges {
+    Ok(AggregationMode::from_params(&params)?.apply(BlockChanges {
         block: Some((&block).into()),
         changes: transaction_contract
             .drain()
@@ -253,7 +255,7 @@ pub fn map_protocol_changes(
                 }
             })
             .collect::<Vec<_>>(),
-    })
+    }))
 }
 
 fn is_deployment_tx(tx: &eth::v2::TransactionTrace, vault_address: &[u8]) -> bool {
@@ -1,3 +1,3 @@
 mod abi;
 mod modules;
-mod pb;
+pub mod pb;
@@ -15,7 +15,8 @@ use substreams::{
 };
 use substreams_ethereum::{pb::eth, Event};
 use tycho_substreams::{
-    balances::aggregate_balances_changes, contract::extract_contract_changes_builder, prelude::*,
+    aggregation::AggregationMode, balances::aggregate_balances_changes,
+    contract::extract_contract_changes_builder, prelude::*,
 };
 
 #[substreams::handlers::map]
@@ -264,6 +265,7 @@ pub fn store_balances(deltas: BlockBalanceDeltas, store: StoreAddBigInt) {
 /// `BlockChanges`  is ordered by transactions properly.
 #[substreams::handlers::map]
 pub fn map_protocol_changes(
+    params: String,
     block: eth::v2::Block,
     grouped_components: BlockTransactionProtocolComponents,
     deltas: BlockBalanceDeltas,
@@ -341,7 +343,7 @@ pub fn map_protocol_changes(
         substreams::log::info!("🚨 Balance changes {:?}", change.balance_changes);
         substreams::log::info!("🚨 Component changes {:?}", change.component_changes);
     }
-    Ok(block_changes)
+    Ok(AggregationMode::from_params(&params)?.apply(block_changes))
 }
 
 fn is_deployment_tx(tx: &eth::v2::TransactionTrace, vault_address: &[u8]) -> bool {
@@ -1,9 +1,7 @@
-use substreams_ethereum::pb::eth;
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
+mod abi;
 mod modules;
+mod pool_factories;
 
-#[substreams::handlers::map]
-fn map_changes(
-    block: eth::v2::Block,
-) -> Result<tycho::BlockContractChanges, substreams::errors::Error> {
-    todo!("Not implemented")
-}
+pub use modules::*;
@@ -1,12 +1,250 @@
+//! Template for protocols with contract factories.
+//!
+//! This template implements the recommended pipeline for a protocol where each component (e.g. a
+//! pool) is deployed by a factory to its own contract, which also escrows the component's ERC-20
+//! token balances:
+//!
+//! 1. `map_protocol_components` discovers new components from factory logs.
+//! 2. `store_protocol_components` remembers all components.
+//! 3. `map_relative_component_balance` extracts relative balance changes from token transfers.
+//! 4. `store_balances` turns relative changes into absolute balances.
+//! 5. `map_protocol_changes` combines components, balances, contract and entity changes into the
+//!    final `BlockChanges` output.
+//!
+//! The first two modules are generated from the factories declared in `pool_factories::factories`.
+//! Adapt these to your protocol's factories and adjust the balance and contract predicates if your
+//! components don't follow the one contract per component design.
 use std::collections::HashMap;
+
+use anyhow::Result;
+use itertools::Itertools;
+use substreams::{
+    pb::substreams::StoreDeltas,
+    store::{StoreAddBigInt, StoreGet, StoreGetProto},
+};
 use substreams_ethereum::pb::eth;
-use tycho_substreams::prelude::*;
+use tycho_substreams::{
+    aggregation::AggregationMode,
+    balances::{aggregate_balances_changes, extract_balance_deltas_from_tx},
+    contract::extract_contract_changes_builder,
+    factories::component_key,
+    prelude::*,
+};
+
+use crate::pool_factories;
+
+tycho_substreams::component_factory_handlers! {
+    map: map_protocol_components(params),
+    store: store_protocol_components,
+    factories: pool_factories::factories,
+}
+
+/// Extracts relative balance changes of all components from ERC-20 transfers.
+#[substreams::handlers::map]
+pub fn map_relative_component_balance(
+    block: eth::v2::Block,
+    store: StoreGetProto<ProtocolComponent>,
+) -> Result<BlockBalanceDeltas> {
+    let balance_deltas = block
+        .transactions()
+        .flat_map(|tx| {
+            component_balance_deltas(tx, |token, component_id| {
+                store
+                    .get_last(component_key(component_id))
+                    .is_some_and(|component| is_component_token(&component, token))
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(BlockBalanceDeltas { balance_deltas })
+}
+
+/// Extracts balance deltas of a transaction and assigns them to `0x` prefixed component ids.
+///
+/// `is_tracked` is called with the token address and the component id, only deltas for which it
+/// returns `true` are kept.
+fn component_balance_deltas<F: Fn(&[u8], &str) -> bool>(
+    tx: &eth::v2::TransactionTrace,
+    is_tracked: F,
+) -> Vec<BalanceDelta> {
+    extract_balance_deltas_from_tx(tx, |token, transactor| {
+        is_tracked(token, &format!("0x{}", hex::encode(transactor)))
+    })
+    .into_iter()
+    .map(|mut delta| {
+        delta.component_id = [b"0x".as_slice(), &delta.component_id].concat();
+        delta
+    })
+    .collect()
+}
+
+/// Checks whether `token` is one of the component's tokens.
+fn is_component_token(component: &ProtocolComponent, token: &[u8]) -> bool {
+    component
+        .tokens
+        .iter()
+        .any(|t| t == token)
+}
+
+/// Aggregates relative balance changes into absolute balances.
+///
+/// Balances are keyed by component and token, so each token of a component is tallied up
+/// separately.
+#[substreams::handlers::store]
+pub fn store_balances(deltas: BlockBalanceDeltas, store: StoreAddBigInt) {
+    tycho_substreams::balances::store_balance_changes(deltas, store);
+}
 
+/// Combines all changes of this block into `BlockChanges`.
+///
+/// Changes are grouped by transaction index using `TransactionChangesBuilder`s, which guarantee
+/// uniqueness of contracts, entities and balances per transaction. The builders are sorted by
+/// transaction index at the end to emit the changes in the correct order.
 #[substreams::handlers::map]
-fn map_protocol_changes(
+pub fn map_protocol_changes(
+    params: String,
     block: eth::v2::Block,
-) -> Result<BlockContractChanges, substreams::errors::Error> {
-    let mut transaction_contract_changes = Vec::<TransactionContractChanges>::new();
-    // TODO: protocol specific logic goes here
-    Ok(BlockContractChanges { block: Some((&block).into()), changes: transaction_contract_changes })
+    new_components: BlockTransactionProtocolComponents,
+    deltas: BlockBalanceDeltas,
+    components_store: StoreGetProto<ProtocolComponent>,
+    balance_store: StoreDeltas, // Note, this map module is using the `deltas` mode for the store.
+) -> Result<BlockChanges> {
+    let mut transaction_changes: HashMap<_, TransactionChangesBuilder> = HashMap::new();
+
+    // Add all components created in this block.
+    new_components
+        .tx_components
+        .iter()
+        .for_each(|tx_component| {
+            let tx = tx_component.tx.as_ref().unwrap();
+            let builder = transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(tx));
+            tx_component
+                .components
+                .iter()
+                .for_each(|component| builder.add_protocol_component(component));
+        });
+
+    // Add absolute balances, the store deltas tell us the new balance after each relative change.
+    aggregate_balances_changes(balance_store, deltas)
+        .into_iter()
+        .for_each(|(_, (tx, balances))| {
+            let builder = transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(&tx));
+            balances
+                .values()
+                .for_each(|token_bc_map| {
+                    token_bc_map
+                        .values()
+                        .for_each(|bc| builder.add_balance_change(bc))
+                });
+        });
+
+    // Add storage, balance and code changes of all component contracts.
+    extract_contract_changes_builder(
+        &block,
+        |addr| {
+            components_store
+                .get_last(component_key(&format!("0x{}", hex::encode(addr))))
+                .is_some()
+        },
+        &mut transaction_changes,
+    );
+
+    // Mark components with changed contracts as updated, this emits an entity change so
+    // consumers know which components need to be re-simulated.
+    transaction_changes
+        .values_mut()
+        .for_each(|builder| {
+            let component_ids = builder
+                .changed_contracts()
+                .map(|addr| format!("0x{}", hex::encode(addr)))
+                .collect::<Vec<_>>();
+            component_ids
+                .iter()
+                .for_each(|id| builder.mark_component_as_updated(id));
+        });
+
+    Ok(AggregationMode::from_params(&params)?.apply(BlockChanges {
+        block: Some((&block).into()),
+        changes: transaction_changes
+            .drain()
+            .sorted_unstable_by_key(|(index, _)| *index)
+            .filter_map(|(_, builder)| builder.build())
+            .collect::<Vec<_>>(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethabi::{ethereum_types::Address, Token};
+    use hex_literal::hex;
+    use substreams::scalar::BigInt;
+    use substreams_ethereum::pb::eth::v2::{Call, Log, TransactionTrace};
+
+    const POOL: [u8; 20] = hex!("00000000000000000000000000000000000000aa");
+    const TOKEN: [u8; 20] = hex!("00000000000000000000000000000000000000b0");
+    const USER: [u8; 20] = hex!("00000000000000000000000000000000000000cc");
+    // keccak256("Transfer(address,address,uint256)")
+    const TRANSFER_TOPIC: [u8; 32] =
+        hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+    fn transfer_log(token: &[u8], from: &[u8], to: &[u8], value: u64, ordinal: u64) -> Log {
+        Log {
+            address: token.to_vec(),
+            topics: vec![
+                TRANSFER_TOPIC.to_vec(),
+                ethabi::encode(&[Token::Address(Address::from_slice(from))]),
+                ethabi::encode(&[Token::Address(Address::from_slice(to))]),
+            ],
+            data: ethabi::encode(&[Token::Uint(value.into())]),
+            ordinal,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_is_component_token() {
+        let component = ProtocolComponent::at_contract(&POOL, &Transaction::default())
+            .with_tokens(&[TOKEN, USER]);
+
+        assert!(is_component_token(&component, &TOKEN));
+        assert!(!is_component_token(&component, &POOL));
+    }
+
+    #[test]
+    fn test_component_balance_deltas() {
+        let tx = TransactionTrace {
+            hash: vec![1],
+            index: 2,
+            calls: vec![Call {
+                logs: vec![
+                    transfer_log(&TOKEN, &USER, &POOL, 100, 1),
+                    transfer_log(&TOKEN, &POOL, &USER, 40, 2),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let pool_id = format!("0x{}", hex::encode(POOL));
+
+        let res = component_balance_deltas(&tx, |token, component_id| {
+            token == TOKEN && component_id == pool_id
+        });
+
+        let exp = [(1, BigInt::from(100)), (2, BigInt::from(-40))]
+            .into_iter()
+            .map(|(ord, delta)| BalanceDelta {
+                ord,
+                tx: Some((&tx).into()),
+                token: TOKEN.to_vec(),
+                delta: delta.to_signed_bytes_be(),
+                component_id: pool_id.as_bytes().to_vec(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(res, exp);
+    }
 }
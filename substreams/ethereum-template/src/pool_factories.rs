@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use tycho_substreams::{
+    factories::{on_event, FactoryRegistry},
+    prelude::*,
+};
+
+use crate::abi::factory::events::PoolCreated;
+
+/// Params of `map_protocol_components`, encoded as a query string.
+#[derive(Debug, Deserialize, PartialEq)]
+struct Params {
+    /// Hex encoded address of the factory deploying the components.
+    factory_address: String,
+    /// Protocol type name assigned to all discovered components.
+    protocol_type_name: String,
+}
+
+impl Params {
+    fn parse(input: &str) -> Result<Self> {
+        serde_qs::from_str(input).context("Unable to deserialize params")
+    }
+
+    fn factory_address(&self) -> Result<Vec<u8>> {
+        hex::decode(
+            self.factory_address
+                .trim_start_matches("0x"),
+        )
+        .context("Invalid factory address")
+    }
+}
+
+/// Builds the factories of the protocol from the module params.
+///
+/// Each component is expected to be deployed to its own contract, which also escrows the
+/// component's token balances. The component id is therefore the hex-encoded pool address.
+///
+/// Replace the `PoolCreated` event with your protocol's factory event, register additional
+/// factories (or factory versions) and attach any static attributes the component needs.
+pub fn factories(params: &str) -> Result<FactoryRegistry> {
+    let params = Params::parse(params)?;
+    let protocol_type_name = params.protocol_type_name.clone();
+
+    Ok(FactoryRegistry::new().with_factory(
+        &params.factory_address()?,
+        on_event(move |event: PoolCreated, ctx| {
+            Some(
+                ProtocolComponent::at_contract(&event.pool, &ctx.tx.into())
+                    .with_tokens(&event.tokens)
+                    .with_attributes(&[("factory", ctx.factory_address)])
+                    .as_swap_type(&protocol_type_name, ImplementationType::Vm),
+            )
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethabi::{ethereum_types::Address, Token};
+    use hex_literal::hex;
+    use substreams_ethereum::pb::eth::v2::{Call, Log, TransactionTrace};
+
+    const FACTORY: [u8; 20] = hex!("0000000000000000000000000000000000000fac");
+    const POOL: [u8; 20] = hex!("00000000000000000000000000000000000000aa");
+    const TOKEN_0: [u8; 20] = hex!("00000000000000000000000000000000000000b0");
+    const TOKEN_1: [u8; 20] = hex!("00000000000000000000000000000000000000b1");
+    // keccak256("PoolCreated(address,address[])")
+    const POOL_CREATED_TOPIC: [u8; 32] =
+        hex!("0a4af51f99a910b453b81f2f9f5673acd70bf8895729e0a6de8e5e0908d89f2b");
+    const PARAMS: &str =
+        "factory_address=0x0000000000000000000000000000000000000fac&protocol_type_name=template_pool";
+
+    fn pool_created_log(emitter: &[u8]) -> Log {
+        Log {
+            address: emitter.to_vec(),
+            topics: vec![
+                POOL_CREATED_TOPIC.to_vec(),
+                ethabi::encode(&[Token::Address(Address::from(POOL))]),
+            ],
+            data: ethabi::encode(&[Token::Array(vec![
+                Token::Address(Address::from(TOKEN_0)),
+                Token::Address(Address::from(TOKEN_1)),
+            ])]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_params() {
+        let params = Params::parse(PARAMS).unwrap();
+
+        assert_eq!(params.protocol_type_name, "template_pool");
+        assert_eq!(params.factory_address().unwrap(), FACTORY.to_vec());
+    }
+
+    #[test]
+    fn test_create_component() {
+        let tx = TransactionTrace { hash: vec![1], index: 3, ..Default::default() };
+
+        let res = factories(PARAMS)
+            .unwrap()
+            .create_component(&pool_created_log(&FACTORY), &Call::default(), &tx);
+
+        let exp = ProtocolComponent::at_contract(&POOL, &(&tx).into())
+            .with_tokens(&[TOKEN_0, TOKEN_1])
+            .with_attributes(&[("factory", FACTORY)])
+            .as_swap_type("template_pool", ImplementationType::Vm);
+        assert_eq!(res, Some(exp));
+    }
+
+    #[test]
+    fn test_create_component_ignores_other_emitters() {
+        let other = hex!("0000000000000000000000000000000000000bad");
+
+        let res = factories(PARAMS)
+            .unwrap()
+            .create_component(
+                &pool_created_log(&other),
+                &Call::default(),
+                &TransactionTrace::default(),
+            );
+
+        assert_eq!(res, None);
+    }
+}
@@ -2,7 +2,8 @@
 
 mod abi;
 mod modules;
-mod pb;
+mod params;
+pub mod pb;
 
 pub use modules::*;
 
@@ -1,105 +1,418 @@
-use std::str::FromStr;
-
-use ethabi::ethereum_types::Address;
-use serde::Deserialize;
-use substreams::prelude::BigInt;
+use substreams::{
+    prelude::BigInt,
+    store::{StoreGet, StoreGetRaw, StoreGetString},
+};
 use substreams_ethereum::pb::eth::v2::{self as eth};
-use substreams_helper::{event_handler::EventHandler, hex::Hexable};
-
-use crate::abi::factory::events::PairCreated;
+use substreams_helper::hex::Hexable;
 
-use tycho_substreams::prelude::*;
+use crate::{
+    abi::factory::{events::PairCreated, functions::SetFeeTo},
+    params::{FactoryConfig, Implementation, Params},
+    store_key::StoreKey,
+};
 
-#[derive(Debug, Deserialize)]
-struct Params {
-    factory_address: String,
-    protocol_type_name: String,
-}
+use tycho_substreams::{
+    factories::{on_event, FactoryContext, FactoryRegistry},
+    prelude::*,
+};
 
 #[substreams::handlers::map]
 pub fn map_pools_created(
     params: String,
     block: eth::Block,
+    fee_to_store: StoreGetRaw,
+    factories_store: StoreGetString,
 ) -> Result<BlockChanges, substreams::errors::Error> {
     let mut new_pools: Vec<TransactionChanges> = vec![];
 
-    let params: Params = serde_qs::from_str(params.as_str()).expect("Unable to deserialize params");
+    let factories = Params::parse(&params)?;
 
-    get_pools(&block, &mut new_pools, &params);
+    get_factories(&block, &mut new_pools, &factories, &fee_to_store, &factories_store);
+    get_pools(&block, &mut new_pools, &factories);
 
     let tycho_block: Block = (&block).into();
 
     Ok(BlockChanges { block: Some(tycho_block), changes: new_pools })
 }
 
-fn get_pools(block: &eth::Block, new_pools: &mut Vec<TransactionChanges>, params: &Params) {
-    // Extract new pools from PairCreated events
-    let mut on_pair_created = |event: PairCreated, _tx: &eth::TransactionTrace, _log: &eth::Log| {
-        let tycho_tx: Transaction = _tx.into();
+fn get_pools(
+    block: &eth::Block,
+    new_pools: &mut Vec<TransactionChanges>,
+    factories: &[FactoryConfig],
+) {
+    let registry = pair_factories(block.number, factories);
+
+    new_pools.extend(
+        registry
+            .map_components(block)
+            .tx_components
+            .into_iter()
+            .flat_map(|tx_components| tx_components.components)
+            .map(|component| TransactionChanges {
+                tx: component.tx.clone(),
+                contract_changes: vec![],
+                entity_changes: vec![EntityChanges {
+                    component_id: component.id.clone(),
+                    attributes: [
+                        "reserve0",
+                        "reserve1",
+                        "block_timestamp_last",
+                        "price0_cumulative_last",
+                        "price1_cumulative_last",
+                        "k_last",
+                    ]
+                    .into_iter()
+                    .map(|name| Attribute {
+                        name: name.to_string(),
+                        value: BigInt::from(0).to_signed_bytes_be(),
+                        change: ChangeType::Creation.into(),
+                    })
+                    .collect(),
+                }],
+                balance_changes: component
+                    .tokens
+                    .iter()
+                    .map(|token| BalanceChange {
+                        token: token.clone(),
+                        balance: BigInt::from(0).to_signed_bytes_be(),
+                        component_id: component.id.as_bytes().to_vec(),
+                    })
+                    .collect(),
+                component_changes: vec![component],
+            }),
+    );
+}
+
+/// Registers a `PairCreated` handler for each configured factory, ignoring pairs created before
+/// the factory's start block.
+fn pair_factories(block_number: u64, factories: &[FactoryConfig]) -> FactoryRegistry {
+    factories
+        .iter()
+        .fold(FactoryRegistry::new(), |registry, factory| {
+            let address = factory
+                .address_bytes()
+                .expect("Factory addresses are validated when parsing");
+            let factory = factory.clone();
+            registry.with_factory(
+                &address,
+                on_event(move |event: PairCreated, ctx| {
+                    (block_number >= factory.start_block)
+                        .then(|| pair_component(&event, &factory, ctx))
+                }),
+            )
+        })
+}
+
+fn pair_component(
+    event: &PairCreated,
+    factory: &FactoryConfig,
+    ctx: &FactoryContext,
+) -> ProtocolComponent {
+    ProtocolComponent {
+        id: event.pair.to_hex(),
+        tokens: vec![event.token0.clone(), event.token1.clone()],
+        contracts: match factory.implementation {
+            Implementation::Custom => vec![],
+            Implementation::Vm => vec![event.pair.clone()],
+        },
+        static_att: vec![
+            // Initial trading fee, forks with dynamic fees additionally emit the current fee as
+            // state attribute, see `map_pool_events`.
+            Attribute {
+                name: "fee".to_string(),
+                value: BigInt::from(factory.fee).to_signed_bytes_be(),
+                change: ChangeType::Creation.into(),
+            },
+            Attribute {
+                name: "fee_denominator".to_string(),
+                value: BigInt::from(factory.fee_denominator).to_signed_bytes_be(),
+                change: ChangeType::Creation.into(),
+            },
+            Attribute {
+                name: "pool_address".to_string(),
+                value: event.pair.clone(),
+                change: ChangeType::Creation.into(),
+            },
+            // Used to match fee updates to the factory they are configured for and to look up the
+            // factory wide state, e.g. `fee_to`, on the factory component.
+            Attribute {
+                name: "factory".to_string(),
+                value: ctx.factory_address.to_vec(),
+                change: ChangeType::Creation.into(),
+            },
+        ],
+        change: i32::from(ChangeType::Creation),
+        protocol_type: Some(ProtocolType {
+            name: factory.protocol_type_name.to_string(),
+            financial_type: FinancialType::Swap.into(),
+            attribute_schema: vec![],
+            implementation_type: ImplementationType::from(factory.implementation).into(),
+        }),
+        tx: Some(ctx.tx.into()),
+    }
+}
+
+/// Registers the configured factories as components without tokens, created along with the first
+/// factory event seen, see `store_factories`.
+///
+/// Pairs mint protocol fees on liquidity changes whenever their factory's `feeTo` is set. Since
+/// this is a factory wide setting, it is emitted as `fee_to` attribute of the factory component,
+/// which pairs reference through their `factory` attribute. An unset `feeTo` is emitted as the zero
+/// address, later changes are emitted by `map_pool_events`.
+fn get_factories(
+    block: &eth::Block,
+    new_pools: &mut Vec<TransactionChanges>,
+    factories: &[FactoryConfig],
+    fee_to_store: &StoreGetRaw,
+    factories_store: &StoreGetString,
+) {
+    for factory in factories {
+        let address = factory
+            .address_bytes()
+            .expect("Factory addresses are validated when parsing");
+        let factory_id = address.to_hex();
+        let Some(first_event) =
+            factories_store.get_last(StoreKey::Factory.get_unique_pool_key(&factory_id))
+        else {
+            continue;
+        };
+        let Some((tx, ordinal)) = factory_events(block, factory, &address)
+            .into_iter()
+            .find(|(_, ordinal)| factory_event_id(block.number, *ordinal) == first_event)
+        else {
+            continue;
+        };
+        let tycho_tx: Transaction = tx.into();
+        let fee_to = fee_to_store
+            .get_at(ordinal, StoreKey::FeeTo.get_unique_pool_key(&factory_id))
+            .unwrap_or_else(|| vec![0; 20]);
 
         new_pools.push(TransactionChanges {
             tx: Some(tycho_tx.clone()),
             contract_changes: vec![],
             entity_changes: vec![EntityChanges {
-                component_id: event.pair.to_hex(),
-                attributes: vec![
-                    Attribute {
-                        name: "reserve0".to_string(),
-                        value: BigInt::from(0).to_signed_bytes_be(),
-                        change: ChangeType::Creation.into(),
-                    },
-                    Attribute {
-                        name: "reserve1".to_string(),
-                        value: BigInt::from(0).to_signed_bytes_be(),
-                        change: ChangeType::Creation.into(),
-                    },
-                ],
+                component_id: factory_id.clone(),
+                attributes: vec![Attribute {
+                    name: "fee_to".to_string(),
+                    value: fee_to,
+                    change: ChangeType::Creation.into(),
+                }],
             }],
-            component_changes: vec![ProtocolComponent {
-                id: event.pair.to_hex(),
-                tokens: vec![event.token0.clone(), event.token1.clone()],
-                contracts: vec![],
-                static_att: vec![
-                    // Trading Fee is hardcoded to 0.3%, saved as int in bps (basis points)
-                    Attribute {
-                        name: "fee".to_string(),
-                        value: BigInt::from(30).to_signed_bytes_be(),
-                        change: ChangeType::Creation.into(),
-                    },
-                    Attribute {
-                        name: "pool_address".to_string(),
-                        value: event.pair.clone(),
-                        change: ChangeType::Creation.into(),
-                    },
-                ],
-                change: i32::from(ChangeType::Creation),
-                protocol_type: Some(ProtocolType {
-                    name: params.protocol_type_name.to_string(),
-                    financial_type: FinancialType::Swap.into(),
-                    attribute_schema: vec![],
-                    implementation_type: ImplementationType::Custom.into(),
-                }),
-                tx: Some(tycho_tx),
-            }],
-            balance_changes: vec![
-                BalanceChange {
-                    token: event.token0,
-                    balance: BigInt::from(0).to_signed_bytes_be(),
-                    component_id: event.pair.to_hex().as_bytes().to_vec(),
-                },
-                BalanceChange {
-                    token: event.token1,
-                    balance: BigInt::from(0).to_signed_bytes_be(),
-                    component_id: event.pair.to_hex().as_bytes().to_vec(),
-                },
-            ],
+            component_changes: vec![factory_component(&factory_id, factory, tycho_tx)],
+            balance_changes: vec![],
+        });
+    }
+}
+
+/// The events with which a factory is registered as component, as transaction and ordinal in
+/// ordinal order: its successful `setFeeTo` calls and its `PairCreated` logs from its start block
+/// on.
+pub(crate) fn factory_events<'a>(
+    block: &'a eth::Block,
+    factory: &FactoryConfig,
+    address: &[u8],
+) -> Vec<(&'a eth::TransactionTrace, u64)> {
+    let mut events = block
+        .transactions()
+        .flat_map(|tx| {
+            tx.calls
+                .iter()
+                .filter(|call| !call.state_reverted && call.address == address)
+                .map(move |call| (tx, call))
+        })
+        .flat_map(|(tx, call)| {
+            let set_fee_to = SetFeeTo::match_call(call).then_some((tx, call.begin_ordinal));
+            let pairs_created = call
+                .logs
+                .iter()
+                .filter(|log| block.number >= factory.start_block && PairCreated::match_log(log))
+                .map(move |log| (tx, log.ordinal));
+            set_fee_to
+                .into_iter()
+                .chain(pairs_created)
         })
-    };
+        .collect::<Vec<_>>();
+    events.sort_by_key(|(_, ordinal)| *ordinal);
+    events
+}
+
+/// Identifies a factory event across blocks, ordinals restart with every block.
+pub(crate) fn factory_event_id(block_number: u64, ordinal: u64) -> String {
+    format!("{block_number}:{ordinal}")
+}
+
+/// The factory component, e.g. of protocol type `uniswap_v2_factory` for `uniswap_v2_pool`s.
+fn factory_component(id: &str, factory: &FactoryConfig, tx: Transaction) -> ProtocolComponent {
+    ProtocolComponent {
+        id: id.to_string(),
+        tokens: vec![],
+        contracts: vec![],
+        static_att: vec![],
+        change: i32::from(ChangeType::Creation),
+        protocol_type: Some(ProtocolType {
+            name: format!(
+                "{}_factory",
+                factory
+                    .protocol_type_name
+                    .trim_end_matches("_pool")
+            ),
+            financial_type: FinancialType::Swap.into(),
+            attribute_schema: vec![],
+            implementation_type: ImplementationType::Custom.into(),
+        }),
+        tx: Some(tx),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethabi::{encode, long_signature, ParamType, Token};
+    use hex_literal::hex;
+
+    const FACTORY: [u8; 20] = hex!("5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f");
+
+    fn factory() -> FactoryConfig {
+        Params::parse(&format!(
+            "factory_address={}&protocol_type_name=uniswap_v2_pool",
+            hex::encode(FACTORY)
+        ))
+        .unwrap()
+        .remove(0)
+    }
+
+    fn pair_created_log(ordinal: u64) -> eth::Log {
+        eth::Log {
+            address: FACTORY.to_vec(),
+            topics: vec![
+                long_signature(
+                    "PairCreated",
+                    &[
+                        ParamType::Address,
+                        ParamType::Address,
+                        ParamType::Address,
+                        ParamType::Uint(256),
+                    ],
+                )
+                .0
+                .to_vec(),
+                vec![0x0a; 32],
+                vec![0x0b; 32],
+            ],
+            data: encode(&[Token::Address([0xaa; 20].into()), Token::Uint(1.into())]),
+            ordinal,
+            ..Default::default()
+        }
+    }
+
+    fn set_fee_to_call(begin_ordinal: u64, state_reverted: bool) -> eth::Call {
+        eth::Call {
+            address: FACTORY.to_vec(),
+            input: SetFeeTo { fee_to: vec![0xfe; 20] }.encode(),
+            begin_ordinal,
+            state_reverted,
+            ..Default::default()
+        }
+    }
+
+    fn block(number: u64, calls: Vec<eth::Call>) -> eth::Block {
+        eth::Block {
+            number,
+            transaction_traces: vec![eth::TransactionTrace {
+                status: eth::TransactionTraceStatus::Succeeded.into(),
+                calls,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_factory_events() {
+        let create_pair = eth::Call {
+            address: FACTORY.to_vec(),
+            logs: vec![pair_created_log(7)],
+            ..Default::default()
+        };
+        let block =
+            block(20, vec![set_fee_to_call(9, false), create_pair, set_fee_to_call(3, true)]);
+
+        let ordinals = factory_events(&block, &factory(), &FACTORY)
+            .into_iter()
+            .map(|(_, ordinal)| ordinal)
+            .collect::<Vec<_>>();
+
+        assert_eq!(ordinals, vec![7, 9]);
+    }
+
+    #[test]
+    fn test_factory_events_before_start_block() {
+        let create_pair = eth::Call {
+            address: FACTORY.to_vec(),
+            logs: vec![pair_created_log(7)],
+            ..Default::default()
+        };
+        let mut factory = factory();
+        factory.start_block = 21;
+
+        let block = block(20, vec![create_pair, set_fee_to_call(9, false)]);
+
+        let events = factory_events(&block, &factory, &FACTORY);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].1, 9);
+    }
+
+    #[test]
+    fn test_get_pools() {
+        let create_pair = eth::Call {
+            address: FACTORY.to_vec(),
+            logs: vec![pair_created_log(7)],
+            ..Default::default()
+        };
+        let mut new_pools = vec![];
+
+        get_pools(&block(20, vec![create_pair]), &mut new_pools, &[factory()]);
+
+        assert_eq!(new_pools.len(), 1);
+        let component = &new_pools[0].component_changes[0];
+        assert_eq!(component.id, format!("0x{}", "aa".repeat(20)));
+        assert_eq!(component.tokens, vec![vec![0x0a; 20], vec![0x0b; 20]]);
+        assert_eq!(new_pools[0].balance_changes.len(), 2);
+        assert_eq!(
+            new_pools[0].entity_changes[0]
+                .attributes
+                .len(),
+            6
+        );
+    }
+
+    #[test]
+    fn test_get_pools_before_start_block() {
+        let create_pair = eth::Call {
+            address: FACTORY.to_vec(),
+            logs: vec![pair_created_log(7)],
+            ..Default::default()
+        };
+        let mut factory = factory();
+        factory.start_block = 21;
+        let mut new_pools = vec![];
+
+        get_pools(&block(20, vec![create_pair]), &mut new_pools, &[factory]);
+
+        assert!(new_pools.is_empty());
+    }
 
-    let mut eh = EventHandler::new(block);
+    #[test]
+    fn test_factory_event_id_differs_across_blocks() {
+        assert_ne!(factory_event_id(20, 7), factory_event_id(21, 7));
+    }
 
-    eh.filter_by_address(vec![Address::from_str(&params.factory_address).unwrap()]);
+    #[test]
+    fn test_factory_component_protocol_type() {
+        let component = factory_component("0xfac", &factory(), Transaction::default());
 
-    eh.on::<PairCreated, _>(&mut on_pair_created);
-    eh.handle_events();
+        assert_eq!(component.protocol_type.unwrap().name, "uniswap_v2_factory");
+        assert!(component.tokens.is_empty());
+    }
 }
@@ -0,0 +1,30 @@
+use substreams::store::{StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsString};
+use substreams_ethereum::pb::eth::v2::{self as eth};
+
+use substreams_helper::hex::Hexable;
+
+use crate::{
+    modules::map_pool_created::{factory_event_id, factory_events},
+    params::Params,
+    store_key::StoreKey,
+};
+
+#[substreams::handlers::store]
+pub fn store_factories(params: String, block: eth::Block, store: StoreSetIfNotExistsString) {
+    // Store the block and ordinal of the first event of each configured factory, with which
+    // `map_pools_created` registers the factory as component.
+    let factories = Params::parse(&params).expect("Invalid params");
+
+    for factory in factories.iter() {
+        let address = factory
+            .address_bytes()
+            .expect("Factory addresses are validated when parsing");
+        if let Some((_, ordinal)) = factory_events(&block, factory, &address).first() {
+            store.set_if_not_exists(
+                *ordinal,
+                StoreKey::Factory.get_unique_pool_key(&address.to_hex()),
+                &factory_event_id(block.number, *ordinal),
+            );
+        }
+    }
+}
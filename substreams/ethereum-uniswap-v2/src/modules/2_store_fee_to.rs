@@ -0,0 +1,39 @@
+use substreams::store::{StoreNew, StoreSet, StoreSetRaw};
+use substreams_ethereum::{
+    pb::eth::v2::{self as eth},
+    Function,
+};
+
+use substreams_helper::hex::Hexable;
+
+use crate::{abi::factory::functions::SetFeeTo, params::Params, store_key::StoreKey};
+
+#[substreams::handlers::store]
+pub fn store_fee_to(params: String, block: eth::Block, store: StoreSetRaw) {
+    // Store the protocol fee recipient of each configured factory. Pairs mint protocol fees on
+    // liquidity changes whenever their factory's `feeTo` is set, see `map_pools_created` and
+    // `map_pool_events`.
+    let factories = Params::parse(&params)
+        .expect("Invalid params")
+        .iter()
+        .map(|factory| {
+            factory
+                .address_bytes()
+                .expect("Factory addresses are validated when parsing")
+        })
+        .collect::<Vec<_>>();
+
+    for call in block
+        .transactions()
+        .flat_map(|tx| tx.calls.iter())
+        .filter(|call| !call.state_reverted && factories.contains(&call.address))
+    {
+        if let Some(SetFeeTo { fee_to }) = SetFeeTo::match_and_decode(call) {
+            store.set(
+                call.begin_ordinal,
+                StoreKey::FeeTo.get_unique_pool_key(&call.address.to_hex()),
+                &fee_to,
+            );
+        }
+    }
+}
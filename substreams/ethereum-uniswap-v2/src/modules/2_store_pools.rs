@@ -11,7 +11,12 @@ pub fn store_pools(
     // Store pools. Required so the next steps can match any event to a known pool by their address
 
     for change in pools_created.changes {
-        for new_protocol_component in change.component_changes {
+        // Factories are components without tokens, they have no pool events to match.
+        for new_protocol_component in change
+            .component_changes
+            .into_iter()
+            .filter(|component| !component.tokens.is_empty())
+        {
             //  Use ordinal 0 because the address should be unique, so ordering doesn't matter.
             store.set_if_not_exists(
                 0,
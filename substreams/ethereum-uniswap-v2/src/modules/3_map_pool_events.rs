@@ -1,12 +1,26 @@
+use anyhow::Context;
 use itertools::Itertools;
-use std::collections::HashMap;
-use substreams::store::{StoreGet, StoreGetProto};
-use substreams_ethereum::pb::eth::v2::{self as eth};
+use std::collections::{HashMap, HashSet};
+use substreams::{
+    scalar::BigInt,
+    store::{StoreGet, StoreGetProto},
+};
+use substreams_ethereum::{
+    pb::eth::v2::{self as eth},
+    Function,
+};
 
 use substreams_helper::{event_handler::EventHandler, hex::Hexable};
 
-use crate::{abi::pool::events::Sync, store_key::StoreKey, traits::PoolAddresser};
-use tycho_substreams::prelude::*;
+use crate::{
+    abi::{factory::functions::SetFeeTo, pool::events::Sync},
+    params::{FeeUpdate, Params},
+    store_key::StoreKey,
+    traits::PoolAddresser,
+};
+use tycho_substreams::{
+    aggregation::AggregationMode, contract::extract_contract_changes_builder, prelude::*,
+};
 
 // Auxiliary struct to serve as a key for the HashMaps.
 #[derive(Clone, Hash, Eq, PartialEq)]
@@ -29,6 +43,14 @@ struct PartialChanges {
 }
 
 impl PartialChanges {
+    fn new(tx: &eth::TransactionTrace) -> Self {
+        PartialChanges {
+            transaction: tx.into(),
+            entity_changes: HashMap::new(),
+            balance_changes: HashMap::new(),
+        }
+    }
+
     // Consolidate the entity changes into a vector of EntityChanges. Initially, the entity changes
     // are in a map to prevent duplicates. For each transaction, we need to have only one final
     // state change, per state. Example:
@@ -48,6 +70,7 @@ impl PartialChanges {
 
 #[substreams::handlers::map]
 pub fn map_pool_events(
+    params: String,
     block: eth::Block,
     block_entity_changes: BlockChanges,
     pools_store: StoreGetProto<ProtocolComponent>,
@@ -57,10 +80,31 @@ pub fn map_pool_events(
     let mut block_entity_changes = block_entity_changes;
     let mut tx_changes: HashMap<Vec<u8>, PartialChanges> = HashMap::new();
 
+    let factories = Params::parse(&params)?;
+    let factory_addresses = factories
+        .iter()
+        .map(|factory| factory.address_bytes())
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let fee_updates = factories
+        .iter()
+        .filter_map(|factory| factory.fee_update().transpose())
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
     handle_sync(&block, &mut tx_changes, &pools_store);
+    handle_storage_changes(&block, &mut tx_changes, &pools_store);
+    handle_fee_updates(&block, &mut tx_changes, &pools_store, &fee_updates);
+    handle_set_fee_to(&block, &mut tx_changes, &factory_addresses);
     merge_block(&mut tx_changes, &mut block_entity_changes);
+    add_vm_contract_changes(&block, &mut block_entity_changes, |addr| {
+        pools_store
+            .get_last(StoreKey::Pool.get_unique_pool_key(&addr.to_vec().to_hex()))
+            .and_then(|pool| pool.protocol_type)
+            .is_some_and(|protocol_type| {
+                protocol_type.implementation_type == i32::from(ImplementationType::Vm)
+            })
+    })?;
 
-    Ok(block_entity_changes)
+    Ok(AggregationMode::from_params(&params)?.apply(block_entity_changes))
 }
 
 /// Handle the sync events and update the reserves of the pools.
@@ -88,11 +132,7 @@ fn handle_sync(
 
         let tx_change = tx_changes
             .entry(_tx.hash.clone())
-            .or_insert_with(|| PartialChanges {
-                transaction: _tx.into(),
-                entity_changes: HashMap::new(),
-                balance_changes: HashMap::new(),
-            });
+            .or_insert_with(|| PartialChanges::new(_tx));
 
         for (i, reserve_bytes) in reserves_bytes.iter().enumerate() {
             let attribute_name = format!("reserve{}", i);
@@ -133,6 +173,163 @@ fn handle_sync(
     eh.handle_events();
 }
 
+/// Storage slots of the `UniswapV2Pair` contract tracked as state attributes, besides the reserves
+/// which are tracked through `Sync` events.
+///
+/// `blockTimestampLast` shares its slot with the reserves and occupies its 4 most significant
+/// bytes.
+const RESERVES_SLOT: u8 = 8;
+const PRICE0_CUMULATIVE_LAST_SLOT: u8 = 9;
+const PRICE1_CUMULATIVE_LAST_SLOT: u8 = 10;
+const K_LAST_SLOT: u8 = 11;
+
+/// Decodes a storage change of a pair into the affected state attribute.
+fn decode_pair_storage_change(change: &eth::StorageChange) -> Option<(&'static str, BigInt)> {
+    let (slot, padding) = change.key.split_last()?;
+    if change.key.len() != 32 || padding.iter().any(|byte| *byte != 0) {
+        return None;
+    }
+    let (name, value) = match *slot {
+        RESERVES_SLOT => ("block_timestamp_last", change.new_value.get(..4)?),
+        PRICE0_CUMULATIVE_LAST_SLOT => ("price0_cumulative_last", change.new_value.as_slice()),
+        PRICE1_CUMULATIVE_LAST_SLOT => ("price1_cumulative_last", change.new_value.as_slice()),
+        K_LAST_SLOT => ("k_last", change.new_value.as_slice()),
+        _ => return None,
+    };
+    Some((name, BigInt::from_unsigned_bytes_be(value)))
+}
+
+/// Handle the storage changes of the pools.
+///
+/// Tracks `kLast`, which determines the protocol fee minted on `mint` and `burn`, as well as the
+/// price oracle accumulators `price0CumulativeLast`, `price1CumulativeLast` and
+/// `blockTimestampLast`. None of these are announced by events, so they are read from the pairs'
+/// storage. Only the last change of each slot within a transaction is kept.
+fn handle_storage_changes(
+    block: &eth::Block,
+    tx_changes: &mut HashMap<Vec<u8>, PartialChanges>,
+    store: &StoreGetProto<ProtocolComponent>,
+) {
+    let mut known_pools: HashMap<Vec<u8>, bool> = HashMap::new();
+    for tx in block.transactions() {
+        let storage_changes = tx
+            .calls
+            .iter()
+            .filter(|call| !call.state_reverted)
+            .flat_map(|call| call.storage_changes.iter())
+            .sorted_by_key(|change| change.ordinal);
+        for change in storage_changes {
+            let Some((name, value)) = decode_pair_storage_change(change) else {
+                continue;
+            };
+            let is_pool = *known_pools
+                .entry(change.address.clone())
+                .or_insert_with(|| {
+                    store
+                        .get_last(StoreKey::Pool.get_unique_pool_key(&change.address.to_hex()))
+                        .is_some()
+                });
+            if !is_pool {
+                continue;
+            }
+
+            tx_changes
+                .entry(tx.hash.clone())
+                .or_insert_with(|| PartialChanges::new(tx))
+                .entity_changes
+                .insert(
+                    ComponentKey::new(change.address.to_hex(), name.to_string()),
+                    Attribute {
+                        name: name.to_string(),
+                        value: value.to_signed_bytes_be(),
+                        change: ChangeType::Update.into(),
+                    },
+                );
+        }
+    }
+}
+
+/// Handle the `setFeeTo` calls of the configured factories.
+///
+/// Protocol fees are minted on `mint` and `burn` whenever `feeTo` is set on the factory, which is a
+/// factory wide setting. The new recipient is emitted once as `fee_to` attribute of the factory
+/// component registered by `map_pools_created`.
+fn handle_set_fee_to(
+    block: &eth::Block,
+    tx_changes: &mut HashMap<Vec<u8>, PartialChanges>,
+    factories: &[Vec<u8>],
+) {
+    for tx in block.transactions() {
+        for call in tx
+            .calls
+            .iter()
+            .filter(|call| !call.state_reverted && factories.contains(&call.address))
+        {
+            let Some(SetFeeTo { fee_to }) = SetFeeTo::match_and_decode(call) else {
+                continue;
+            };
+            tx_changes
+                .entry(tx.hash.clone())
+                .or_insert_with(|| PartialChanges::new(tx))
+                .entity_changes
+                .insert(
+                    ComponentKey::new(call.address.to_hex(), "fee_to".to_string()),
+                    Attribute {
+                        name: "fee_to".to_string(),
+                        value: fee_to,
+                        change: ChangeType::Update.into(),
+                    },
+                );
+        }
+    }
+}
+
+/// Handle the fee update events of forks with per pair fees.
+///
+/// Emits the new fee as `fee` state attribute of the pair. Updates for pairs not known to the store
+/// or created by another factory than the one the update is configured for are ignored.
+fn handle_fee_updates(
+    block: &eth::Block,
+    tx_changes: &mut HashMap<Vec<u8>, PartialChanges>,
+    store: &StoreGetProto<ProtocolComponent>,
+    fee_updates: &[FeeUpdate],
+) {
+    for log in block.logs() {
+        let Some((fee_update, pair, fee)) = fee_updates
+            .iter()
+            .find_map(|fee_update| {
+                fee_update
+                    .decode(log.log)
+                    .map(|(pair, fee)| (fee_update, pair, fee))
+            })
+        else {
+            continue;
+        };
+        let pool_address_hex = pair.to_hex();
+        let is_factory_pool = store
+            .get_last(StoreKey::Pool.get_unique_pool_key(&pool_address_hex))
+            .and_then(|pool| pool.get_attribute_value("factory"))
+            .is_some_and(|factory| factory == fee_update.factory);
+        if !is_factory_pool {
+            continue;
+        }
+
+        let tx = log.receipt.transaction;
+        tx_changes
+            .entry(tx.hash.clone())
+            .or_insert_with(|| PartialChanges::new(tx))
+            .entity_changes
+            .insert(
+                ComponentKey::new(pool_address_hex, "fee".to_string()),
+                Attribute {
+                    name: "fee".to_string(),
+                    value: fee.to_signed_bytes_be(),
+                    change: ChangeType::Update.into(),
+                },
+            );
+    }
+}
+
 /// Merge the changes from the sync events with the create_pool events previously mapped on
 /// block_entity_changes.
 ///
@@ -181,11 +378,35 @@ fn merge_block(
             .expect("Transaction not found")
             .clone();
 
-        // If there are sync events for this transaction, add them to the block_entity_changes
+        // If there are sync events for this transaction, add them to the block_entity_changes.
+        // The later changes take precedence when the entity changes are merged, see
+        // `add_vm_contract_changes`. Attributes created within this transaction stay creations
+        // though, e.g. the reserves of a pair created and funded through the router.
         if let Some(partial_changes) = tx_changes.remove(&tx.hash) {
-            change.entity_changes = partial_changes
+            let created = change
+                .entity_changes
+                .iter()
+                .flat_map(|entity_change| {
+                    entity_change
+                        .attributes
+                        .iter()
+                        .filter(|attr| attr.change == i32::from(ChangeType::Creation))
+                        .map(|attr| (entity_change.component_id.clone(), attr.name.clone()))
+                })
+                .collect::<HashSet<_>>();
+            let mut entity_changes = partial_changes
                 .clone()
                 .consolidate_entity_changes();
+            for entity_change in entity_changes.iter_mut() {
+                for attr in entity_change.attributes.iter_mut() {
+                    if created.contains(&(entity_change.component_id.clone(), attr.name.clone())) {
+                        attr.change = ChangeType::Creation.into();
+                    }
+                }
+            }
+            change
+                .entity_changes
+                .extend(entity_changes);
             change.balance_changes = partial_changes
                 .balance_changes
                 .into_values()
@@ -221,3 +442,236 @@ fn merge_block(
         .into_values()
         .collect();
 }
+
+/// Adds the storage, balance and code changes of pairs simulated in the VM.
+///
+/// Pairs of factories configured with `implementation=vm` are simulated by executing their
+/// bytecode, so their contract state is emitted in addition to the attributes. Pairs with changed
+/// contracts are marked as updated so consumers re-simulate them even if no attribute changed.
+///
+/// `is_vm_pool` decides whether an address is a pair simulated in the VM.
+fn add_vm_contract_changes(
+    block: &eth::Block,
+    block_entity_changes: &mut BlockChanges,
+    is_vm_pool: impl Fn(&[u8]) -> bool,
+) -> Result<(), substreams::errors::Error> {
+    let mut transaction_changes: HashMap<u64, TransactionChangesBuilder> = HashMap::new();
+    for change in block_entity_changes.changes.drain(..) {
+        let tx = change
+            .tx
+            .as_ref()
+            .context("Transaction not found")?;
+        let builder = transaction_changes
+            .entry(tx.index)
+            .or_insert_with(|| TransactionChangesBuilder::new(tx));
+        change
+            .component_changes
+            .iter()
+            .for_each(|component| builder.add_protocol_component(component));
+        change
+            .entity_changes
+            .iter()
+            .for_each(|entity_change| builder.add_entity_change(entity_change));
+        change
+            .balance_changes
+            .iter()
+            .for_each(|balance_change| builder.add_balance_change(balance_change));
+    }
+
+    extract_contract_changes_builder(block, is_vm_pool, &mut transaction_changes);
+
+    transaction_changes
+        .values_mut()
+        .for_each(|builder| {
+            let pool_ids = builder
+                .changed_contracts()
+                .map(|addr| addr.to_vec().to_hex())
+                .collect::<Vec<_>>();
+            pool_ids
+                .iter()
+                .for_each(|id| builder.mark_component_as_updated(id));
+        });
+
+    block_entity_changes.changes = transaction_changes
+        .drain()
+        .sorted_unstable_by_key(|(index, _)| *index)
+        .filter_map(|(_, builder)| builder.build())
+        .collect();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    const PAIR: [u8; 20] = hex!("00000000000000000000000000000000000000aa");
+
+    fn storage_change(slot: u8, new_value: Vec<u8>) -> eth::StorageChange {
+        let mut key = vec![0; 32];
+        key[31] = slot;
+        eth::StorageChange { address: PAIR.to_vec(), key, new_value, ..Default::default() }
+    }
+
+    #[test]
+    fn test_decode_pair_storage_change() {
+        let mut reserves = vec![0; 32];
+        reserves[..4].copy_from_slice(&hex!("665f2a10"));
+        reserves[31] = 0xff;
+        let mut k_last = vec![0xff; 32];
+        k_last[0] = 0x80;
+
+        assert_eq!(
+            decode_pair_storage_change(&storage_change(RESERVES_SLOT, reserves)),
+            Some(("block_timestamp_last", BigInt::from(0x665f2a10)))
+        );
+        assert_eq!(
+            decode_pair_storage_change(&storage_change(K_LAST_SLOT, k_last.clone())),
+            Some(("k_last", BigInt::from_unsigned_bytes_be(&k_last)))
+        );
+        assert_eq!(decode_pair_storage_change(&storage_change(7, vec![1; 32])), None);
+    }
+
+    fn attribute(name: &str, value: u8, change: ChangeType) -> Attribute {
+        Attribute { name: name.to_string(), value: vec![value], change: change.into() }
+    }
+
+    #[test]
+    fn test_merge_block_keeps_creation_attributes() {
+        let tx = eth::TransactionTrace { hash: vec![1], ..Default::default() };
+        let pool = PAIR.to_vec().to_hex();
+        let mut block_entity_changes = BlockChanges {
+            block: None,
+            changes: vec![TransactionChanges {
+                tx: Some((&tx).into()),
+                entity_changes: vec![EntityChanges {
+                    component_id: pool.clone(),
+                    attributes: vec![
+                        attribute("reserve0", 0, ChangeType::Creation),
+                        attribute("k_last", 0xfe, ChangeType::Creation),
+                    ],
+                }],
+                ..Default::default()
+            }],
+        };
+        let mut partial_changes = PartialChanges::new(&tx);
+        partial_changes.entity_changes.insert(
+            ComponentKey::new(pool.clone(), "reserve0".to_string()),
+            attribute("reserve0", 5, ChangeType::Update),
+        );
+        let mut tx_changes = HashMap::from([(tx.hash.clone(), partial_changes)]);
+
+        merge_block(&mut tx_changes, &mut block_entity_changes);
+
+        let attributes = block_entity_changes.changes[0]
+            .entity_changes
+            .iter()
+            .flat_map(|change| change.attributes.iter())
+            .map(|attribute| (attribute.name.as_str(), attribute.value.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            attributes,
+            vec![("reserve0", vec![0]), ("k_last", vec![0xfe]), ("reserve0", vec![5])]
+        );
+    }
+
+    #[test]
+    fn test_create_and_sync_in_same_transaction() {
+        let tx = eth::TransactionTrace { hash: vec![1], index: 4, ..Default::default() };
+        let pool = PAIR.to_vec().to_hex();
+        let mut block_entity_changes = BlockChanges {
+            block: None,
+            changes: vec![TransactionChanges {
+                tx: Some((&tx).into()),
+                entity_changes: vec![EntityChanges {
+                    component_id: pool.clone(),
+                    attributes: vec![
+                        attribute("reserve0", 0, ChangeType::Creation),
+                        attribute("reserve1", 0, ChangeType::Creation),
+                        attribute("k_last", 0, ChangeType::Creation),
+                    ],
+                }],
+                component_changes: vec![ProtocolComponent {
+                    id: pool.clone(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        };
+        let mut partial_changes = PartialChanges::new(&tx);
+        for (name, value) in [("reserve0", 5), ("reserve1", 6), ("fee", 7)] {
+            partial_changes.entity_changes.insert(
+                ComponentKey::new(pool.clone(), name.to_string()),
+                attribute(name, value, ChangeType::Update),
+            );
+        }
+        let mut tx_changes = HashMap::from([(tx.hash.clone(), partial_changes)]);
+
+        merge_block(&mut tx_changes, &mut block_entity_changes);
+        add_vm_contract_changes(&eth::Block::default(), &mut block_entity_changes, |_| false)
+            .unwrap();
+
+        assert_eq!(block_entity_changes.changes.len(), 1);
+        let changes = &block_entity_changes.changes[0];
+        assert_eq!(changes.component_changes.len(), 1);
+        let attributes = changes.entity_changes[0]
+            .attributes
+            .iter()
+            .map(|attr| (attr.name.as_str(), (attr.value.clone(), attr.change)))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(
+            attributes,
+            HashMap::from([
+                ("reserve0", (vec![5], ChangeType::Creation.into())),
+                ("reserve1", (vec![6], ChangeType::Creation.into())),
+                ("k_last", (vec![0], ChangeType::Creation.into())),
+                ("fee", (vec![7], ChangeType::Update.into())),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_add_vm_contract_changes() {
+        let tx = eth::TransactionTrace {
+            hash: vec![1],
+            index: 3,
+            status: eth::TransactionTraceStatus::Succeeded.into(),
+            calls: vec![eth::Call {
+                address: PAIR.to_vec(),
+                storage_changes: vec![storage_change(K_LAST_SLOT, vec![1; 32])],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let block = eth::Block {
+            detail_level: eth::block::DetailLevel::DetaillevelExtended.into(),
+            transaction_traces: vec![tx.clone()],
+            ..Default::default()
+        };
+        let mut block_entity_changes = BlockChanges { block: None, changes: vec![] };
+
+        add_vm_contract_changes(&block, &mut block_entity_changes, |addr| addr == PAIR).unwrap();
+
+        let changes = &block_entity_changes.changes[0];
+        assert_eq!(changes.tx.as_ref().unwrap().index, 3);
+        assert_eq!(changes.contract_changes[0].address, PAIR.to_vec());
+        assert_eq!(
+            changes.contract_changes[0].slots[0].slot,
+            storage_change(K_LAST_SLOT, vec![]).key
+        );
+        assert_eq!(changes.contract_changes[0].slots[0].value, vec![1; 32]);
+        assert_eq!(changes.entity_changes[0].component_id, PAIR.to_vec().to_hex());
+        assert_eq!(changes.entity_changes[0].attributes[0].name, "update_marker");
+    }
+
+    #[test]
+    fn test_add_vm_contract_changes_requires_transaction() {
+        let mut block_entity_changes =
+            BlockChanges { block: None, changes: vec![TransactionChanges::default()] };
+
+        assert!(add_vm_contract_changes(&eth::Block::default(), &mut block_entity_changes, |_| {
+            false
+        })
+        .is_err());
+    }
+}
@@ -1,9 +1,15 @@
 pub use map_pool_created::map_pools_created;
 pub use map_pool_events::map_pool_events;
+pub use store_factories::store_factories;
+pub use store_fee_to::store_fee_to;
 pub use store_pools::store_pools;
 
 #[path = "1_map_pool_created.rs"]
 mod map_pool_created;
+#[path = "2_store_factories.rs"]
+mod store_factories;
+#[path = "2_store_fee_to.rs"]
+mod store_fee_to;
 #[path = "2_store_pools.rs"]
 mod store_pools;
 
@@ -0,0 +1,301 @@
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use substreams::scalar::BigInt;
+use substreams_ethereum::pb::eth::v2::{self as eth};
+use tycho_substreams::prelude::*;
+
+/// Params of `map_pools_created`, `store_fee_to`, `store_factories` and `map_pool_events`.
+///
+/// Either configures a single factory through the top level keys, e.g.
+/// `factory_address=...&protocol_type_name=...&fee=30`, or multiple factories through the
+/// `factories` list, e.g. `factories[0][address]=...&factories[0][protocol_type_name]=...`.
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Params {
+    #[serde(default)]
+    factories: Vec<FactoryConfig>,
+    factory_address: Option<String>,
+    protocol_type_name: Option<String>,
+    fee: Option<u64>,
+    fee_denominator: Option<u64>,
+    #[serde(default)]
+    implementation: Implementation,
+    fee_update_topic: Option<String>,
+    fee_update_emitter: Option<String>,
+}
+
+impl Params {
+    /// Parses the params into the list of configured factories.
+    pub fn parse(input: &str) -> Result<Vec<FactoryConfig>> {
+        let params: Params = serde_qs::from_str(input).context("Unable to deserialize params")?;
+        let mut factories = params.factories;
+        if let Some(address) = params.factory_address {
+            factories.push(FactoryConfig {
+                address,
+                protocol_type_name: params
+                    .protocol_type_name
+                    .context("Missing protocol_type_name")?,
+                fee: params.fee.unwrap_or_else(default_fee),
+                fee_denominator: params
+                    .fee_denominator
+                    .unwrap_or_else(default_fee_denominator),
+                start_block: 0,
+                implementation: params.implementation,
+                fee_update_topic: params.fee_update_topic,
+                fee_update_emitter: params.fee_update_emitter,
+            });
+        }
+        if factories.is_empty() {
+            bail!("No factory configured");
+        }
+        for factory in factories.iter() {
+            factory.address_bytes()?;
+            factory.fee_update()?;
+        }
+        Ok(factories)
+    }
+}
+
+/// A Uniswap V2 (fork) factory to index.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct FactoryConfig {
+    /// Hex encoded factory address.
+    pub address: String,
+    /// Protocol type name of the pairs created by this factory.
+    pub protocol_type_name: String,
+    /// Swap fee in units of `fee_denominator`. Defaults to Uniswap's 0.3%.
+    #[serde(default = "default_fee")]
+    pub fee: u64,
+    /// Denominator of `fee`. Defaults to basis points.
+    #[serde(default = "default_fee_denominator")]
+    pub fee_denominator: u64,
+    /// Pairs created before this block are ignored.
+    #[serde(default)]
+    pub start_block: u64,
+    /// How the pairs of this factory are simulated.
+    #[serde(default)]
+    pub implementation: Implementation,
+    /// Hex encoded topic 0 of the event updating a pair's fee, for forks charging a per pair fee
+    /// which can be changed after the pair was created. The new fee is expected in the first data
+    /// word, in units of `fee_denominator`.
+    pub fee_update_topic: Option<String>,
+    /// Hex encoded address emitting the fee updates, e.g. the factory. The pair is then expected
+    /// as first indexed parameter. If omitted, the pairs are expected to emit the updates.
+    pub fee_update_emitter: Option<String>,
+}
+
+/// How the pairs of a factory are simulated.
+#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Implementation {
+    /// Natively, from the reserves and the fee attributes. Suitable for forks sticking to the
+    /// constant product formula with a fixed fee.
+    #[default]
+    Custom,
+    /// By executing the pair's bytecode. Required for forks with custom fee curves, dynamic fees
+    /// or other deviations from the original pair implementation. The pair is registered as
+    /// contract of the component and its storage is emitted by `map_pool_events`.
+    Vm,
+}
+
+impl From<Implementation> for ImplementationType {
+    fn from(implementation: Implementation) -> Self {
+        match implementation {
+            Implementation::Custom => ImplementationType::Custom,
+            Implementation::Vm => ImplementationType::Vm,
+        }
+    }
+}
+
+impl FactoryConfig {
+    pub fn address_bytes(&self) -> Result<Vec<u8>> {
+        hex::decode(self.address.trim_start_matches("0x"))
+            .with_context(|| format!("Invalid factory address {}", self.address))
+    }
+
+    /// The fee updates of the pairs of this factory, if it charges per pair fees.
+    pub fn fee_update(&self) -> Result<Option<FeeUpdate>> {
+        let decode = |value: &str| hex::decode(value.trim_start_matches("0x"));
+        let Some(topic) = &self.fee_update_topic else {
+            return Ok(None);
+        };
+        Ok(Some(FeeUpdate {
+            factory: self.address_bytes()?,
+            topic: decode(topic).context("Invalid fee_update_topic")?,
+            emitter: self
+                .fee_update_emitter
+                .as_deref()
+                .map(decode)
+                .transpose()
+                .context("Invalid fee_update_emitter")?,
+        }))
+    }
+}
+
+fn default_fee() -> u64 {
+    30
+}
+
+fn default_fee_denominator() -> u64 {
+    10_000
+}
+
+/// Decodes fee update events of forks with per pair fees.
+///
+/// Only pairs created by `factory` are updated, so factories of forks announcing fee updates
+/// through an event with the same topic don't affect each other.
+#[derive(Debug, PartialEq)]
+pub struct FeeUpdate {
+    pub factory: Vec<u8>,
+    topic: Vec<u8>,
+    emitter: Option<Vec<u8>>,
+}
+
+impl FeeUpdate {
+    /// Returns the pair address and its new fee if the log is a fee update.
+    pub fn decode(&self, log: &eth::Log) -> Option<(Vec<u8>, BigInt)> {
+        if log.topics.first() != Some(&self.topic) {
+            return None;
+        }
+        let pair = match &self.emitter {
+            Some(emitter) if *emitter == log.address => log.topics.get(1)?[12..].to_vec(),
+            Some(_) => return None,
+            None => log.address.clone(),
+        };
+        let fee = BigInt::from_unsigned_bytes_be(log.data.get(..32)?);
+        Some((pair, fee))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    const FACTORY: [u8; 20] = hex!("0000000000000000000000000000000000000fac");
+    const PAIR: [u8; 20] = hex!("00000000000000000000000000000000000000aa");
+    const TOPIC: [u8; 32] =
+        hex!("00000000000000000000000000000000000000000000000000000000000000fe");
+
+    fn fee_log(emitter: &[u8], topics: Vec<Vec<u8>>, fee: u8) -> eth::Log {
+        let mut data = vec![0; 32];
+        data[31] = fee;
+        eth::Log { address: emitter.to_vec(), topics, data, ..Default::default() }
+    }
+
+    #[test]
+    fn test_params_single_factory() {
+        let res = Params::parse(
+            "factory_address=5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f&protocol_type_name=uniswap_v2_pool",
+        )
+        .unwrap();
+
+        assert_eq!(
+            res,
+            vec![FactoryConfig {
+                address: "5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f".to_string(),
+                protocol_type_name: "uniswap_v2_pool".to_string(),
+                fee: 30,
+                fee_denominator: 10_000,
+                start_block: 0,
+                implementation: Implementation::Custom,
+                fee_update_topic: None,
+                fee_update_emitter: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_params_multiple_factories() {
+        let res = Params::parse(
+            "factories[0][address]=5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f&factories[0][protocol_type_name]=uniswap_v2_pool&\
+             factories[1][address]=1097053fd2ea711dad45caccc45eff7548fcb362&factories[1][protocol_type_name]=pancakeswap_v2_pool&factories[1][fee]=25&factories[1][start_block]=15614590",
+        )
+        .unwrap();
+
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].fee, 30);
+        assert_eq!(res[0].start_block, 0);
+        assert_eq!(res[1].protocol_type_name, "pancakeswap_v2_pool");
+        assert_eq!(res[1].fee, 25);
+        assert_eq!(res[1].fee_denominator, 10_000);
+        assert_eq!(res[1].start_block, 15614590);
+    }
+
+    #[test]
+    fn test_params_vm_implementation() {
+        let res = Params::parse(
+            "factories[0][address]=5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f&factories[0][protocol_type_name]=uniswap_v2_pool&\
+             factories[1][address]=1097053fd2ea711dad45caccc45eff7548fcb362&factories[1][protocol_type_name]=fork_v2_pool&factories[1][implementation]=vm",
+        )
+        .unwrap();
+
+        assert_eq!(res[0].implementation, Implementation::Custom);
+        assert_eq!(res[1].implementation, Implementation::Vm);
+        assert!(Params::parse(
+            "factory_address=5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f&protocol_type_name=fork_v2_pool&implementation=evm"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_params_without_factory() {
+        assert!(Params::parse("fee=25").is_err());
+        assert!(Params::parse("factory_address=xyz&protocol_type_name=uniswap_v2_pool").is_err());
+        assert!(Params::parse(
+            "factory_address=5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f&protocol_type_name=uniswap_v2_pool&fee_update_topic=xyz"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_params_fee_update_per_factory() {
+        let res = Params::parse(&format!(
+            "aggregation=transaction&\
+             factories[0][address]=5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f&factories[0][protocol_type_name]=uniswap_v2_pool&\
+             factories[1][address]={}&factories[1][protocol_type_name]=fork_v2_pool&factories[1][fee_update_topic]=0x{}",
+            hex::encode(FACTORY),
+            hex::encode(TOPIC)
+        ))
+        .unwrap();
+
+        assert_eq!(res[0].fee_update().unwrap(), None);
+        assert_eq!(
+            res[1].fee_update().unwrap(),
+            Some(FeeUpdate { factory: FACTORY.to_vec(), topic: TOPIC.to_vec(), emitter: None })
+        );
+    }
+
+    #[test]
+    fn test_decode_pair_fee_update() {
+        let res = Params::parse(&format!(
+            "factory_address={}&protocol_type_name=fork_v2_pool&fee_update_topic=0x{}",
+            hex::encode(FACTORY),
+            hex::encode(TOPIC)
+        ))
+        .unwrap();
+        let fee_update = res[0].fee_update().unwrap().unwrap();
+
+        let res = fee_update.decode(&fee_log(&PAIR, vec![TOPIC.to_vec()], 20));
+
+        assert_eq!(res, Some((PAIR.to_vec(), BigInt::from(20))));
+    }
+
+    #[test]
+    fn test_decode_factory_fee_update() {
+        let res = Params::parse(&format!(
+            "factory_address={}&protocol_type_name=fork_v2_pool&fee_update_topic={}&fee_update_emitter={}",
+            hex::encode(FACTORY),
+            hex::encode(TOPIC),
+            hex::encode(FACTORY)
+        ))
+        .unwrap();
+        let fee_update = res[0].fee_update().unwrap().unwrap();
+        let topics = vec![TOPIC.to_vec(), [[0u8; 12].as_slice(), &PAIR].concat()];
+
+        let res = fee_update.decode(&fee_log(&FACTORY, topics.clone(), 10));
+        let other_emitter = fee_update.decode(&fee_log(&PAIR, topics, 10));
+
+        assert_eq!(res, Some((PAIR.to_vec(), BigInt::from(10))));
+        assert_eq!(other_emitter, None);
+    }
+}
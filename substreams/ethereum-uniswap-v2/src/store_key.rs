@@ -1,6 +1,8 @@
 #[derive(Clone)]
 pub enum StoreKey {
     Pool,
+    FeeTo,
+    Factory,
 }
 
 impl StoreKey {
@@ -11,6 +13,8 @@ impl StoreKey {
     pub fn unique_id(&self) -> String {
         match self {
             StoreKey::Pool => "Pool".to_string(),
+            StoreKey::FeeTo => "FeeTo".to_string(),
+            StoreKey::Factory => "Factory".to_string(),
         }
     }
 }
@@ -14,13 +14,13 @@ use tycho_substreams::prelude::*;
 pub fn map_pools_created(
     params: String,
     block: eth::Block,
-) -> Result<BlockEntityChanges, substreams::errors::Error> {
+) -> Result<BlockChanges, substreams::errors::Error> {
     let mut new_pools: Vec<TransactionEntityChanges> = vec![];
     let factory_address = params.as_str();
 
     get_new_pools(&block, &mut new_pools, factory_address);
 
-    Ok(BlockEntityChanges { block: None, changes: new_pools })
+    Ok(BlockEntityChanges { block: None, changes: new_pools }.into())
 }
 
 // Extract new pools from PoolCreated events
@@ -11,7 +11,8 @@ use substreams_helper::hex::Hexable;
 
 use crate::{
     abi::pool::events::{
-        Burn, Collect, CollectProtocol, Flash, Initialize, Mint, SetFeeProtocol, Swap,
+        Burn, Collect, CollectProtocol, Flash, IncreaseObservationCardinalityNext, Initialize,
+        Mint, SetFeeProtocol, Swap,
     },
     pb::uniswap::v3::{
         events::{
@@ -177,6 +178,24 @@ fn log_to_event(event: &Log, pool: Pool, tx: TransactionTrace) -> Option<PoolEve
                 amount_1: cp.amount1.to_string(),
             })),
         })
+    } else if let Some(iocn) = IncreaseObservationCardinalityNext::match_and_decode(event) {
+        Some(PoolEvent {
+            log_ordinal: event.ordinal,
+            pool_address: Hex(pool.address).to_string(),
+            token0: Hex(pool.token0).to_string(),
+            token1: Hex(pool.token1).to_string(),
+            transaction: Some(tx.into()),
+            r#type: Some(Type::IncreaseObservationCardinalityNext(
+                pool_event::IncreaseObservationCardinalityNext {
+                    observation_cardinality_next_old: iocn
+                        .observation_cardinality_next_old
+                        .to_u64(),
+                    observation_cardinality_next_new: iocn
+                        .observation_cardinality_next_new
+                        .to_u64(),
+                },
+            )),
+        })
     } else {
         None
     }
@@ -40,6 +40,21 @@ pub fn store_ticks_liquidity(ticks_deltas: TickDeltas, store: StoreAddBigInt) {
     });
 }
 
+#[substreams::handlers::store]
+pub fn store_ticks_liquidity_gross(ticks_deltas: TickDeltas, store: StoreAddBigInt) {
+    let mut deltas = ticks_deltas.deltas.clone();
+
+    deltas.sort_unstable_by_key(|delta| delta.ordinal);
+
+    deltas.iter().for_each(|delta| {
+        store.add(
+            delta.ordinal,
+            format!("pool:{0}:tick:{1}", hex::encode(&delta.pool_address), delta.tick_index,),
+            BigInt::from_signed_bytes_be(&delta.liquidity_gross_delta),
+        );
+    });
+}
+
 fn event_to_ticks_deltas(event: PoolEvent) -> Vec<TickDelta> {
     match event.r#type.as_ref().unwrap() {
         pool_event::Type::Mint(mint) => {
@@ -50,6 +65,9 @@ fn event_to_ticks_deltas(event: PoolEvent) -> Vec<TickDelta> {
                     liquidity_net_delta: BigInt::from_str(&mint.amount)
                         .unwrap()
                         .to_signed_bytes_be(),
+                    liquidity_gross_delta: BigInt::from_str(&mint.amount)
+                        .unwrap()
+                        .to_signed_bytes_be(),
                     ordinal: event.log_ordinal,
                     transaction: event.transaction.clone(),
                 },
@@ -60,6 +78,9 @@ fn event_to_ticks_deltas(event: PoolEvent) -> Vec<TickDelta> {
                         .unwrap()
                         .neg()
                         .to_signed_bytes_be(),
+                    liquidity_gross_delta: BigInt::from_str(&mint.amount)
+                        .unwrap()
+                        .to_signed_bytes_be(),
                     ordinal: event.log_ordinal,
                     transaction: event.transaction,
                 },
@@ -73,6 +94,10 @@ fn event_to_ticks_deltas(event: PoolEvent) -> Vec<TickDelta> {
                     .unwrap()
                     .neg()
                     .to_signed_bytes_be(),
+                liquidity_gross_delta: BigInt::from_str(&burn.amount)
+                    .unwrap()
+                    .neg()
+                    .to_signed_bytes_be(),
                 ordinal: event.log_ordinal,
                 transaction: event.transaction.clone(),
             },
@@ -82,6 +107,10 @@ fn event_to_ticks_deltas(event: PoolEvent) -> Vec<TickDelta> {
                 liquidity_net_delta: BigInt::from_str(&burn.amount)
                     .unwrap()
                     .to_signed_bytes_be(),
+                liquidity_gross_delta: BigInt::from_str(&burn.amount)
+                    .unwrap()
+                    .neg()
+                    .to_signed_bytes_be(),
                 ordinal: event.log_ordinal,
                 transaction: event.transaction,
             },
@@ -7,19 +7,23 @@ use std::{collections::HashMap, str::FromStr, vec};
 use substreams::{pb::substreams::StoreDeltas, scalar::BigInt};
 use substreams_ethereum::pb::eth::v2::{self as eth};
 use substreams_helper::hex::Hexable;
-use tycho_substreams::{balances::aggregate_balances_changes, prelude::*};
+use tycho_substreams::{
+    aggregation::AggregationMode, balances::aggregate_balances_changes, prelude::*,
+};
 
 type PoolAddress = Vec<u8>;
 
 #[substreams::handlers::map]
 pub fn map_protocol_changes(
+    params: String,
     block: eth::Block,
-    created_pools: BlockEntityChanges,
+    created_pools: BlockChanges,
     events: Events,
     balances_map_deltas: BlockBalanceDeltas,
     balances_store_deltas: StoreDeltas,
     ticks_map_deltas: TickDeltas,
     ticks_store_deltas: StoreDeltas,
+    ticks_gross_store_deltas: StoreDeltas,
     pool_liquidity_changes: LiquidityChanges,
     pool_liquidity_store_deltas: StoreDeltas,
 ) -> Result<BlockChanges, substreams::errors::Error> {
@@ -76,7 +80,7 @@ pub fn map_protocol_changes(
     ticks_store_deltas
         .deltas
         .into_iter()
-        .zip(ticks_map_deltas.deltas)
+        .zip(ticks_map_deltas.deltas.iter())
         .for_each(|(store_delta, tick_delta)| {
             let new_value_bigint =
                 BigInt::from_str(&String::from_utf8(store_delta.new_value).unwrap()).unwrap();
@@ -98,7 +102,7 @@ pub fn map_protocol_changes(
                     ChangeType::Update.into()
                 },
             };
-            let tx = tick_delta.transaction.unwrap();
+            let tx = tick_delta.transaction.clone().unwrap();
             let builder = transaction_changes
                 .entry(tx.index)
                 .or_insert_with(|| TransactionChangesBuilder::new(&tx.into()));
@@ -109,6 +113,51 @@ pub fn map_protocol_changes(
             });
         });
 
+    // Insert ticks liquidity-gross changes, a tick is initialized as long as it references any
+    // liquidity. Fee growth outside of a tick and the tick bitmap can't be derived from logs: the
+    // former depends on the global fee growth at the time the tick is crossed, the latter follows
+    // from the initialized ticks.
+    ticks_gross_store_deltas
+        .deltas
+        .into_iter()
+        .zip(ticks_map_deltas.deltas.iter())
+        .for_each(|(store_delta, tick_delta)| {
+            let new_value_bigint =
+                BigInt::from_str(&String::from_utf8(store_delta.new_value).unwrap()).unwrap();
+            let is_creation = store_delta.old_value.is_empty() ||
+                BigInt::from_str(&String::from_utf8(store_delta.old_value).unwrap())
+                    .unwrap()
+                    .is_zero();
+            let change = if is_creation {
+                ChangeType::Creation
+            } else if new_value_bigint.is_zero() {
+                ChangeType::Deletion
+            } else {
+                ChangeType::Update
+            };
+            let initialized = BigInt::from(if new_value_bigint.is_zero() { 0 } else { 1 });
+            let tx = tick_delta.transaction.clone().unwrap();
+            let builder = transaction_changes
+                .entry(tx.index)
+                .or_insert_with(|| TransactionChangesBuilder::new(&tx.into()));
+
+            builder.add_entity_change(&EntityChanges {
+                component_id: tick_delta.pool_address.to_hex(),
+                attributes: vec![
+                    Attribute {
+                        name: format!("ticks/{}/liquidity-gross", tick_delta.tick_index),
+                        value: new_value_bigint.to_signed_bytes_be(),
+                        change: change.into(),
+                    },
+                    Attribute {
+                        name: format!("ticks/{}/initialized", tick_delta.tick_index),
+                        value: initialized.to_signed_bytes_be(),
+                        change: change.into(),
+                    },
+                ],
+            });
+        });
+
     // Insert liquidity changes
     pool_liquidity_store_deltas
         .deltas
@@ -153,44 +202,48 @@ pub fn map_protocol_changes(
             });
         });
 
-    Ok(BlockChanges {
+    Ok(AggregationMode::from_params(&params)?.apply(BlockChanges {
         block: Some((&block).into()),
         changes: transaction_changes
             .drain()
             .sorted_unstable_by_key(|(index, _)| *index)
             .filter_map(|(_, builder)| builder.build())
             .collect::<Vec<_>>(),
-    })
+    }))
 }
 
+/// Maps an event to the attributes it updates.
+///
+/// Of the oracle state only `observation_cardinality_next` is tracked, it is set by `initialize`
+/// and `increaseObservationCardinalityNext` only. The observations, the current observation index
+/// and cardinality are written by swaps and liquidity changes depending on the pool state at the
+/// end of the previous block, which can not be derived from logs. Use the storage based package if
+/// they are required.
 fn event_to_attributes_updates(event: PoolEvent) -> Vec<(Transaction, PoolAddress, Attribute)> {
+    let update = |name: String, value: BigInt| -> (Transaction, PoolAddress, Attribute) {
+        (
+            event
+                .transaction
+                .clone()
+                .unwrap()
+                .into(),
+            hex::decode(&event.pool_address).unwrap(),
+            Attribute {
+                name,
+                value: value.to_signed_bytes_be(),
+                change: ChangeType::Update.into(),
+            },
+        )
+    };
     match event.r#type.as_ref().unwrap() {
         pool_event::Type::Initialize(initalize) => {
             vec![
-                (
-                    event
-                        .transaction
-                        .clone()
-                        .unwrap()
-                        .into(),
-                    hex::decode(event.pool_address.clone()).unwrap(),
-                    Attribute {
-                        name: "sqrt_price_x96".to_string(),
-                        value: BigInt::from_str(&initalize.sqrt_price)
-                            .unwrap()
-                            .to_signed_bytes_be(),
-                        change: ChangeType::Update.into(),
-                    },
-                ),
-                (
-                    event.transaction.unwrap().into(),
-                    hex::decode(event.pool_address).unwrap(),
-                    Attribute {
-                        name: "tick".to_string(),
-                        value: BigInt::from(initalize.tick).to_signed_bytes_be(),
-                        change: ChangeType::Update.into(),
-                    },
+                update(
+                    "sqrt_price_x96".to_string(),
+                    BigInt::from_str(&initalize.sqrt_price).unwrap(),
                 ),
+                update("tick".to_string(), initalize.tick.into()),
+                update("observation_cardinality_next".to_string(), 1.into()),
             ]
         }
         pool_event::Type::Swap(swap) => vec![
@@ -243,6 +296,70 @@ fn event_to_attributes_updates(event: PoolEvent) -> Vec<(Transaction, PoolAddres
                 },
             ),
         ],
+        pool_event::Type::IncreaseObservationCardinalityNext(iocn) => vec![update(
+            "observation_cardinality_next".to_string(),
+            iocn.observation_cardinality_next_new
+                .into(),
+        )],
         _ => vec![],
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_increase_observation_cardinality_next_updates() {
+        let event = PoolEvent {
+            pool_address: "00000000000000000000000000000000000000aa".to_string(),
+            transaction: Some(Default::default()),
+            r#type: Some(pool_event::Type::IncreaseObservationCardinalityNext(
+                pool_event::IncreaseObservationCardinalityNext {
+                    observation_cardinality_next_old: 1,
+                    observation_cardinality_next_new: 3,
+                },
+            )),
+            ..Default::default()
+        };
+
+        let res = event_to_attributes_updates(event)
+            .into_iter()
+            .map(|(_, _, attr)| (attr.name, attr.value))
+            .collect::<Vec<_>>();
+
+        assert_eq!(res, vec![("observation_cardinality_next".to_string(), vec![3])]);
+    }
+
+    #[test]
+    fn test_initialize_updates() {
+        let event = PoolEvent {
+            pool_address: "00000000000000000000000000000000000000aa".to_string(),
+            transaction: Some(Default::default()),
+            r#type: Some(pool_event::Type::Initialize(pool_event::Initialize {
+                sqrt_price: "79228162514264337593543950336".to_string(),
+                tick: -1,
+            })),
+            ..Default::default()
+        };
+
+        let res = event_to_attributes_updates(event)
+            .into_iter()
+            .map(|(_, _, attr)| (attr.name, attr.value))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            res,
+            vec![
+                (
+                    "sqrt_price_x96".to_string(),
+                    BigInt::from(2)
+                        .pow(96)
+                        .to_signed_bytes_be()
+                ),
+                ("tick".to_string(), vec![0xff]),
+                ("observation_cardinality_next".to_string(), vec![1]),
+            ]
+        );
+    }
+}
@@ -38,7 +38,7 @@ pub struct TickDelta {
     /// The index of the tick.
     #[prost(int32, tag="2")]
     pub tick_index: i32,
-    /// The liquidity net delta of this tick. Bigint encoded as signed little endian bytes.
+    /// The liquidity net delta of this tick. Bigint encoded as signed big endian bytes.
     #[prost(bytes="vec", tag="3")]
     pub liquidity_net_delta: ::prost::alloc::vec::Vec<u8>,
     /// Used to determine the order of the balance changes. Necessary for the balance store.
@@ -46,6 +46,9 @@ pub struct TickDelta {
     pub ordinal: u64,
     #[prost(message, optional, tag="5")]
     pub transaction: ::core::option::Option<Transaction>,
+    /// The liquidity gross delta of this tick. Bigint encoded as signed big endian bytes.
+    #[prost(bytes="vec", tag="6")]
+    pub liquidity_gross_delta: ::prost::alloc::vec::Vec<u8>,
 }
 /// A group of TickDelta
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -61,7 +64,7 @@ pub struct LiquidityChange {
     /// The address of the pool.
     #[prost(bytes="vec", tag="1")]
     pub pool_address: ::prost::alloc::vec::Vec<u8>,
-    /// The liquidity changed amount. Bigint encoded as signed little endian bytes.
+    /// The liquidity changed amount. Bigint encoded as signed big endian bytes.
     #[prost(bytes="vec", tag="2")]
     pub value: ::prost::alloc::vec::Vec<u8>,
     /// The type of update, can be absolute or delta.
@@ -101,7 +104,7 @@ pub mod events {
         pub token1: ::prost::alloc::string::String,
         #[prost(message, optional, tag="105")]
         pub transaction: ::core::option::Option<super::Transaction>,
-        #[prost(oneof="pool_event::Type", tags="1, 2, 3, 4, 5, 6, 7, 8")]
+        #[prost(oneof="pool_event::Type", tags="1, 2, 3, 4, 5, 6, 7, 8, 9")]
         pub r#type: ::core::option::Option<pool_event::Type>,
     }
     /// Nested message and enum types in `PoolEvent`.
@@ -248,6 +251,16 @@ pub mod events {
             pub amount_1: ::prost::alloc::string::String,
         }
         #[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct IncreaseObservationCardinalityNext {
+            /// Unsigned
+            #[prost(uint64, tag="1")]
+            pub observation_cardinality_next_old: u64,
+            /// Unsigned
+            #[prost(uint64, tag="2")]
+            pub observation_cardinality_next_new: u64,
+        }
+        #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Oneof)]
         pub enum Type {
             #[prost(message, tag="1")]
@@ -266,6 +279,8 @@ pub mod events {
             SetFeeProtocol(SetFeeProtocol),
             #[prost(message, tag="8")]
             CollectProtocol(CollectProtocol),
+            #[prost(message, tag="9")]
+            IncreaseObservationCardinalityNext(IncreaseObservationCardinalityNext),
         }
     }
 }
@@ -8,5 +8,11 @@ fn main() -> Result<(), anyhow::Error> {
     Abigen::new("Pool", "abi/Pool.json")?
         .generate()?
         .write_to_file("src/abi/pool.rs")?;
+    Abigen::new("PancakeV3Pool", "abi/PancakeV3Pool.json")?
+        .generate()?
+        .write_to_file("src/abi/pancake_v3_pool.rs")?;
+    Abigen::new("NonfungiblePositionManager", "abi/NonfungiblePositionManager.json")?
+        .generate()?
+        .write_to_file("src/abi/nonfungible_position_manager.rs")?;
     Ok(())
 }
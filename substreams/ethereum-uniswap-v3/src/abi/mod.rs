@@ -1,4 +1,6 @@
 #![allow(clippy::all, clippy::pedantic, clippy::nursery)]
 
 pub mod factory;
+pub mod nonfungible_position_manager;
+pub mod pancake_v3_pool;
 pub mod pool;
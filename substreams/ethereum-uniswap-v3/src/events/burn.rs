@@ -1,10 +1,10 @@
-use substreams_ethereum::pb::eth::v2::StorageChange;
+use substreams_ethereum::pb::eth::v2::Call;
 use substreams_helper::storage_change::StorageChangesFilter;
 
 use crate::{
     abi::pool::events::Burn,
     pb::uniswap::v3::Pool,
-    storage::{constants::TRACKED_SLOTS, pool_storage::UniswapPoolStorage},
+    storage::{constants::StorageLayout, pool_storage::UniswapPoolStorage},
 };
 
 use super::{BalanceDelta, EventTrait};
@@ -13,26 +13,29 @@ use tycho_substreams::prelude::Attribute;
 impl EventTrait for Burn {
     fn get_changed_attributes(
         &self,
-        storage_changes: &[StorageChange],
-        pool_address: &[u8; 20],
+        call: &Call,
+        pool: &Pool,
+        layout: &StorageLayout,
     ) -> Vec<Attribute> {
-        let storage_vec = storage_changes.to_vec();
+        let storage_vec = call.storage_changes.to_vec();
 
         let filtered_storage_changes = storage_vec
-            .filter_by_address(pool_address)
+            .filter_by_address(&pool.address_bytes())
             .into_iter()
             .cloned()
             .collect();
 
-        let pool_storage = UniswapPoolStorage::new(&filtered_storage_changes);
+        let pool_storage = UniswapPoolStorage::new(&filtered_storage_changes, layout);
 
-        let mut changed_attributes =
-            pool_storage.get_changed_attributes(TRACKED_SLOTS.to_vec().iter().collect());
+        let mut changed_attributes = pool_storage.get_tracked_attributes();
 
         let changed_ticks =
             pool_storage.get_ticks_changes(vec![&self.tick_upper, &self.tick_lower]);
+        let changed_tick_bitmap = pool_storage
+            .get_tick_bitmap_changes(vec![&self.tick_upper, &self.tick_lower], pool.tick_spacing);
 
         changed_attributes.extend(changed_ticks);
+        changed_attributes.extend(changed_tick_bitmap);
 
         changed_attributes
     }
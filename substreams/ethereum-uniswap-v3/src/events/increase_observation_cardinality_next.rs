@@ -0,0 +1,36 @@
+use substreams_ethereum::pb::eth::v2::Call;
+
+use crate::{
+    abi::pool::events::IncreaseObservationCardinalityNext,
+    pb::uniswap::v3::Pool,
+    storage::{constants::StorageLayout, pool_storage::UniswapPoolStorage},
+};
+use substreams_helper::storage_change::StorageChangesFilter;
+use tycho_substreams::prelude::Attribute;
+
+use super::{BalanceDelta, EventTrait};
+
+impl EventTrait for IncreaseObservationCardinalityNext {
+    fn get_changed_attributes(
+        &self,
+        call: &Call,
+        pool: &Pool,
+        layout: &StorageLayout,
+    ) -> Vec<Attribute> {
+        let storage_vec = call.storage_changes.to_vec();
+
+        let filtered_storage_changes = storage_vec
+            .filter_by_address(&pool.address_bytes())
+            .into_iter()
+            .cloned()
+            .collect();
+
+        let pool_storage = UniswapPoolStorage::new(&filtered_storage_changes, layout);
+
+        pool_storage.get_tracked_attributes()
+    }
+
+    fn get_balance_delta(&self, _pool: &Pool, _ordinal: u64) -> Vec<BalanceDelta> {
+        vec![]
+    }
+}
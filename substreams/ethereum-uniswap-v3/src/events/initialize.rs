@@ -1,9 +1,9 @@
-use substreams_ethereum::pb::eth::v2::StorageChange;
+use substreams_ethereum::pb::eth::v2::Call;
 
 use crate::{
     abi::pool::events::Initialize,
     pb::uniswap::v3::Pool,
-    storage::{constants::TRACKED_SLOTS, pool_storage::UniswapPoolStorage},
+    storage::{constants::StorageLayout, pool_storage::UniswapPoolStorage},
 };
 use substreams_helper::storage_change::StorageChangesFilter;
 use tycho_substreams::prelude::Attribute;
@@ -13,20 +13,21 @@ use super::{BalanceDelta, EventTrait};
 impl EventTrait for Initialize {
     fn get_changed_attributes(
         &self,
-        storage_changes: &[StorageChange],
-        pool_address: &[u8; 20],
+        call: &Call,
+        pool: &Pool,
+        layout: &StorageLayout,
     ) -> Vec<Attribute> {
-        let storage_vec = storage_changes.to_vec();
+        let storage_vec = call.storage_changes.to_vec();
 
         let filtered_storage_changes = storage_vec
-            .filter_by_address(pool_address)
+            .filter_by_address(&pool.address_bytes())
             .into_iter()
             .cloned()
             .collect();
 
-        let pool_storage = UniswapPoolStorage::new(&filtered_storage_changes);
+        let pool_storage = UniswapPoolStorage::new(&filtered_storage_changes, layout);
 
-        pool_storage.get_changed_attributes(TRACKED_SLOTS.to_vec().iter().collect())
+        pool_storage.get_tracked_attributes()
     }
 
     fn get_balance_delta(&self, _pool: &Pool, _ordinal: u64) -> Vec<BalanceDelta> {
@@ -1,13 +1,18 @@
 use substreams_ethereum::{
-    pb::eth::v2::{Log, StorageChange},
+    pb::eth::v2::{Call, Log},
     Event,
 };
 
 use crate::{
-    abi::pool::events::{
-        Burn, Collect, CollectProtocol, Flash, Initialize, Mint, SetFeeProtocol, Swap,
+    abi::{
+        pancake_v3_pool::events::{SetFeeProtocol as PancakeSetFeeProtocol, Swap as PancakeSwap},
+        pool::events::{
+            Burn, Collect, CollectProtocol, Flash, IncreaseObservationCardinalityNext, Initialize,
+            Mint, SetFeeProtocol, Swap,
+        },
     },
     pb::uniswap::v3::{BalanceDelta, Pool},
+    storage::constants::StorageLayout,
 };
 use tycho_substreams::prelude::Attribute;
 
@@ -15,28 +20,40 @@ pub mod burn;
 pub mod collect;
 pub mod collect_fee_protocol;
 pub mod flash;
+pub mod increase_observation_cardinality_next;
 pub mod initialize;
 pub mod mint;
 pub mod set_fee_protocol;
 pub mod swap;
 
+impl Pool {
+    pub fn address_bytes(&self) -> [u8; 20] {
+        self.address
+            .as_slice()
+            .try_into()
+            .expect("Pool address is not 20 bytes long")
+    }
+}
+
 /// A trait for extracting changed attributes and balance from an event.
 pub trait EventTrait {
-    /// Get all relevant changed attributes from the `[StorageChange]`.
+    /// Get all relevant changed attributes from the storage changes of the call emitting the event.
     /// If an attribute is changed multiple times, only the last state will be returned.
     ///
     /// # Arguments
     ///
-    /// * `storage_changes` - A slice of `StorageChange` that indicates the changes in storage.
+    /// * `call` - The call emitting the event, with its storage changes and keccak preimages.
     /// * `pool` - Reference to the `Pool`.
+    /// * `layout` - The storage layout of the pool contract.
     ///
     /// # Returns
     ///
     /// A vector of `Attribute` that represents the changed attributes.
     fn get_changed_attributes(
         &self,
-        storage_changes: &[StorageChange],
-        pool_address: &[u8; 20],
+        call: &Call,
+        pool: &Pool,
+        layout: &StorageLayout,
     ) -> Vec<Attribute>;
 
     /// Get all balance deltas from the event.
@@ -54,6 +71,9 @@ pub trait EventTrait {
 }
 
 /// Represent every events of a UniswapV3 pool.
+///
+/// Includes the events of forks which changed an event's signature. Their topics differ from the
+/// original ones, so all variants can be decoded regardless of the fork being indexed.
 pub enum EventType {
     Initialize(Initialize),
     Swap(Swap),
@@ -63,6 +83,9 @@ pub enum EventType {
     Collect(Collect),
     SetFeeProtocol(SetFeeProtocol),
     CollectProtocol(CollectProtocol),
+    IncreaseObservationCardinalityNext(IncreaseObservationCardinalityNext),
+    PancakeSwap(PancakeSwap),
+    PancakeSetFeeProtocol(PancakeSetFeeProtocol),
 }
 
 impl EventType {
@@ -76,6 +99,9 @@ impl EventType {
             EventType::Collect(e) => e,
             EventType::SetFeeProtocol(e) => e,
             EventType::CollectProtocol(e) => e,
+            EventType::IncreaseObservationCardinalityNext(e) => e,
+            EventType::PancakeSwap(e) => e,
+            EventType::PancakeSetFeeProtocol(e) => e,
         }
     }
 }
@@ -99,6 +125,10 @@ pub fn decode_event(event: &Log) -> Option<EventType> {
         Collect::match_and_decode(event).map(EventType::Collect),
         SetFeeProtocol::match_and_decode(event).map(EventType::SetFeeProtocol),
         CollectProtocol::match_and_decode(event).map(EventType::CollectProtocol),
+        IncreaseObservationCardinalityNext::match_and_decode(event)
+            .map(EventType::IncreaseObservationCardinalityNext),
+        PancakeSwap::match_and_decode(event).map(EventType::PancakeSwap),
+        PancakeSetFeeProtocol::match_and_decode(event).map(EventType::PancakeSetFeeProtocol),
     ]
     .into_iter()
     .find_map(std::convert::identity)
@@ -109,21 +139,23 @@ pub fn decode_event(event: &Log) -> Option<EventType> {
 /// # Arguments
 ///
 /// * `event` - A reference to the `Log`.
-/// * `storage_changes` - A slice of `StorageChange` that indicates the changes in storage.
+/// * `call` - The call emitting the log, with its storage changes and keccak preimages.
 /// * `pool` - Reference to the `Pool` structure.
+/// * `layout` - The storage layout of the pool contract.
 ///
 /// # Returns
 ///
 /// A vector of `Attribute` that represents the changed attributes.
 pub fn get_log_changed_attributes(
     event: &Log,
-    storage_changes: &[StorageChange],
-    pool_address: &[u8; 20],
+    call: &Call,
+    pool: &Pool,
+    layout: &StorageLayout,
 ) -> Vec<Attribute> {
     decode_event(event)
         .map(|e| {
             e.as_event_trait()
-                .get_changed_attributes(storage_changes, pool_address)
+                .get_changed_attributes(call, pool, layout)
         })
         .unwrap_or_default()
 }
@@ -1,9 +1,12 @@
-use substreams_ethereum::pb::eth::v2::StorageChange;
+use substreams_ethereum::pb::eth::v2::Call;
 
 use crate::{
-    abi::pool::events::SetFeeProtocol,
+    abi::{
+        pancake_v3_pool::events::SetFeeProtocol as PancakeSetFeeProtocol,
+        pool::events::SetFeeProtocol,
+    },
     pb::uniswap::v3::Pool,
-    storage::{constants::TRACKED_SLOTS, pool_storage::UniswapPoolStorage},
+    storage::{constants::StorageLayout, pool_storage::UniswapPoolStorage},
 };
 use substreams_helper::storage_change::StorageChangesFilter;
 use tycho_substreams::prelude::Attribute;
@@ -13,23 +16,48 @@ use super::{BalanceDelta, EventTrait};
 impl EventTrait for SetFeeProtocol {
     fn get_changed_attributes(
         &self,
-        storage_changes: &[StorageChange],
-        pool_address: &[u8; 20],
+        call: &Call,
+        pool: &Pool,
+        layout: &StorageLayout,
     ) -> Vec<Attribute> {
-        let storage_vec = storage_changes.to_vec();
-
-        let filtered_storage_changes = storage_vec
-            .filter_by_address(pool_address)
-            .into_iter()
-            .cloned()
-            .collect();
+        get_fee_protocol_changed_attributes(call, pool, layout)
+    }
 
-        let pool_storage = UniswapPoolStorage::new(&filtered_storage_changes);
+    fn get_balance_delta(&self, _pool: &Pool, _ordinal: u64) -> Vec<BalanceDelta> {
+        vec![]
+    }
+}
 
-        pool_storage.get_changed_attributes(TRACKED_SLOTS.to_vec().iter().collect())
+/// PancakeSwap V3 widens the protocol fees to 32 bits.
+impl EventTrait for PancakeSetFeeProtocol {
+    fn get_changed_attributes(
+        &self,
+        call: &Call,
+        pool: &Pool,
+        layout: &StorageLayout,
+    ) -> Vec<Attribute> {
+        get_fee_protocol_changed_attributes(call, pool, layout)
     }
 
     fn get_balance_delta(&self, _pool: &Pool, _ordinal: u64) -> Vec<BalanceDelta> {
         vec![]
     }
 }
+
+fn get_fee_protocol_changed_attributes(
+    call: &Call,
+    pool: &Pool,
+    layout: &StorageLayout,
+) -> Vec<Attribute> {
+    let storage_vec = call.storage_changes.to_vec();
+
+    let filtered_storage_changes = storage_vec
+        .filter_by_address(&pool.address_bytes())
+        .into_iter()
+        .cloned()
+        .collect();
+
+    let pool_storage = UniswapPoolStorage::new(&filtered_storage_changes, layout);
+
+    pool_storage.get_tracked_attributes()
+}
@@ -1,12 +1,12 @@
 use num_bigint::Sign;
 use substreams::scalar::BigInt;
-use substreams_ethereum::pb::eth::v2::StorageChange;
+use substreams_ethereum::pb::eth::v2::Call;
 use substreams_helper::storage_change::StorageChangesFilter;
 
 use crate::{
-    abi::pool::events::Swap,
+    abi::{pancake_v3_pool::events::Swap as PancakeSwap, pool::events::Swap},
     pb::uniswap::v3::Pool,
-    storage::{constants::TRACKED_SLOTS, pool_storage::UniswapPoolStorage},
+    storage::{constants::StorageLayout, pool_storage::UniswapPoolStorage},
 };
 use tycho_substreams::prelude::Attribute;
 
@@ -15,37 +15,70 @@ use super::{BalanceDelta, EventTrait};
 impl EventTrait for Swap {
     fn get_changed_attributes(
         &self,
-        storage_changes: &[StorageChange],
-        pool_address: &[u8; 20],
+        call: &Call,
+        pool: &Pool,
+        layout: &StorageLayout,
     ) -> Vec<Attribute> {
-        let storage_vec = storage_changes.to_vec();
-
-        let filtered_storage_changes = storage_vec
-            .filter_by_address(pool_address)
-            .into_iter()
-            .cloned()
-            .collect();
+        get_swap_changed_attributes(call, pool, layout)
+    }
 
-        let pool_storage = UniswapPoolStorage::new(&filtered_storage_changes);
+    fn get_balance_delta(&self, pool: &Pool, ordinal: u64) -> Vec<BalanceDelta> {
+        get_swap_balance_delta(pool, ordinal, &self.amount0, &self.amount1)
+    }
+}
 
-        pool_storage.get_changed_attributes(TRACKED_SLOTS.to_vec().iter().collect())
+/// PancakeSwap V3 additionally emits the protocol fees collected by the swap.
+impl EventTrait for PancakeSwap {
+    fn get_changed_attributes(
+        &self,
+        call: &Call,
+        pool: &Pool,
+        layout: &StorageLayout,
+    ) -> Vec<Attribute> {
+        get_swap_changed_attributes(call, pool, layout)
     }
 
     fn get_balance_delta(&self, pool: &Pool, ordinal: u64) -> Vec<BalanceDelta> {
-        let create_balance_delta = |token_address: Vec<u8>, amount: BigInt| -> BalanceDelta {
-            let (amount_sign, amount_bytes) = amount.clone().to_bytes_le();
-            BalanceDelta {
-                token_address,
-                amount: amount_bytes,
-                sign: amount_sign == Sign::Plus,
-                pool_address: pool.address.clone(),
-                ordinal,
-            }
-        };
-
-        vec![
-            create_balance_delta(pool.token0.clone(), self.amount0.clone()),
-            create_balance_delta(pool.token1.clone(), self.amount1.clone()),
-        ]
+        get_swap_balance_delta(pool, ordinal, &self.amount0, &self.amount1)
     }
 }
+
+fn get_swap_changed_attributes(call: &Call, pool: &Pool, layout: &StorageLayout) -> Vec<Attribute> {
+    let storage_vec = call.storage_changes.to_vec();
+
+    let filtered_storage_changes = storage_vec
+        .filter_by_address(&pool.address_bytes())
+        .into_iter()
+        .cloned()
+        .collect();
+
+    let pool_storage = UniswapPoolStorage::new(&filtered_storage_changes, layout);
+
+    let mut changed_attributes = pool_storage.get_tracked_attributes();
+    changed_attributes.extend(pool_storage.get_crossed_ticks_changes(&call.keccak_preimages));
+
+    changed_attributes
+}
+
+fn get_swap_balance_delta(
+    pool: &Pool,
+    ordinal: u64,
+    amount0: &BigInt,
+    amount1: &BigInt,
+) -> Vec<BalanceDelta> {
+    let create_balance_delta = |token_address: Vec<u8>, amount: BigInt| -> BalanceDelta {
+        let (amount_sign, amount_bytes) = amount.clone().to_bytes_le();
+        BalanceDelta {
+            token_address,
+            amount: amount_bytes,
+            sign: amount_sign == Sign::Plus,
+            pool_address: pool.address.clone(),
+            ordinal,
+        }
+    };
+
+    vec![
+        create_balance_delta(pool.token0.clone(), amount0.clone()),
+        create_balance_delta(pool.token1.clone(), amount1.clone()),
+    ]
+}
@@ -1,36 +1,231 @@
 use std::str::FromStr;
 
+use anyhow::{bail, Context, Result};
 use ethabi::ethereum_types::Address;
-use substreams::scalar::BigInt;
-use substreams_ethereum::pb::eth::v2::{self as eth};
+use hex_literal::hex;
+use serde::Deserialize;
+use substreams::{
+    scalar::BigInt,
+    store::{StoreGet, StoreGetString},
+};
+use substreams_ethereum::{pb::eth::v2 as eth, Event};
 
-use substreams_helper::{event_handler::EventHandler, hex::Hexable};
+use substreams_helper::hex::Hexable;
 
-use crate::abi::factory::events::PoolCreated;
+use crate::abi::factory::events::{FeeAmountEnabled, OwnerChanged};
 use tycho_substreams::prelude::*;
 
+/// Params of `map_pools_created`, e.g.
+/// `factory_address=0BFbCF9fa4f9C56B0F40a671Ad40E0805A091865&
+/// protocol_type_name=pancakeswap_v3_pool&chain=ethereum`.
+///
+/// Without a factory address, Uniswap's own factory on the given chain is indexed. For backwards
+/// compatibility, a bare factory address is accepted as well.
+///
+/// Pools are created from Uniswap V3's `PoolCreated(token0, token1, fee, tickSpacing, pool)` event
+/// by default. Forks announcing new pools with another event configure its topic 0 as
+/// `pool_created_topic` and where to find the pool's fields as `pool_created_fields`, see
+/// [`PoolCreatedEvent`].
+#[derive(Debug, Deserialize, PartialEq)]
+pub(crate) struct Params {
+    /// Hex encoded address of the factory.
+    factory_address: Option<String>,
+    /// Protocol type name of the created pools.
+    #[serde(default = "default_protocol_type_name")]
+    protocol_type_name: String,
+    /// Chain the factory is deployed on.
+    #[serde(default = "default_chain")]
+    chain: String,
+    /// Hex encoded topic 0 of the event announcing new pools.
+    pool_created_topic: Option<String>,
+    /// Comma separated positions of the `token0`, `token1`, `fee`, `tick_spacing` and `pool`
+    /// fields within the event announcing new pools, e.g. `topic1,topic2,topic3,data0,data1`.
+    pool_created_fields: Option<String>,
+}
+
+impl Params {
+    pub(crate) fn parse(input: &str) -> Result<Self> {
+        if !input.contains('=') {
+            return Ok(Params {
+                factory_address: Some(input.to_string()),
+                protocol_type_name: default_protocol_type_name(),
+                chain: default_chain(),
+                pool_created_topic: None,
+                pool_created_fields: None,
+            });
+        }
+        serde_qs::from_str(input).context("Unable to deserialize params")
+    }
+
+    /// Returns the configured factory, or Uniswap's deployment on the configured chain.
+    pub(crate) fn factory_address(&self) -> Result<Address> {
+        let address = match (&self.factory_address, self.chain.as_str()) {
+            (Some(address), _) => address.as_str(),
+            (None, "ethereum" | "arbitrum" | "optimism" | "polygon") => {
+                "1F98431c8aD98523631AE4a59f267346ea31F984"
+            }
+            (None, "base") => "33128a8fC17869897dcE68Ed026d694621f6FDfD",
+            (None, "bsc") => "dB1d10011AD0Ff90774D0C6Bb92e5C5c8b4461F7",
+            (None, chain) => bail!("No factory address configured for chain {chain}"),
+        };
+        Address::from_str(address).context("Invalid factory address")
+    }
+
+    /// Returns the event announcing new pools, Uniswap V3's `PoolCreated` unless configured.
+    pub(crate) fn pool_created_event(&self) -> Result<PoolCreatedEvent> {
+        let topic = match &self.pool_created_topic {
+            Some(topic) => {
+                hex::decode(topic.trim_start_matches("0x")).context("Invalid pool_created_topic")?
+            }
+            None => POOL_CREATED_TOPIC.to_vec(),
+        };
+        let fields = self
+            .pool_created_fields
+            .as_deref()
+            .unwrap_or("topic1,topic2,topic3,data0,data1")
+            .split(',')
+            .map(EventField::from_str)
+            .collect::<Result<Vec<_>>>()?;
+        let [token0, token1, fee, tick_spacing, pool] = fields[..] else {
+            bail!("Expected 5 pool_created_fields, got {}", fields.len());
+        };
+        Ok(PoolCreatedEvent { topic, token0, token1, fee, tick_spacing, pool })
+    }
+}
+
+/// keccak256("PoolCreated(address,address,uint24,int24,address)")
+const POOL_CREATED_TOPIC: [u8; 32] =
+    hex!("783cca1c0412dd0d695e784568c96da2e9c22ff989357a2e8b1d9b2b4e6b7118");
+
+/// Position of a field within an event, either an indexed topic or a word of the data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum EventField {
+    Topic(usize),
+    Data(usize),
+}
+
+impl FromStr for EventField {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let parse_index = |index: &str| {
+            index
+                .parse::<usize>()
+                .with_context(|| format!("Invalid event field {input}"))
+        };
+        match (input.strip_prefix("topic"), input.strip_prefix("data")) {
+            (Some(index), _) => match parse_index(index)? {
+                index @ 1..=3 => Ok(EventField::Topic(index)),
+                _ => bail!("Invalid event field {input}, topics range from topic1 to topic3"),
+            },
+            (_, Some(index)) => Ok(EventField::Data(parse_index(index)?)),
+            _ => bail!("Invalid event field {input}, expected topicN or dataN"),
+        }
+    }
+}
+
+impl EventField {
+    /// Returns the 32 byte word of the field, if the log contains it.
+    fn word<'a>(&self, log: &'a eth::Log) -> Option<&'a [u8]> {
+        let word = match self {
+            EventField::Topic(index) => log.topics.get(*index)?.as_slice(),
+            EventField::Data(index) => log
+                .data
+                .get(index * 32..(index + 1) * 32)?,
+        };
+        (word.len() == 32).then_some(word)
+    }
+}
+
+/// The event announcing new pools, with the positions of the pool's fields.
+#[derive(Debug, PartialEq)]
+pub(crate) struct PoolCreatedEvent {
+    topic: Vec<u8>,
+    token0: EventField,
+    token1: EventField,
+    fee: EventField,
+    tick_spacing: EventField,
+    pool: EventField,
+}
+
+/// A pool announced by the factory.
+#[derive(Debug, PartialEq)]
+pub(crate) struct CreatedPool {
+    pub token0: Vec<u8>,
+    pub token1: Vec<u8>,
+    pub fee: BigInt,
+    pub tick_spacing: BigInt,
+    pub pool: Vec<u8>,
+}
+
+impl PoolCreatedEvent {
+    /// Decodes the pool if the log announces a new pool.
+    pub(crate) fn decode(&self, log: &eth::Log) -> Option<CreatedPool> {
+        if log.topics.first() != Some(&self.topic) {
+            return None;
+        }
+        let address = |field: EventField| Some(field.word(log)?[12..].to_vec());
+        Some(CreatedPool {
+            token0: address(self.token0)?,
+            token1: address(self.token1)?,
+            fee: BigInt::from_unsigned_bytes_be(self.fee.word(log)?),
+            tick_spacing: BigInt::from_signed_bytes_be(self.tick_spacing.word(log)?),
+            pool: address(self.pool)?,
+        })
+    }
+}
+
+fn default_protocol_type_name() -> String {
+    "uniswap_v3_pool".to_string()
+}
+
+fn default_chain() -> String {
+    "ethereum".to_string()
+}
+
 #[substreams::handlers::map]
 pub fn map_pools_created(
     params: String,
     block: eth::Block,
+    factory_store: StoreGetString,
 ) -> Result<BlockChanges, substreams::errors::Error> {
     let mut new_pools: Vec<TransactionChanges> = vec![];
-    let factory_address = params.as_str();
+    let params = Params::parse(&params)?;
+    let factory_address = params.factory_address()?;
 
-    get_new_pools(&block, &mut new_pools, factory_address);
+    let pool_created = params.pool_created_event()?;
+
+    get_new_pools(&block, &mut new_pools, &params, factory_address, &pool_created);
+    let factory_key = format!(
+        "Factory:{}",
+        factory_address
+            .as_bytes()
+            .to_vec()
+            .to_hex()
+    );
+    get_factory_changes(&block, &mut new_pools, &params, factory_address.as_bytes(), |ordinal| {
+        factory_store.get_at(ordinal, &factory_key)
+    });
 
     Ok(BlockChanges { block: Some((&block).into()), changes: new_pools })
 }
 
-// Extract new pools from PoolCreated events
+// Extract new pools from the factory's pool creation events
 fn get_new_pools(
     block: &eth::Block,
     new_pools: &mut Vec<TransactionChanges>,
-    factory_address: &str,
+    params: &Params,
+    factory_address: Address,
+    pool_created: &PoolCreatedEvent,
 ) {
-    // Extract new pools from PoolCreated events
-    let mut on_pool_created = |event: PoolCreated, _tx: &eth::TransactionTrace, _log: &eth::Log| {
-        let tycho_tx: Transaction = _tx.into();
+    for log in block
+        .logs()
+        .filter(|log| log.address() == factory_address.as_bytes())
+    {
+        let Some(event) = pool_created.decode(log.log) else {
+            continue;
+        };
+        let tycho_tx: Transaction = log.receipt.transaction.into();
 
         new_pools.push(TransactionChanges {
             tx: Some(tycho_tx.clone()),
@@ -53,6 +248,31 @@ fn get_new_pools(
                         value: BigInt::from(0).to_signed_bytes_le(),
                         change: ChangeType::Creation.into(),
                     },
+                    Attribute {
+                        name: "observation_index".to_string(),
+                        value: BigInt::from(0).to_signed_bytes_le(),
+                        change: ChangeType::Creation.into(),
+                    },
+                    Attribute {
+                        name: "observation_cardinality".to_string(),
+                        value: BigInt::from(0).to_signed_bytes_le(),
+                        change: ChangeType::Creation.into(),
+                    },
+                    Attribute {
+                        name: "observation_cardinality_next".to_string(),
+                        value: BigInt::from(0).to_signed_bytes_le(),
+                        change: ChangeType::Creation.into(),
+                    },
+                    Attribute {
+                        name: "fee_growth_global0_x128".to_string(),
+                        value: BigInt::from(0).to_signed_bytes_le(),
+                        change: ChangeType::Creation.into(),
+                    },
+                    Attribute {
+                        name: "fee_growth_global1_x128".to_string(),
+                        value: BigInt::from(0).to_signed_bytes_le(),
+                        change: ChangeType::Creation.into(),
+                    },
                 ],
             }],
             component_changes: vec![ProtocolComponent {
@@ -78,7 +298,7 @@ fn get_new_pools(
                 ],
                 change: i32::from(ChangeType::Creation),
                 protocol_type: Option::from(ProtocolType {
-                    name: "uniswap_v3_pool".to_string(),
+                    name: params.protocol_type_name.clone(),
                     financial_type: FinancialType::Swap.into(),
                     attribute_schema: vec![],
                     implementation_type: ImplementationType::Custom.into(),
@@ -87,12 +307,351 @@ fn get_new_pools(
             }],
             balance_changes: vec![],
         })
-    };
+    }
+}
+
+/// Extracts the state of the factory itself: its owner and the enabled fee tiers.
+///
+/// The factory is registered as a component without tokens, created along with the first factory
+/// event seen, see `store_factory`. Its state is emitted as entity changes of that component, each
+/// enabled fee is mapped to its tick spacing as `fee_amount_tick_spacing/{fee}`. Forks may enable
+/// tiers Uniswap doesn't have, consumers should rely on this state instead of a fixed list of
+/// tiers.
+fn get_factory_changes(
+    block: &eth::Block,
+    changes: &mut Vec<TransactionChanges>,
+    params: &Params,
+    factory: &[u8],
+    first_factory_event: impl Fn(u64) -> Option<String>,
+) {
+    let factory_id = factory.to_vec().to_hex();
+    for log in block
+        .logs()
+        .filter(|log| log.address() == factory)
+    {
+        let Some(attribute) = factory_attribute(log.log) else {
+            continue;
+        };
+
+        let tx: Transaction = log.receipt.transaction.into();
+        let tx_changes = match changes.iter().position(|tx_changes| {
+            tx_changes
+                .tx
+                .as_ref()
+                .is_some_and(|changes_tx| changes_tx.index == tx.index)
+        }) {
+            Some(index) => &mut changes[index],
+            None => {
+                changes.push(TransactionChanges { tx: Some(tx.clone()), ..Default::default() });
+                changes.last_mut().unwrap()
+            }
+        };
+        if first_factory_event(log.ordinal()) == Some(factory_event_id(block.number, log.ordinal()))
+        {
+            tx_changes
+                .component_changes
+                .push(factory_component(&factory_id, params, tx));
+        }
+        tx_changes
+            .entity_changes
+            .push(EntityChanges { component_id: factory_id.clone(), attributes: vec![attribute] });
+    }
+
+    changes.sort_by_key(|tx_changes| {
+        tx_changes
+            .tx
+            .as_ref()
+            .map(|tx| tx.index)
+    });
+}
+
+/// Identifies a factory event across blocks, ordinals restart with every block.
+pub(crate) fn factory_event_id(block_number: u64, ordinal: u64) -> String {
+    format!("{block_number}:{ordinal}")
+}
+
+/// Decodes a factory event into the attribute of the factory component it updates.
+pub(crate) fn factory_attribute(log: &eth::Log) -> Option<Attribute> {
+    if let Some(event) = FeeAmountEnabled::match_and_decode(log) {
+        Some(Attribute {
+            name: format!("fee_amount_tick_spacing/{}", event.fee),
+            value: event.tick_spacing.to_signed_bytes_le(),
+            change: ChangeType::Update.into(),
+        })
+    } else {
+        OwnerChanged::match_and_decode(log).map(|event| Attribute {
+            name: "owner".to_string(),
+            value: event.new_owner,
+            change: ChangeType::Update.into(),
+        })
+    }
+}
+
+/// The factory component, e.g. of protocol type `uniswap_v3_factory` for `uniswap_v3_pool`s.
+fn factory_component(id: &str, params: &Params, tx: Transaction) -> ProtocolComponent {
+    ProtocolComponent {
+        id: id.to_string(),
+        tokens: vec![],
+        contracts: vec![],
+        static_att: vec![],
+        change: i32::from(ChangeType::Creation),
+        protocol_type: Some(ProtocolType {
+            name: format!(
+                "{}_factory",
+                params
+                    .protocol_type_name
+                    .trim_end_matches("_pool")
+            ),
+            financial_type: FinancialType::Swap.into(),
+            attribute_schema: vec![],
+            implementation_type: ImplementationType::Custom.into(),
+        }),
+        tx: Some(tx),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params_bare_factory_address() {
+        let params = Params::parse("1F98431c8aD98523631AE4a59f267346ea31F984").unwrap();
+
+        assert_eq!(
+            params,
+            Params {
+                factory_address: Some("1F98431c8aD98523631AE4a59f267346ea31F984".to_string()),
+                protocol_type_name: "uniswap_v3_pool".to_string(),
+                chain: "ethereum".to_string(),
+                pool_created_topic: None,
+                pool_created_fields: None,
+            }
+        );
+    }
+
+    fn word(value: &[u8]) -> Vec<u8> {
+        let mut word = vec![0; 32];
+        word[32 - value.len()..].copy_from_slice(value);
+        word
+    }
+
+    #[test]
+    fn test_pool_created_event_default() {
+        let event = Params::parse("chain=ethereum")
+            .unwrap()
+            .pool_created_event()
+            .unwrap();
+        let log = eth::Log {
+            topics: vec![
+                ethabi::long_signature(
+                    "PoolCreated",
+                    &[
+                        ethabi::ParamType::Address,
+                        ethabi::ParamType::Address,
+                        ethabi::ParamType::Uint(24),
+                        ethabi::ParamType::Int(24),
+                        ethabi::ParamType::Address,
+                    ],
+                )
+                .0
+                .to_vec(),
+                word(&[0x0a; 20]),
+                word(&[0x0b; 20]),
+                word(&[0x01, 0xf4]),
+            ],
+            data: [word(&[0x0a]), word(&[0xaa; 20])].concat(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            event.decode(&log),
+            Some(CreatedPool {
+                token0: vec![0x0a; 20],
+                token1: vec![0x0b; 20],
+                fee: BigInt::from(500),
+                tick_spacing: BigInt::from(10),
+                pool: vec![0xaa; 20],
+            })
+        );
+        assert_eq!(event.decode(&eth::Log { topics: vec![vec![0; 32]], ..log }), None);
+    }
+
+    #[test]
+    fn test_pool_created_event_configured() {
+        // A fork announcing `PoolDeployed(address indexed pool, address token0, address token1,
+        // int24 tickSpacing, uint24 fee)`.
+        let event = Params::parse(
+            "pool_created_topic=0x00000000000000000000000000000000000000000000000000000000000000fe&\
+             pool_created_fields=data0,data1,data3,data2,topic1",
+        )
+        .unwrap()
+        .pool_created_event()
+        .unwrap();
+        let log = eth::Log {
+            topics: vec![word(&[0xfe]), word(&[0xaa; 20])],
+            data: [word(&[0x0a; 20]), word(&[0x0b; 20]), vec![0xff; 32], word(&[0x64])].concat(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            event.decode(&log),
+            Some(CreatedPool {
+                token0: vec![0x0a; 20],
+                token1: vec![0x0b; 20],
+                fee: BigInt::from(100),
+                tick_spacing: BigInt::from(-1),
+                pool: vec![0xaa; 20],
+            })
+        );
+        assert_eq!(event.decode(&eth::Log { data: vec![], ..log }), None);
+    }
+
+    #[test]
+    fn test_pool_created_event_invalid_fields() {
+        let event = |fields: &str| {
+            Params::parse(&format!("pool_created_fields={fields}"))
+                .unwrap()
+                .pool_created_event()
+        };
+
+        assert!(event("topic1,topic2,topic3,data0").is_err());
+        assert!(event("topic1,topic2,topic4,data0,data1").is_err());
+        assert!(event("topic1,topic2,fee,data0,data1").is_err());
+    }
+
+    #[test]
+    fn test_params() {
+        let params = Params::parse(
+            "factory_address=0BFbCF9fa4f9C56B0F40a671Ad40E0805A091865&protocol_type_name=pancakeswap_v3_pool",
+        )
+        .unwrap();
+
+        assert_eq!(
+            params.factory_address().unwrap(),
+            Address::from_str("0BFbCF9fa4f9C56B0F40a671Ad40E0805A091865").unwrap()
+        );
+        assert_eq!(params.protocol_type_name, "pancakeswap_v3_pool");
+    }
+
+    #[test]
+    fn test_params_chain_default_factory() {
+        let params = Params::parse("chain=base").unwrap();
+
+        assert_eq!(
+            params.factory_address().unwrap(),
+            Address::from_str("33128a8fC17869897dcE68Ed026d694621f6FDfD").unwrap()
+        );
+        assert_eq!(params.protocol_type_name, "uniswap_v3_pool");
+        assert!(Params::parse("chain=unknown")
+            .unwrap()
+            .factory_address()
+            .is_err());
+    }
+
+    fn factory_log(event: &str, topics: [[u8; 32]; 2]) -> eth::Log {
+        let param_types = match event {
+            "FeeAmountEnabled" => vec![ethabi::ParamType::Uint(24), ethabi::ParamType::Int(24)],
+            _ => vec![ethabi::ParamType::Address, ethabi::ParamType::Address],
+        };
+        eth::Log {
+            topics: [ethabi::long_signature(event, &param_types).0, topics[0], topics[1]]
+                .into_iter()
+                .map(|topic| topic.to_vec())
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_factory_attribute() {
+        let mut fee = [0u8; 32];
+        fee[29..].copy_from_slice(&[0x00, 0x00, 0x64]);
+        let tick_spacing = [0xffu8; 32];
+        let mut new_owner = [0u8; 32];
+        new_owner[12..].copy_from_slice(&[0xaa; 20]);
+
+        let fee_amount = factory_attribute(&factory_log("FeeAmountEnabled", [fee, tick_spacing]));
+        let owner = factory_attribute(&factory_log("OwnerChanged", [[0u8; 32], new_owner]));
+
+        assert_eq!(
+            fee_amount,
+            Some(Attribute {
+                name: "fee_amount_tick_spacing/100".to_string(),
+                value: BigInt::from(-1).to_signed_bytes_le(),
+                change: ChangeType::Update.into(),
+            })
+        );
+        assert_eq!(
+            owner,
+            Some(Attribute {
+                name: "owner".to_string(),
+                value: vec![0xaa; 20],
+                change: ChangeType::Update.into(),
+            })
+        );
+        assert_eq!(factory_attribute(&eth::Log::default()), None);
+    }
+
+    #[test]
+    fn test_factory_component_protocol_type() {
+        let params = Params::parse("protocol_type_name=pancakeswap_v3_pool").unwrap();
+
+        let component = factory_component("0xfac", &params, Transaction::default());
+
+        assert_eq!(component.protocol_type.unwrap().name, "pancakeswap_v3_factory");
+        assert!(component.tokens.is_empty());
+    }
+
+    fn owner_changed_block(number: u64, factory: &[u8], ordinal: u64) -> eth::Block {
+        let owner_changed = eth::Log {
+            address: factory.to_vec(),
+            ordinal,
+            ..factory_log("OwnerChanged", [[0u8; 32], [0u8; 32]])
+        };
+        eth::Block {
+            number,
+            transaction_traces: vec![eth::TransactionTrace {
+                status: eth::TransactionTraceStatus::Succeeded.into(),
+                receipt: Some(eth::TransactionReceipt {
+                    logs: vec![owner_changed],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
 
-    let mut eh = EventHandler::new(block);
+    #[test]
+    fn test_factory_component_created_once_across_blocks_with_same_ordinal() {
+        let params = Params::parse("1F98431c8aD98523631AE4a59f267346ea31F984").unwrap();
+        let factory = params.factory_address().unwrap();
+        let first = owner_changed_block(20, factory.as_bytes(), 5);
+        let second = owner_changed_block(21, factory.as_bytes(), 5);
+        let first_factory_event = |_| Some(factory_event_id(20, 5));
 
-    eh.filter_by_address(vec![Address::from_str(factory_address).unwrap()]);
+        let mut first_changes = vec![];
+        get_factory_changes(
+            &first,
+            &mut first_changes,
+            &params,
+            factory.as_bytes(),
+            first_factory_event,
+        );
+        let mut second_changes = vec![];
+        get_factory_changes(
+            &second,
+            &mut second_changes,
+            &params,
+            factory.as_bytes(),
+            first_factory_event,
+        );
 
-    eh.on::<PoolCreated, _>(&mut on_pool_created);
-    eh.handle_events();
+        assert_eq!(first_changes[0].component_changes.len(), 1);
+        assert!(second_changes[0]
+            .component_changes
+            .is_empty());
+        assert_eq!(second_changes[0].entity_changes[0].attributes[0].name, "owner");
+    }
 }
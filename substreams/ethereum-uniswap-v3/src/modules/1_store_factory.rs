@@ -0,0 +1,26 @@
+use substreams::store::{StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsString};
+use substreams_ethereum::pb::eth::v2 as eth;
+use substreams_helper::hex::Hexable;
+
+use crate::modules::map_pool_created::{factory_attribute, factory_event_id, Params};
+
+/// Stores the block and ordinal of the first factory event, with which `map_pools_created`
+/// registers the factory as component.
+#[substreams::handlers::store]
+pub fn store_factory(params: String, block: eth::Block, store: StoreSetIfNotExistsString) {
+    let factory = Params::parse(&params)
+        .and_then(|params| params.factory_address())
+        .expect("Invalid params");
+
+    if let Some(log) = block
+        .logs()
+        .filter(|log| log.address() == factory.as_bytes())
+        .find(|log| factory_attribute(log.log).is_some())
+    {
+        store.set_if_not_exists(
+            log.ordinal(),
+            format!("Factory:{}", factory.as_bytes().to_vec().to_hex()),
+            &factory_event_id(block.number, log.ordinal()),
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use std::str;
 
-use substreams::store::{StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsProto};
+use substreams::{
+    scalar::BigInt,
+    store::{StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsProto},
+};
 
 use crate::pb::uniswap::v3::Pool;
 
@@ -12,12 +15,20 @@ pub fn store_pools(pools_created: BlockChanges, store: StoreSetIfNotExistsProto<
 
     for change in pools_created.changes {
         for component_change in &change.component_changes {
+            // The factory is registered as component without tokens, see `map_pools_created`.
+            if component_change.tokens.is_empty() {
+                continue;
+            }
             let pool_address: &str = &component_change.id;
             let pool: Pool = Pool {
                 address: hex::decode(pool_address.trim_start_matches("0x")).unwrap(),
                 token0: component_change.tokens[0].clone(),
                 token1: component_change.tokens[1].clone(),
                 created_tx_hash: change.tx.as_ref().unwrap().hash.clone(),
+                tick_spacing: component_change
+                    .get_attribute_value("tick_spacing")
+                    .map(|value| BigInt::from_signed_bytes_le(&value).to_i32())
+                    .unwrap_or_default(),
             };
             store.set_if_not_exists(0, format!("{}:{}", "Pool", pool_address), &pool);
         }
@@ -1,3 +1,5 @@
+use anyhow::Context;
+use serde::Deserialize;
 use std::{collections::HashMap, vec};
 use substreams::store::{StoreGet, StoreGetBigInt, StoreGetProto};
 use substreams_ethereum::pb::eth::v2::{self as eth, TransactionTrace};
@@ -7,17 +9,30 @@ use substreams_helper::hex::Hexable;
 use crate::{
     events::{get_log_changed_attributes, get_log_changed_balances},
     pb::uniswap::v3::Pool,
+    storage::constants::LayoutProfile,
 };
 
-use tycho_substreams::prelude::*;
+use tycho_substreams::{aggregation::AggregationMode, prelude::*};
+
+/// Params of `map_pool_events`, besides the aggregation mode.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+struct Params {
+    /// Storage layout of the indexed pools, defaults to Uniswap V3's.
+    #[serde(default)]
+    layout: LayoutProfile,
+}
 
 #[substreams::handlers::map]
 pub fn map_pool_events(
+    params: String,
     block: eth::Block,
     created_pools: BlockChanges,
     pools_store: StoreGetProto<Pool>,
     balance_store: StoreGetBigInt,
 ) -> Result<BlockChanges, substreams::errors::Error> {
+    let event_params: Params =
+        serde_qs::from_str(&params).context("Unable to deserialize params")?;
+    let layout = event_params.layout.storage_layout();
     let mut tx_changes_map: HashMap<Vec<u8>, TransactionChanges> = HashMap::new();
 
     // Add created pools to the tx_changes_map
@@ -40,15 +55,8 @@ pub fn map_pool_events(
             if let Some(pool) =
                 pools_store.get_last(format!("{}:{}", "Pool", &log.address.to_hex()))
             {
-                let changed_attributes = get_log_changed_attributes(
-                    log,
-                    &call_view.call.storage_changes,
-                    pool.address
-                        .clone()
-                        .as_slice()
-                        .try_into()
-                        .expect("Pool address is not 20 bytes long"),
-                );
+                let changed_attributes =
+                    get_log_changed_attributes(log, call_view.call, &pool, layout);
 
                 let mut balance_changes: Vec<BalanceChange> = vec![];
 
@@ -114,7 +122,7 @@ pub fn map_pool_events(
     let block_entity_changes =
         BlockChanges { block: Some(tycho_block), changes: tx_entity_changes };
 
-    Ok(block_entity_changes)
+    Ok(AggregationMode::from_params(&params)?.apply(block_entity_changes))
 }
 
 fn update_tx_changes_map(
@@ -232,3 +240,24 @@ fn merge_balance_changes(
 
     balances.into_values().collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params_default_layout() {
+        let params: Params = serde_qs::from_str("aggregation=transaction").unwrap();
+
+        assert_eq!(params.layout, LayoutProfile::UniswapV3);
+    }
+
+    #[test]
+    fn test_params_layout() {
+        let params: Params =
+            serde_qs::from_str("aggregation=transaction&layout=pancakeswap_v3").unwrap();
+
+        assert_eq!(params.layout, LayoutProfile::PancakeswapV3);
+        assert!(serde_qs::from_str::<Params>("layout=unknown_v3").is_err());
+    }
+}
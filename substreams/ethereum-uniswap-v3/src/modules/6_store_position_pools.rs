@@ -0,0 +1,121 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use substreams::store::{
+    StoreGet, StoreGetProto, StoreNew, StoreSetIfNotExists, StoreSetIfNotExistsProto,
+};
+use substreams_ethereum::{pb::eth::v2 as eth, Event};
+use substreams_helper::hex::Hexable;
+
+use crate::{
+    abi::{nonfungible_position_manager::events::IncreaseLiquidity, pool::events::Mint},
+    pb::uniswap::v3::Pool,
+};
+
+/// Params of the optional position modules, e.g.
+/// `position_manager=C36442b4a4522E871399CD717aBDD847Ab11FE88`.
+///
+/// Positions are only indexed if a position manager is configured.
+#[derive(Debug, Deserialize, PartialEq)]
+pub(crate) struct PositionParams {
+    /// Hex encoded address of the NonfungiblePositionManager.
+    position_manager: Option<String>,
+    /// Base slot of the position manager's `_positions` mapping.
+    #[serde(default = "default_positions_slot")]
+    pub(crate) positions_slot: u8,
+}
+
+impl PositionParams {
+    pub(crate) fn parse(input: &str) -> Result<Self> {
+        serde_qs::from_str(input).context("Unable to deserialize params")
+    }
+
+    /// Returns the address of the position manager, if one is configured.
+    pub(crate) fn position_manager(&self) -> Result<Option<Vec<u8>>> {
+        self.position_manager
+            .as_ref()
+            .map(|address| {
+                hex::decode(address.trim_start_matches("0x"))
+                    .context("Invalid position manager address")
+            })
+            .transpose()
+    }
+}
+
+/// Slot of `_positions` in Uniswap's position manager, following the ERC721 state.
+fn default_positions_slot() -> u8 {
+    12
+}
+
+/// Stores the pool of each position.
+///
+/// Positions only reference their pool by an internal id, so the pool is taken from the `Mint`
+/// event the position manager triggers right before announcing the new liquidity.
+///
+/// Only positions minted from the module's `initialBlock` on are known, the pool of older positions
+/// is never resolved and their changes are skipped by `map_position_changes`. Start the position
+/// modules no later than the position manager's deployment to index all positions.
+#[substreams::handlers::store]
+pub fn store_position_pools(
+    params: String,
+    block: eth::Block,
+    pools_store: StoreGetProto<Pool>,
+    store: StoreSetIfNotExistsProto<Pool>,
+) {
+    let Some(position_manager) = PositionParams::parse(&params)
+        .and_then(|params| params.position_manager())
+        .expect("Invalid position params")
+    else {
+        return;
+    };
+
+    for trx in block.transactions() {
+        let mut minted_pool: Option<Pool> = None;
+        for log in trx
+            .receipt
+            .iter()
+            .flat_map(|receipt| receipt.logs.iter())
+        {
+            if log.address == position_manager {
+                if let Some(event) = IncreaseLiquidity::match_and_decode(log) {
+                    if let Some(pool) = minted_pool.take() {
+                        store.set_if_not_exists(
+                            log.ordinal,
+                            format!("Position:{}", event.token_id),
+                            &pool,
+                        );
+                    }
+                }
+            } else if let Some(event) = Mint::match_and_decode(log) {
+                if event.owner == position_manager {
+                    minted_pool = pools_store.get_last(format!("Pool:{}", log.address.to_hex()));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_params() {
+        let params = PositionParams::parse(
+            "position_manager=C36442b4a4522E871399CD717aBDD847Ab11FE88&aggregation=transaction",
+        )
+        .unwrap();
+
+        assert_eq!(params.positions_slot, 12);
+        assert_eq!(
+            params.position_manager().unwrap(),
+            Some(hex::decode("C36442b4a4522E871399CD717aBDD847Ab11FE88").unwrap())
+        );
+        assert_eq!(
+            PositionParams::parse("")
+                .unwrap()
+                .position_manager()
+                .unwrap(),
+            None
+        );
+    }
+}
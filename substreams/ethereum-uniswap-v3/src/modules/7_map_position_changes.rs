@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+
+use substreams::{
+    scalar::BigInt,
+    store::{StoreGet, StoreGetProto},
+};
+use substreams_ethereum::{pb::eth::v2 as eth, Event};
+use substreams_helper::hex::Hexable;
+
+use crate::{
+    abi::nonfungible_position_manager::events::{
+        Collect, DecreaseLiquidity, IncreaseLiquidity, Transfer,
+    },
+    modules::store_position_pools::PositionParams,
+    pb::uniswap::v3::Pool,
+    storage::position_storage::PositionManagerStorage,
+};
+
+use tycho_substreams::{aggregation::AggregationMode, prelude::*};
+
+/// Emits the positions of the position manager touched in this block.
+///
+/// Positions are emitted as `positions/{token_id}/{field}` attributes of the pool component they
+/// belong to. Besides the position manager's storage fields, `positions/{token_id}/owner` holds the
+/// current owner of the position NFT, the zero address once it's burned.
+///
+/// Positions whose pool is unknown, because they were minted before `store_position_pools`'s
+/// `initialBlock` or belong to a pool that isn't indexed, are skipped.
+#[substreams::handlers::map]
+pub fn map_position_changes(
+    params: String,
+    block: eth::Block,
+    position_pools_store: StoreGetProto<Pool>,
+) -> Result<BlockChanges, substreams::errors::Error> {
+    let position_params = PositionParams::parse(&params)?;
+    let mut transaction_changes: BTreeMap<u64, TransactionChangesBuilder> = BTreeMap::new();
+
+    if let Some(position_manager) = position_params.position_manager()? {
+        for trx in block.transactions() {
+            let storage_changes = trx
+                .calls
+                .iter()
+                .filter(|call| !call.state_reverted && call.address == position_manager)
+                .flat_map(|call| call.storage_changes.iter().cloned())
+                .collect::<Vec<_>>();
+            let storage =
+                PositionManagerStorage::new(&storage_changes, position_params.positions_slot);
+
+            // Collect the touched positions and their latest owner, if transferred.
+            let mut positions: Vec<(BigInt, Option<Vec<u8>>)> = Vec::new();
+            for log in trx
+                .receipt
+                .iter()
+                .flat_map(|receipt| receipt.logs.iter())
+                .filter(|log| log.address == position_manager)
+            {
+                let (token_id, owner) = if let Some(event) = Transfer::match_and_decode(log) {
+                    (event.token_id, Some(event.to))
+                } else if let Some(event) = IncreaseLiquidity::match_and_decode(log) {
+                    (event.token_id, None)
+                } else if let Some(event) = DecreaseLiquidity::match_and_decode(log) {
+                    (event.token_id, None)
+                } else if let Some(event) = Collect::match_and_decode(log) {
+                    (event.token_id, None)
+                } else {
+                    continue;
+                };
+                match positions
+                    .iter_mut()
+                    .find(|(id, _)| *id == token_id)
+                {
+                    Some((_, current_owner)) => {
+                        if owner.is_some() {
+                            *current_owner = owner;
+                        }
+                    }
+                    None => positions.push((token_id, owner)),
+                }
+            }
+
+            for (token_id, owner) in positions {
+                // Skip positions of pools that are not indexed.
+                let Some(pool) = position_pools_store.get_last(format!("Position:{}", token_id))
+                else {
+                    continue;
+                };
+                let mut attributes = storage.get_position_changes(&token_id);
+                if let Some(owner) = owner {
+                    attributes.push(Attribute {
+                        name: format!("positions/{}/owner", token_id),
+                        value: owner,
+                        change: ChangeType::Update.into(),
+                    });
+                }
+                if attributes.is_empty() {
+                    continue;
+                }
+
+                transaction_changes
+                    .entry(trx.index.into())
+                    .or_insert_with(|| TransactionChangesBuilder::new(&trx.into()))
+                    .add_entity_change(&EntityChanges {
+                        component_id: pool.address.to_hex(),
+                        attributes,
+                    });
+            }
+        }
+    }
+
+    Ok(AggregationMode::from_params(&params)?.apply(BlockChanges {
+        block: Some((&block).into()),
+        changes: transaction_changes
+            .into_values()
+            .filter_map(|builder| builder.build())
+            .collect::<Vec<_>>(),
+    }))
+}
@@ -1,10 +1,16 @@
 pub use map_pool_created::map_pools_created;
 pub use map_pool_events::map_pool_events;
+pub use map_position_changes::map_position_changes;
+pub use store_factory::store_factory;
 pub use store_pools::store_pools;
+pub use store_position_pools::store_position_pools;
 
 #[path = "1_map_pool_created.rs"]
 mod map_pool_created;
 
+#[path = "1_store_factory.rs"]
+mod store_factory;
+
 #[path = "2_store_pools.rs"]
 mod store_pools;
 
@@ -16,3 +22,9 @@ mod store_pools_balances;
 
 #[path = "5_map_pool_events.rs"]
 mod map_pool_events;
+
+#[path = "6_store_position_pools.rs"]
+mod store_position_pools;
+
+#[path = "7_map_position_changes.rs"]
+mod map_position_changes;
@@ -10,6 +10,8 @@ pub struct Pool {
     pub token1: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes="vec", tag="4")]
     pub created_tx_hash: ::prost::alloc::vec::Vec<u8>,
+    #[prost(int32, tag="5")]
+    pub tick_spacing: i32,
 }
 /// A change to a pool's balance.
 #[allow(clippy::derive_partial_eq_without_eq)]
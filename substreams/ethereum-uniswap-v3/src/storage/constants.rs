@@ -1,32 +1,58 @@
-use hex_literal::hex;
+use serde::Deserialize;
 
 use super::pool_storage::StorageLocation;
 
-const SLOT0: [u8; 32] = hex!("0000000000000000000000000000000000000000000000000000000000000000");
+/// Returns the storage key of a top level slot.
+const fn slot(index: u8) -> [u8; 32] {
+    let mut slot = [0u8; 32];
+    slot[31] = index;
+    slot
+}
 
-const LIQUIDITY_SLOT: StorageLocation = StorageLocation {
-    name: "liquidity",
-    slot: hex!("0000000000000000000000000000000000000000000000000000000000000004"),
-    offset: 0,
-    number_of_bytes: 16,
-    signed: false,
-};
+/// Locations of the tracked pool state within a pool contract's storage.
+pub struct StorageLayout {
+    /// Slots tracked as state attributes of the pool.
+    pub tracked_slots: &'static [StorageLocation<'static>],
+    /// Base slot of the `ticks` mapping.
+    pub ticks_map_slot: [u8; 32],
+    /// Base slot of the `tickBitmap` mapping.
+    pub tick_bitmap_slot: [u8; 32],
+    /// First slot of the fixed size `observations` array, each observation occupies one slot.
+    pub observations_slot: u8,
+}
 
-const PROTOCOL_FEES_TOKEN_0_SLOT: StorageLocation = StorageLocation {
-    name: "protocol_fees/token0",
-    slot: hex!("0000000000000000000000000000000000000000000000000000000000000003"),
-    offset: 0,
-    number_of_bytes: 16,
-    signed: false,
-};
+/// Number of elements of the `observations` array.
+pub const OBSERVATIONS_LENGTH: u32 = 65535;
 
-const PROTOCOL_FEES_TOKEN_1_SLOT: StorageLocation = StorageLocation {
-    name: "protocol_fees/token1",
-    slot: hex!("0000000000000000000000000000000000000000000000000000000000000003"),
-    offset: 16,
-    number_of_bytes: 16,
-    signed: false,
-};
+/// Storage layout profiles of Uniswap V3 and its forks, selected via the `layout` param of
+/// `map_pool_events`.
+///
+/// The profile only affects how the pool state is decoded. Pools are still discovered through
+/// Uniswap V3's `PoolCreated` event, see `map_pools_created`.
+#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutProfile {
+    #[default]
+    UniswapV3,
+    /// An unmodified fork of Uniswap V3.
+    SushiswapV3,
+    /// Widens `slot0.feeProtocol` to 32 bits, which pushes it into its own slot and shifts all
+    /// following state variables by one slot. The protocol fees are emitted as `fee_protocol0`
+    /// and `fee_protocol1` instead of the packed `fee_protocol`.
+    PancakeswapV3,
+}
+
+impl LayoutProfile {
+    pub fn storage_layout(&self) -> &'static StorageLayout {
+        match self {
+            LayoutProfile::UniswapV3 | LayoutProfile::SushiswapV3 => &UNISWAP_V3_LAYOUT,
+            LayoutProfile::PancakeswapV3 => &PANCAKESWAP_V3_LAYOUT,
+        }
+    }
+}
+
+/// Slot of the `slot0` struct, it's identical for all supported layouts.
+pub(crate) const SLOT0: [u8; 32] = slot(0);
 
 const SQRT_PRICE_X96_SLOT: StorageLocation = StorageLocation {
     name: "sqrt_price_x96",
@@ -39,22 +65,359 @@ const SQRT_PRICE_X96_SLOT: StorageLocation = StorageLocation {
 const CURRENT_TICK_SLOT: StorageLocation =
     StorageLocation { name: "tick", slot: SLOT0, offset: 20, number_of_bytes: 3, signed: true };
 
-const FEE_PROTOCOL_SLOT: StorageLocation = StorageLocation {
-    name: "fee_protocol",
+const OBSERVATION_INDEX_SLOT: StorageLocation = StorageLocation {
+    name: "observation_index",
+    slot: SLOT0,
+    offset: 23,
+    number_of_bytes: 2,
+    signed: false,
+};
+
+const OBSERVATION_CARDINALITY_SLOT: StorageLocation = StorageLocation {
+    name: "observation_cardinality",
     slot: SLOT0,
-    offset: 29,
-    number_of_bytes: 1,
+    offset: 25,
+    number_of_bytes: 2,
     signed: false,
 };
 
-pub(crate) const TICKS_MAP_SLOT: [u8; 32] =
-    hex!("0000000000000000000000000000000000000000000000000000000000000005");
+const OBSERVATION_CARDINALITY_NEXT_SLOT: StorageLocation = StorageLocation {
+    name: "observation_cardinality_next",
+    slot: SLOT0,
+    offset: 27,
+    number_of_bytes: 2,
+    signed: false,
+};
 
-pub(crate) const TRACKED_SLOTS: [StorageLocation; 6] = [
-    LIQUIDITY_SLOT,
-    PROTOCOL_FEES_TOKEN_0_SLOT,
-    PROTOCOL_FEES_TOKEN_1_SLOT,
-    SQRT_PRICE_X96_SLOT,
-    CURRENT_TICK_SLOT,
-    FEE_PROTOCOL_SLOT,
+/// Fields of a `Tick.Info` as (name, slot offset, byte offset, number of bytes, signed), relative
+/// to the tick's base slot.
+pub(crate) const TICK_FIELDS: [(&str, u8, usize, usize, bool); 5] = [
+    ("liquidity-gross", 0, 0, 16, false),
+    ("net-liquidity", 0, 16, 16, true),
+    ("fee-growth-outside-0-x128", 1, 0, 32, false),
+    ("fee-growth-outside-1-x128", 2, 0, 32, false),
+    ("initialized", 3, 31, 1, false),
 ];
+
+/// Fields of a `NonfungiblePositionManager.Position` as (name, slot offset, byte offset, number of
+/// bytes, signed), relative to the position's base slot. The first slot holds the permit nonce and
+/// operator, the pool id shares the second slot with the tick range and liquidity.
+pub(crate) const POSITION_FIELDS: [(&str, u8, usize, usize, bool); 7] = [
+    ("tick_lower", 1, 10, 3, true),
+    ("tick_upper", 1, 13, 3, true),
+    ("liquidity", 1, 16, 16, false),
+    ("fee_growth_inside0_last_x128", 2, 0, 32, false),
+    ("fee_growth_inside1_last_x128", 3, 0, 32, false),
+    ("tokens_owed0", 4, 0, 16, false),
+    ("tokens_owed1", 4, 16, 16, false),
+];
+
+/// Fields of an `Oracle.Observation`, relative to the observation's slot.
+pub(crate) const OBSERVATION_FIELDS: [(&str, usize, usize, bool); 4] = [
+    ("block_timestamp", 0, 4, false),
+    ("tick_cumulative", 4, 7, true),
+    ("seconds_per_liquidity_cumulative_x128", 11, 20, false),
+    ("initialized", 31, 1, false),
+];
+
+const UNISWAP_V3_LAYOUT: StorageLayout = StorageLayout {
+    tracked_slots: &[
+        StorageLocation {
+            name: "fee_growth_global0_x128",
+            slot: slot(1),
+            offset: 0,
+            number_of_bytes: 32,
+            signed: false,
+        },
+        StorageLocation {
+            name: "fee_growth_global1_x128",
+            slot: slot(2),
+            offset: 0,
+            number_of_bytes: 32,
+            signed: false,
+        },
+        StorageLocation {
+            name: "liquidity",
+            slot: slot(4),
+            offset: 0,
+            number_of_bytes: 16,
+            signed: false,
+        },
+        StorageLocation {
+            name: "protocol_fees/token0",
+            slot: slot(3),
+            offset: 0,
+            number_of_bytes: 16,
+            signed: false,
+        },
+        StorageLocation {
+            name: "protocol_fees/token1",
+            slot: slot(3),
+            offset: 16,
+            number_of_bytes: 16,
+            signed: false,
+        },
+        SQRT_PRICE_X96_SLOT,
+        CURRENT_TICK_SLOT,
+        OBSERVATION_INDEX_SLOT,
+        OBSERVATION_CARDINALITY_SLOT,
+        OBSERVATION_CARDINALITY_NEXT_SLOT,
+        // Both protocol fees packed into one byte: `feeProtocol0 + (feeProtocol1 << 4)`.
+        StorageLocation {
+            name: "fee_protocol",
+            slot: slot(0),
+            offset: 29,
+            number_of_bytes: 1,
+            signed: false,
+        },
+    ],
+    ticks_map_slot: slot(5),
+    tick_bitmap_slot: slot(6),
+    observations_slot: 8,
+};
+
+const PANCAKESWAP_V3_LAYOUT: StorageLayout = StorageLayout {
+    tracked_slots: &[
+        StorageLocation {
+            name: "fee_growth_global0_x128",
+            slot: slot(2),
+            offset: 0,
+            number_of_bytes: 32,
+            signed: false,
+        },
+        StorageLocation {
+            name: "fee_growth_global1_x128",
+            slot: slot(3),
+            offset: 0,
+            number_of_bytes: 32,
+            signed: false,
+        },
+        StorageLocation {
+            name: "liquidity",
+            slot: slot(5),
+            offset: 0,
+            number_of_bytes: 16,
+            signed: false,
+        },
+        StorageLocation {
+            name: "protocol_fees/token0",
+            slot: slot(4),
+            offset: 0,
+            number_of_bytes: 16,
+            signed: false,
+        },
+        StorageLocation {
+            name: "protocol_fees/token1",
+            slot: slot(4),
+            offset: 16,
+            number_of_bytes: 16,
+            signed: false,
+        },
+        SQRT_PRICE_X96_SLOT,
+        CURRENT_TICK_SLOT,
+        OBSERVATION_INDEX_SLOT,
+        OBSERVATION_CARDINALITY_SLOT,
+        OBSERVATION_CARDINALITY_NEXT_SLOT,
+        // `feeProtocol0 + (feeProtocol1 << 16)`, unlike Uniswap's nibbles each half is a separate
+        // attribute.
+        StorageLocation {
+            name: "fee_protocol0",
+            slot: slot(1),
+            offset: 0,
+            number_of_bytes: 2,
+            signed: false,
+        },
+        StorageLocation {
+            name: "fee_protocol1",
+            slot: slot(1),
+            offset: 2,
+            number_of_bytes: 2,
+            signed: false,
+        },
+    ],
+    ticks_map_slot: slot(6),
+    tick_bitmap_slot: slot(7),
+    observations_slot: 9,
+};
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use substreams::scalar::BigInt;
+    use substreams_ethereum::pb::eth::v2::StorageChange;
+    use tycho_substreams::prelude::Attribute;
+
+    use super::*;
+    use crate::storage::{
+        pool_storage::UniswapPoolStorage,
+        utils::{add_to_slot, calc_map_slot, left_pad_from_bigint},
+    };
+
+    fn fee_protocol_attributes(profile: LayoutProfile, change: StorageChange) -> Vec<Attribute> {
+        let changes = vec![change];
+        let mut res = UniswapPoolStorage::new(&changes, profile.storage_layout())
+            .get_tracked_attributes()
+            .into_iter()
+            .filter(|attr| attr.name.starts_with("fee_protocol"))
+            .collect::<Vec<_>>();
+        res.sort_by(|a, b| a.name.cmp(&b.name));
+        res
+    }
+
+    #[test]
+    fn test_uniswap_fee_protocol() {
+        let mut new_value = vec![0; 32];
+        new_value[2] = 0x44;
+        let change = StorageChange {
+            key: slot(0).to_vec(),
+            old_value: vec![0; 32],
+            new_value,
+            ..Default::default()
+        };
+
+        let res = fee_protocol_attributes(LayoutProfile::UniswapV3, change.clone());
+
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].name, "fee_protocol");
+        assert_eq!(res[0].value, BigInt::from(0x44).to_signed_bytes_le());
+        assert!(fee_protocol_attributes(LayoutProfile::PancakeswapV3, change).is_empty());
+    }
+
+    #[test]
+    fn test_pancakeswap_fee_protocol() {
+        let mut new_value = vec![0; 32];
+        new_value[28..].copy_from_slice(&[0x00, 0x01, 0x00, 0x02]);
+        let change = StorageChange {
+            key: slot(1).to_vec(),
+            old_value: vec![0; 32],
+            new_value,
+            ..Default::default()
+        };
+
+        let res = fee_protocol_attributes(LayoutProfile::PancakeswapV3, change);
+
+        assert_eq!(
+            res.into_iter()
+                .map(|attr| (attr.name, attr.value))
+                .collect::<Vec<_>>(),
+            vec![
+                ("fee_protocol0".to_string(), BigInt::from(2).to_signed_bytes_le()),
+                ("fee_protocol1".to_string(), BigInt::from(1).to_signed_bytes_le()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_observation_changes() {
+        // blockTimestamp = 0x665f2a10, tickCumulative = -2, secondsPerLiquidity = 3, initialized
+        let mut new_value = [0u8; 32];
+        new_value[0] = 1;
+        new_value[20] = 3;
+        new_value[21..28].copy_from_slice(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe]);
+        new_value[28..].copy_from_slice(&[0x66, 0x5f, 0x2a, 0x10]);
+        let mut key = slot(0);
+        key[30..].copy_from_slice(&(8u16 + 300).to_be_bytes());
+        let changes = vec![StorageChange {
+            key: key.to_vec(),
+            old_value: vec![0; 32],
+            new_value: new_value.to_vec(),
+            ..Default::default()
+        }];
+
+        let mut res = UniswapPoolStorage::new(&changes, LayoutProfile::UniswapV3.storage_layout())
+            .get_tracked_attributes()
+            .into_iter()
+            .map(|attr| (attr.name, attr.value))
+            .collect::<Vec<_>>();
+        res.sort();
+
+        assert_eq!(
+            res,
+            vec![
+                (
+                    "observations/300/block_timestamp".to_string(),
+                    BigInt::from(0x665f2a10).to_signed_bytes_le()
+                ),
+                ("observations/300/initialized".to_string(), BigInt::from(1).to_signed_bytes_le()),
+                (
+                    "observations/300/seconds_per_liquidity_cumulative_x128".to_string(),
+                    BigInt::from(3).to_signed_bytes_le()
+                ),
+                (
+                    "observations/300/tick_cumulative".to_string(),
+                    BigInt::from(-2).to_signed_bytes_le()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_crossed_ticks_changes() {
+        let layout = LayoutProfile::UniswapV3.storage_layout();
+        // The swap crosses tick 60 and only reads tick -60.
+        let tick_preimage = |tick: i32| {
+            let preimage = [left_pad_from_bigint(&BigInt::from(tick)), layout.ticks_map_slot];
+            (hex::encode(calc_map_slot(&preimage[0], &preimage[1])), hex::encode(preimage.concat()))
+        };
+        let keccak_preimages = HashMap::from([
+            tick_preimage(60),
+            tick_preimage(-60),
+            // A mapping other than `ticks`, e.g. the tick bitmap.
+            (
+                hex::encode(calc_map_slot(&[0; 32], &layout.tick_bitmap_slot)),
+                hex::encode([[0; 32], layout.tick_bitmap_slot].concat()),
+            ),
+        ]);
+        let tick_slot =
+            calc_map_slot(&left_pad_from_bigint(&BigInt::from(60)), &layout.ticks_map_slot);
+        let mut fee_growth = vec![0; 32];
+        fee_growth[31] = 7;
+        let changes = vec![StorageChange {
+            key: add_to_slot(&tick_slot, 1).to_vec(),
+            old_value: vec![0; 32],
+            new_value: fee_growth,
+            ..Default::default()
+        }];
+        let storage = UniswapPoolStorage::new(&changes, layout);
+
+        let res = storage
+            .get_crossed_ticks_changes(&keccak_preimages)
+            .into_iter()
+            .map(|attr| (attr.name, attr.value))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            res,
+            vec![(
+                "ticks/60/fee-growth-outside-0-x128".to_string(),
+                BigInt::from(7).to_signed_bytes_le()
+            )]
+        );
+        assert!(storage
+            .get_crossed_ticks_changes(&HashMap::new())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_tick_bitmap_changes() {
+        let layout = LayoutProfile::UniswapV3.storage_layout();
+        // Tick -60 with spacing 60 is compressed to -1, which is bit 255 of word -1.
+        let mut word = vec![0; 32];
+        word[0] = 0x80;
+        let changes = vec![StorageChange {
+            key: calc_map_slot(&left_pad_from_bigint(&BigInt::from(-1)), &layout.tick_bitmap_slot)
+                .to_vec(),
+            old_value: vec![0; 32],
+            new_value: word.clone(),
+            ..Default::default()
+        }];
+
+        let res = UniswapPoolStorage::new(&changes, layout)
+            .get_tick_bitmap_changes(vec![&BigInt::from(-60), &BigInt::from(600)], 60);
+
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].name, "tick_bitmap/-1");
+        assert_eq!(res[0].value, BigInt::from_unsigned_bytes_be(&word).to_signed_bytes_le());
+    }
+}
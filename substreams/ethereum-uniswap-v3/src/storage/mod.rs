@@ -1,4 +1,5 @@
 pub mod pool_storage;
+pub mod position_storage;
 
 pub mod constants;
 mod utils;
@@ -1,10 +1,15 @@
+use std::collections::{HashMap, HashSet};
+
 use crate::storage::utils;
 use tycho_substreams::prelude::{Attribute, ChangeType};
 
 use substreams::scalar::BigInt;
 use substreams_ethereum::pb::eth::v2::StorageChange;
 
-use super::{constants::TICKS_MAP_SLOT, utils::read_bytes};
+use super::{
+    constants::{StorageLayout, OBSERVATIONS_LENGTH, OBSERVATION_FIELDS, TICK_FIELDS},
+    utils::read_bytes,
+};
 
 /// `StorageLocation` is a struct that represents a specific location within a contract's storage
 /// associated with a name.
@@ -28,13 +33,132 @@ pub struct StorageLocation<'a> {
     pub signed: bool,
 }
 
+/// Checks the storage changes for modifications of the given storage locations, see
+/// `UniswapPoolStorage::get_changed_attributes`.
+pub(crate) fn get_changed_attributes(
+    storage_changes: &[StorageChange],
+    locations: Vec<&StorageLocation>,
+) -> Vec<Attribute> {
+    let mut attributes = Vec::new();
+
+    // For each storage change, check if it changes a tracked slot.
+    // If it does, add the attribute to the list of attributes
+    for change in storage_changes {
+        for storage_location in locations.iter() {
+            // Check if the change slot matches the tracked slot
+            if change.key == storage_location.slot {
+                let old_data = read_bytes(
+                    &change.old_value,
+                    storage_location.offset,
+                    storage_location.number_of_bytes,
+                );
+                let new_data = read_bytes(
+                    &change.new_value,
+                    storage_location.offset,
+                    storage_location.number_of_bytes,
+                );
+
+                // Check if there is a change in the data
+                if old_data != new_data {
+                    let value = match storage_location.signed {
+                        true => BigInt::from_signed_bytes_be(new_data),
+                        false => BigInt::from_unsigned_bytes_be(new_data),
+                    };
+                    attributes.push(Attribute {
+                        name: storage_location.name.to_string(),
+                        value: value.to_signed_bytes_le(),
+                        change: ChangeType::Update.into(),
+                    });
+                }
+            }
+        }
+    }
+
+    attributes
+}
+
 pub struct UniswapPoolStorage<'a> {
     pub storage_changes: &'a Vec<StorageChange>,
+    pub layout: &'a StorageLayout,
 }
 
 impl<'a> UniswapPoolStorage<'a> {
-    pub fn new(storage_changes: &'a Vec<StorageChange>) -> UniswapPoolStorage<'a> {
-        Self { storage_changes }
+    pub fn new(
+        storage_changes: &'a Vec<StorageChange>,
+        layout: &'a StorageLayout,
+    ) -> UniswapPoolStorage<'a> {
+        Self { storage_changes, layout }
+    }
+
+    /// Returns the changes of all slots tracked by the pool's storage layout, including the
+    /// changed oracle observations.
+    pub fn get_tracked_attributes(&self) -> Vec<Attribute> {
+        let mut attributes = self.get_changed_attributes(
+            self.layout
+                .tracked_slots
+                .iter()
+                .collect(),
+        );
+        attributes.extend(self.get_observations_changes());
+        attributes
+    }
+
+    /// Iterates over the storage changes and collects the changes of the `observations` array.
+    ///
+    /// Each changed field of an observation is returned as `observations/{index}/{field}`
+    /// attribute. Observations are written by the first swap or liquidity change of a block, on
+    /// initialization and when `increaseObservationCardinalityNext` prepares new slots.
+    pub fn get_observations_changes(&self) -> Vec<Attribute> {
+        let mut observation_slots = self
+            .storage_changes
+            .iter()
+            .filter_map(|change| {
+                let index = self.observation_index(&change.key)?;
+                Some((index, <[u8; 32]>::try_from(change.key.as_slice()).ok()?))
+            })
+            .collect::<Vec<_>>();
+        observation_slots.sort_unstable();
+        observation_slots.dedup();
+
+        // Create all the names first, the storage locations only keep references to them.
+        let names = observation_slots
+            .iter()
+            .map(|(index, _)| {
+                OBSERVATION_FIELDS
+                    .iter()
+                    .map(|(field, ..)| format!("observations/{index}/{field}"))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let storage_locs = observation_slots
+            .iter()
+            .zip(names.iter())
+            .flat_map(|((_, slot), names)| {
+                OBSERVATION_FIELDS
+                    .iter()
+                    .zip(names.iter())
+                    .map(|((_, offset, number_of_bytes, signed), name)| StorageLocation {
+                        name,
+                        slot: *slot,
+                        offset: *offset,
+                        number_of_bytes: *number_of_bytes,
+                        signed: *signed,
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        self.get_changed_attributes(storage_locs.iter().collect())
+    }
+
+    /// Returns the index within the `observations` array if `key` is one of its slots.
+    fn observation_index(&self, key: &[u8]) -> Option<u32> {
+        if key.len() != 32 || key[..28].iter().any(|byte| *byte != 0) {
+            return None;
+        }
+        let slot = u32::from_be_bytes(key[28..].try_into().ok()?);
+        slot.checked_sub(self.layout.observations_slot.into())
+            .filter(|index| *index < OBSERVATIONS_LENGTH)
     }
 
     /// Iterates through storage changes and checks for modifications in the provided list of
@@ -50,42 +174,7 @@ impl<'a> UniswapPoolStorage<'a> {
     ///     `Vec<Attribute>`: A vector containing Attributes for each change detected in the tracked
     /// slots. Returns an empty vector if no changes are detected.
     pub fn get_changed_attributes(&self, locations: Vec<&StorageLocation>) -> Vec<Attribute> {
-        let mut attributes = Vec::new();
-
-        // For each storage change, check if it changes a tracked slot.
-        // If it does, add the attribute to the list of attributes
-        for change in self.storage_changes {
-            for storage_location in locations.iter() {
-                // Check if the change slot matches the tracked slot
-                if change.key == storage_location.slot {
-                    let old_data = read_bytes(
-                        &change.old_value,
-                        storage_location.offset,
-                        storage_location.number_of_bytes,
-                    );
-                    let new_data = read_bytes(
-                        &change.new_value,
-                        storage_location.offset,
-                        storage_location.number_of_bytes,
-                    );
-
-                    // Check if there is a change in the data
-                    if old_data != new_data {
-                        let value = match storage_location.signed {
-                            true => BigInt::from_signed_bytes_be(new_data),
-                            false => BigInt::from_unsigned_bytes_be(new_data),
-                        };
-                        attributes.push(Attribute {
-                            name: storage_location.name.to_string(),
-                            value: value.to_signed_bytes_le(),
-                            change: ChangeType::Update.into(),
-                        });
-                    }
-                }
-            }
-        }
-
-        attributes
+        get_changed_attributes(self.storage_changes, locations)
     }
 
     /// Iterates over a list of tick indexes and checks for modifications in the list of
@@ -99,31 +188,126 @@ impl<'a> UniswapPoolStorage<'a> {
     ///     `Vec<Attribute>`: A vector containing Attributes for each change detected. Returns an
     /// empty vector if no changes are detected.
     ///
-    /// Note: Currently, we only track the net-liquidity attribute for each tick.
+    /// Each changed field of a tick is returned as `ticks/{index}/{field}`, see `TICK_FIELDS`.
     pub fn get_ticks_changes(&self, ticks_idx: Vec<&BigInt>) -> Vec<Attribute> {
+        // First, create all the names. We need this to keep the references to the names alive
+        // until we call `get_changed_attributes()`
+        let tick_names = ticks_idx
+            .iter()
+            .map(|tick_idx| {
+                TICK_FIELDS
+                    .iter()
+                    .map(|(field, ..)| format!("ticks/{}/{}", tick_idx, field))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // Then, iterate over ticks_idx and tick_names simultaneously
         let mut storage_locs = Vec::new();
-        let mut tick_names = Vec::new();
+        for (tick_idx, names) in ticks_idx.iter().zip(tick_names.iter()) {
+            let tick_slot = utils::calc_map_slot(
+                &utils::left_pad_from_bigint(tick_idx),
+                &self.layout.ticks_map_slot,
+            );
 
-        // First, create all the names and push them into tick_names.
-        // We need this to keep the references to the names alive until we call
-        // `get_changed_attributes()`
-        for tick_idx in ticks_idx.iter() {
-            tick_names.push(format!("ticks/{}/net-liquidity", tick_idx));
+            for ((_, slot_offset, offset, number_of_bytes, signed), name) in
+                TICK_FIELDS.iter().zip(names.iter())
+            {
+                storage_locs.push(StorageLocation {
+                    name,
+                    slot: utils::add_to_slot(&tick_slot, *slot_offset),
+                    offset: *offset,
+                    number_of_bytes: *number_of_bytes,
+                    signed: *signed,
+                });
+            }
         }
 
-        // Then, iterate over ticks_idx and tick_names simultaneously
-        for (tick_idx, tick_name) in ticks_idx.iter().zip(tick_names.iter()) {
-            let tick_slot =
-                utils::calc_map_slot(&utils::left_pad_from_bigint(tick_idx), &TICKS_MAP_SLOT);
-
-            storage_locs.push(StorageLocation {
-                name: tick_name,
-                slot: tick_slot,
-                offset: 16,
-                number_of_bytes: 16,
-                signed: true,
-            });
+        self.get_changed_attributes(storage_locs.iter().collect())
+    }
+
+    /// Returns the changes of the ticks crossed by a swap.
+    ///
+    /// Swaps don't announce the ticks they cross, but crossing a tick flips its fee growth
+    /// outside. To do so, the pool hashes the tick index with the `ticks` mapping slot, the crossed
+    /// ticks are therefore recovered from the call's keccak preimages of that mapping whose fee
+    /// growth slots changed.
+    pub fn get_crossed_ticks_changes(
+        &self,
+        keccak_preimages: &HashMap<String, String>,
+    ) -> Vec<Attribute> {
+        let changed_slots = self
+            .storage_changes
+            .iter()
+            .map(|change| change.key.as_slice())
+            .collect::<HashSet<_>>();
+        let mut crossed_ticks = keccak_preimages
+            .iter()
+            .filter_map(|(hash, preimage)| {
+                let preimage = hex::decode(preimage.trim_start_matches("0x")).ok()?;
+                if preimage.len() != 64 || preimage[32..] != self.layout.ticks_map_slot {
+                    return None;
+                }
+                let tick_slot: [u8; 32] = hex::decode(hash.trim_start_matches("0x"))
+                    .ok()?
+                    .try_into()
+                    .ok()?;
+                [1, 2]
+                    .iter()
+                    .any(|slot_offset| {
+                        changed_slots
+                            .contains(utils::add_to_slot(&tick_slot, *slot_offset).as_slice())
+                    })
+                    .then(|| BigInt::from_signed_bytes_be(&preimage[..32]))
+            })
+            .collect::<Vec<_>>();
+        crossed_ticks.sort_unstable();
+        crossed_ticks.dedup();
+
+        self.get_ticks_changes(crossed_ticks.iter().collect())
+    }
+
+    /// Returns the changes of the tick bitmap words containing the given ticks.
+    ///
+    /// Words are returned as `tick_bitmap/{word_position}` attributes.
+    pub fn get_tick_bitmap_changes(
+        &self,
+        ticks_idx: Vec<&BigInt>,
+        tick_spacing: i32,
+    ) -> Vec<Attribute> {
+        if tick_spacing <= 0 {
+            return vec![];
         }
+        let mut word_positions = ticks_idx
+            .iter()
+            .map(|tick_idx| {
+                tick_idx
+                    .to_i32()
+                    .div_euclid(tick_spacing) >>
+                    8
+            })
+            .collect::<Vec<_>>();
+        word_positions.sort_unstable();
+        word_positions.dedup();
+
+        let names = word_positions
+            .iter()
+            .map(|word_position| format!("tick_bitmap/{}", word_position))
+            .collect::<Vec<_>>();
+        let storage_locs = word_positions
+            .iter()
+            .zip(names.iter())
+            .map(|(word_position, name)| StorageLocation {
+                name,
+                slot: utils::calc_map_slot(
+                    &utils::left_pad_from_bigint(&BigInt::from(*word_position)),
+                    &self.layout.tick_bitmap_slot,
+                ),
+                offset: 0,
+                number_of_bytes: 32,
+                signed: false,
+            })
+            .collect::<Vec<_>>();
 
         self.get_changed_attributes(storage_locs.iter().collect())
     }
@@ -0,0 +1,89 @@
+use substreams::scalar::BigInt;
+use substreams_ethereum::pb::eth::v2::StorageChange;
+use tycho_substreams::prelude::Attribute;
+
+use super::{
+    constants::POSITION_FIELDS,
+    pool_storage::{get_changed_attributes, StorageLocation},
+    utils,
+};
+
+/// Reads positions from the storage changes of a `NonfungiblePositionManager`.
+pub struct PositionManagerStorage<'a> {
+    pub storage_changes: &'a [StorageChange],
+    /// Base slot of the `_positions` mapping.
+    pub positions_slot: [u8; 32],
+}
+
+impl<'a> PositionManagerStorage<'a> {
+    pub fn new(storage_changes: &'a [StorageChange], positions_slot: u8) -> Self {
+        let mut slot = [0u8; 32];
+        slot[31] = positions_slot;
+        Self { storage_changes, positions_slot: slot }
+    }
+
+    /// Returns the changed fields of a position as `positions/{token_id}/{field}` attributes, see
+    /// `POSITION_FIELDS`.
+    pub fn get_position_changes(&self, token_id: &BigInt) -> Vec<Attribute> {
+        let position_slot =
+            utils::calc_map_slot(&utils::left_pad_from_bigint(token_id), &self.positions_slot);
+        let names = POSITION_FIELDS
+            .iter()
+            .map(|(field, ..)| format!("positions/{}/{}", token_id, field))
+            .collect::<Vec<_>>();
+        let storage_locs = POSITION_FIELDS
+            .iter()
+            .zip(names.iter())
+            .map(|((_, slot_offset, offset, number_of_bytes, signed), name)| StorageLocation {
+                name,
+                slot: utils::add_to_slot(&position_slot, *slot_offset),
+                offset: *offset,
+                number_of_bytes: *number_of_bytes,
+                signed: *signed,
+            })
+            .collect::<Vec<_>>();
+
+        get_changed_attributes(self.storage_changes, storage_locs.iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_changes() {
+        let storage = PositionManagerStorage::new(&[], 12);
+        let position_slot = utils::calc_map_slot(
+            &utils::left_pad_from_bigint(&BigInt::from(42)),
+            &storage.positions_slot,
+        );
+        // tickUpper = 60, tickLower = -60, liquidity = 1000
+        let mut new_value = vec![0; 32];
+        new_value[14..16].copy_from_slice(&1000u16.to_be_bytes());
+        new_value[16..19].copy_from_slice(&60i32.to_be_bytes()[1..]);
+        new_value[19..22].copy_from_slice(&(-60i32).to_be_bytes()[1..]);
+        new_value[31] = 1;
+        let changes = vec![StorageChange {
+            key: utils::add_to_slot(&position_slot, 1).to_vec(),
+            old_value: vec![0; 32],
+            new_value,
+            ..Default::default()
+        }];
+
+        let res = PositionManagerStorage::new(&changes, 12)
+            .get_position_changes(&BigInt::from(42))
+            .into_iter()
+            .map(|attr| (attr.name, attr.value))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            res,
+            vec![
+                ("positions/42/tick_lower".to_string(), BigInt::from(-60).to_signed_bytes_le()),
+                ("positions/42/tick_upper".to_string(), BigInt::from(60).to_signed_bytes_le()),
+                ("positions/42/liquidity".to_string(), BigInt::from(1000).to_signed_bytes_le()),
+            ]
+        );
+    }
+}
@@ -10,6 +10,21 @@ pub fn calc_map_slot(map_index: &[u8; 32], base_slot: &[u8; 32]) -> [u8; 32] {
     output
 }
 
+/// Returns the slot `n` slots after `slot`, used to access members of structs.
+pub fn add_to_slot(slot: &[u8; 32], n: u8) -> [u8; 32] {
+    let mut result = *slot;
+    let mut carry = n as u16;
+    for byte in result.iter_mut().rev() {
+        if carry == 0 {
+            break;
+        }
+        let sum = *byte as u16 + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+    }
+    result
+}
+
 pub fn left_pad_from_bigint(input: &BigInt) -> [u8; 32] {
     if input.lt(&BigInt::zero()) {
         return left_pad(&input.to_signed_bytes_be(), 255);
@@ -65,7 +80,7 @@ pub fn read_bytes(buf: &[u8], offset: usize, number_of_bytes: usize) -> &[u8] {
 
 #[cfg(test)]
 mod tests {
-    use crate::storage::utils::{left_pad, read_bytes};
+    use crate::storage::utils::{add_to_slot, left_pad, read_bytes};
     use hex_literal::hex;
     use std::{fmt::Write, num::ParseIntError};
 
@@ -94,6 +109,15 @@ mod tests {
         let _ = left_pad(&input, 0);
     }
 
+    #[test]
+    fn add_to_slot_with_carry() {
+        let slot = hex!("00000000000000000000000000000000000000000000000000000000000001ff");
+        assert_eq!(
+            add_to_slot(&slot, 3),
+            hex!("0000000000000000000000000000000000000000000000000000000000000202")
+        )
+    }
+
     #[test]
     #[should_panic]
     fn read_bytes_buf_too_small() {
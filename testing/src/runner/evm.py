@@ -17,6 +17,20 @@ erc20_abi = [
     }
 ]
 
+vault_abi = [
+    {
+        "constant": True,
+        "inputs": [{"name": "poolId", "type": "bytes32"}],
+        "name": "getPoolTokens",
+        "outputs": [
+            {"name": "tokens", "type": "address[]"},
+            {"name": "balances", "type": "uint256[]"},
+            {"name": "lastChangeBlock", "type": "uint256"},
+        ],
+        "type": "function",
+    }
+]
+
 
 def get_token_balance(token_address, wallet_address, block_number):
     rpc_url = os.getenv("RPC_URL")
@@ -45,6 +59,32 @@ def get_token_balance(token_address, wallet_address, block_number):
     return balance
 
 
+def get_vault_pool_balance(vault_address, pool_id, token_address, block_number):
+    """Balance of a token in a pool of a Balancer V2 style vault, which holds the tokens of all
+    its pools."""
+    rpc_url = os.getenv("RPC_URL")
+
+    if rpc_url is None:
+        raise EnvironmentError("RPC_URL environment variable not set")
+
+    web3 = Web3(Web3.HTTPProvider(rpc_url))
+
+    if not web3.isConnected():
+        raise ConnectionError("Failed to connect to the Ethereum node")
+
+    contract = web3.eth.contract(
+        address=Web3.toChecksumAddress(vault_address), abi=vault_abi
+    )
+    tokens, balances, _ = contract.functions.getPoolTokens(pool_id).call(
+        block_identifier=block_number
+    )
+    for token, balance in zip(tokens, balances):
+        if Web3.toChecksumAddress(token) == Web3.toChecksumAddress(token_address):
+            return balance
+
+    return 0
+
+
 def get_block_header(block_number):
     rpc_url = os.getenv("RPC_URL")
 
@@ -81,6 +81,16 @@ class ProtocolComponentWithTestConfig(ProtocolComponentExpectation):
         False,
         description="Flag indicating whether to skip simulation for this component",
     )
+    expected_state: Optional[Dict[str, HexBytes]] = Field(
+        default_factory=dict,
+        description="State attributes the component is expected to hold at the stop block",
+    )
+
+    @validator("expected_state", pre=True, always=True)
+    def convert_expected_state_to_hexbytes(cls, v):
+        if v:
+            return {k: v[k] if isinstance(v[k], HexBytes) else HexBytes(v[k].lower()) for k in v}
+        return {}
 
 
 class IntegrationTest(BaseModel):
@@ -95,6 +105,9 @@ class IntegrationTest(BaseModel):
     expected_components: List[ProtocolComponentWithTestConfig] = Field(
         ..., description="List of protocol components expected in the indexed state"
     )
+    skip_balance_check: Optional[bool] = Field(
+        None, description="Overrides the global skip_balance_check for this test"
+    )
 
 
 class IntegrationTestsConfig(BaseModel):
@@ -119,6 +132,11 @@ class IntegrationTestsConfig(BaseModel):
     skip_balance_check: bool = Field(
         ..., description="Flag to skip balance check for all tests"
     )
+    balance_vault: Optional[str] = Field(
+        None,
+        description="Vault holding the tokens of all components, whose balances are then checked "
+        "through its getPoolTokens instead of the token balances of the components",
+    )
     protocol_type_names: List[str] = Field(
         ..., description="List of protocol type names for the tested protocol"
     )
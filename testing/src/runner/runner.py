@@ -34,7 +34,7 @@ from models import (
     ProtocolComponentExpectation,
 )
 from adapter_builder import AdapterContractBuilder
-from evm import get_token_balance, get_block_header
+from evm import get_token_balance, get_vault_pool_balance, get_block_header
 from tycho import TychoRunner
 from utils import build_snapshot_message, token_factory
 
@@ -109,8 +109,16 @@ class TestRunner:
                 test.initialized_accounts or [],
             )
 
+            skip_balance_check = (
+                self.config.skip_balance_check
+                if test.skip_balance_check is None
+                else test.skip_balance_check
+            )
             result = self.tycho_runner.run_with_rpc_server(
-                self.validate_state, test.expected_components, test.stop_block
+                self.validate_state,
+                test.expected_components,
+                test.stop_block,
+                skip_balance_check,
             )
 
             if result.success:
@@ -131,6 +139,7 @@ class TestRunner:
         self,
         expected_components: List[ProtocolComponentWithTestConfig],
         stop_block: int,
+        skip_balance_check: bool,
     ) -> TestResult:
         """Validate the current protocol state against the expected state."""
         protocol_components = self.tycho_rpc_client.get_protocol_components(
@@ -158,6 +167,19 @@ class TestRunner:
                 if diff is not None:
                     return TestResult.Failed(diff)
 
+                state = next(
+                    (s for s in protocol_states if s.component_id.lower() == comp_id),
+                    None,
+                )
+                attributes = state.attributes if state else {}
+                for name, expected_value in expected_component.expected_state.items():
+                    value = attributes.get(name)
+                    if value is None or HexBytes(value) != expected_value:
+                        return TestResult.Failed(
+                            f"State attribute '{name}' mismatch for {comp_id}: expected "
+                            f"{expected_value.hex()}, got {HexBytes(value).hex() if value is not None else None}"
+                        )
+
             token_balances: dict[str, dict[HexBytes, int]] = defaultdict(dict)
             for component in protocol_components:
                 comp_id = component.id.lower()
@@ -177,8 +199,17 @@ class TestRunner:
                     tycho_balance = int(balance_hex)
                     token_balances[comp_id][token] = tycho_balance
 
-                    if not self.config.skip_balance_check:
-                        node_balance = get_token_balance(token, comp_id, stop_block)
+                    if not skip_balance_check:
+                        if self.config.balance_vault is None:
+                            node_balance = get_token_balance(token, comp_id, stop_block)
+                        elif token == HexBytes(comp_id[:42]):
+                            # The vault holds the pool's own BPT, which is not part of
+                            # the pool's balances.
+                            continue
+                        else:
+                            node_balance = get_vault_pool_balance(
+                                self.config.balance_vault, comp_id, token, stop_block
+                            )
                         if node_balance != tycho_balance:
                             return TestResult.Failed(
                                 f"Balance mismatch for {comp_id}:{token} at block {stop_block}: got {node_balance} "